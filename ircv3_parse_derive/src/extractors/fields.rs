@@ -16,7 +16,7 @@ pub fn extract_named_fields(
         },
         _ => Err(Error::new_spanned(
             &input.ident,
-            format!("{} only supports structs", name.into()),
+            format!("{} only supports structs and enums", name.into()),
         )),
     }
 }
@@ -4,6 +4,7 @@ mod param;
 mod source;
 mod tag;
 mod trailing;
+mod value;
 
 pub use command::CommandField;
 pub use fields::extract_named_fields;
@@ -11,3 +12,4 @@ pub use param::ParamField;
 pub use source::SourceField;
 pub use tag::Tag;
 pub use trailing::TrailingField;
+pub use value::{expand_from_value, expand_to_value};
@@ -2,6 +2,7 @@ use quote::{quote, ToTokens};
 use syn::{Error, Result};
 use syn::{Field, Ident, LitInt, LitStr};
 
+use crate::extractors::{expand_from_value, expand_to_value};
 use crate::ser::SerializationBuilder;
 use crate::PARAM;
 use crate::{error_msg, TypeKind};
@@ -44,6 +45,23 @@ impl ParamField {
             Option(inner) if matches!(TypeKind::classify(inner), String) => {
                 Ok(quote! { #field_name: #params.map(|s| s.to_string()) })
             }
+            Option(inner) if matches!(TypeKind::classify(inner), Other) => {
+                let from_value = expand_from_value(inner, field_name, quote! { value });
+                Ok(quote! {
+                    #field_name: match #params {
+                        Some(value) => Some(#from_value),
+                        None => None,
+                    }
+                })
+            }
+            Other => {
+                let from_value = expand_from_value(
+                    &field.ty,
+                    field_name,
+                    quote! { #params.ok_or(ircv3_parse::DeError::missing_param_field(stringify!(#field_name), #idx))? },
+                );
+                Ok(quote! { #field_name: #from_value })
+            }
             _ => Err(Error::new_spanned(
                 field,
                 error_msg::unsupported_type(PARAM, field_name, field.ty.to_token_stream()),
@@ -59,19 +77,21 @@ impl ParamField {
     ) -> Result<()> {
         use TypeKind::*;
 
+        let access = builder.access(field_name);
+
         match TypeKind::classify(&field.ty) {
             Str => {
-                builder.params_push(quote! { params.push(self.#field_name)?; });
+                builder.params_push(quote! { params.push(#access)?; });
                 Ok(())
             }
             String => {
-                builder.params_push(quote! { params.push(self.#field_name.as_ref())?; });
+                builder.params_push(quote! { params.push(#access.as_ref())?; });
                 Ok(())
             }
             Option(inner) => match TypeKind::classify(inner) {
                 Str => {
                     builder.params_push(quote! {
-                        if let Some(p) = self.#field_name {
+                        if let Some(p) = #access {
                             params.push(p)?;
                         }
                     });
@@ -79,25 +99,27 @@ impl ParamField {
                 }
                 String => {
                     builder.params_push(quote! {
-                        if let Some(p) = self.#field_name {
+                        if let Some(p) = #access {
                             params.push(p.as_ref())?;
                         }
                     });
                     Ok(())
                 }
                 _ => {
-                    builder.custom_params(quote! {
-                        if let Some(p) = self.#field_name {
-                            p.to_message(serialize)?;
+                    let to_value = expand_to_value(quote! { p }, quote! { param(serialize) });
+                    builder.param_field(quote! {
+                        if let Some(p) = &#access {
+                            #to_value
                         }
                     });
                     Ok(())
                 }
             },
             _ => {
-                builder.custom_params(quote! {
-                    self.#field_name.to_message(serialize)?;
-                });
+                builder.param_field(expand_to_value(
+                    quote! { &#access },
+                    quote! { param(serialize) },
+                ));
                 Ok(())
             }
         }
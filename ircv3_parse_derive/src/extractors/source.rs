@@ -88,61 +88,62 @@ impl SourceField {
     ) -> Result<()> {
         use TypeKind::*;
 
+        let access = builder.access(field_name);
+
         match self {
             Self::Name => match TypeKind::classify(&field.ty) {
                 Str => {
-                    builder.set_source_name(
-                        quote! { let mut source = serialize.source(self.#field_name)?; },
-                    );
+                    builder
+                        .set_source_name(quote! { let mut source = serialize.source(#access)?; });
                     Ok(())
                 }
                 String => {
                     builder.set_source_name(
-                        quote! { let mut source = serialize.source(self.#field_name.as_ref())?; },
+                        quote! { let mut source = serialize.source(#access.as_ref())?; },
                     );
                     Ok(())
                 }
                 Option(inner) => match TypeKind::classify(inner) {
                     Str => {
                         builder.set_source_name(quote! {
-                            if let Some(field) = self.#field_name{
-                                let mut source = serialize.source(self.#field_name)?;
+                            if let Some(field) = #access{
+                                let mut source = serialize.source(#access)?;
                             }
                         });
                         Ok(())
                     }
                     String => {
                         builder.set_source_name(quote! {
-                            if let Some(field) = self.#field_name{
-                                let mut source = serialize.source(self.#field_name.as_ref())?;
+                            if let Some(field) = #access{
+                                let mut source = serialize.source(#access.as_ref())?;
                             }
                         });
                         Ok(())
                     }
                     _ => {
-                        builder.custom_source(quote! {self.#field_name.to_message(serialize)?;});
+                        builder.custom_source(quote! {#access.to_message(serialize)?;});
                         Ok(())
                     }
                 },
                 _ => {
-                    builder.custom_source(quote! {self.#field_name.to_message(serialize)?;});
+                    builder.custom_source(quote! {#access.to_message(serialize)?;});
                     Ok(())
                 }
             },
             Self::User => match TypeKind::classify(&field.ty) {
                 Str => {
-                    builder.set_source_user(quote! {source.user(self.#field_name)?;});
+                    builder.set_source_user(quote! {source.user(#access)?;});
                     Ok(())
                 }
                 String => {
-                    builder.set_source_user(quote! {source.user(self.#field_name.as_ref())?;});
+                    builder.set_source_user(quote! {source.user(#access.as_ref())?;});
                     Ok(())
                 }
 
                 Option(inner) => match TypeKind::classify(inner) {
                     Str => {
                         builder.set_source_user(quote! {
-                            if let Some(field) = self.#field_name{
+                            if let Some(field) = #access{
                                 source.user(field)?;
                             }
                         });
@@ -150,7 +151,7 @@ impl SourceField {
                     }
                     String => {
                         builder.set_source_user(quote! {
-                            if let Some(field) = self.#field_name{
+                            if let Some(field) = #access{
                                 source.user(field.as_ref())?;
                             }
                         });
@@ -158,7 +159,7 @@ impl SourceField {
                     }
                     _ => {
                         builder.custom_source(quote! {
-                            if let Some(field) = self.#field_name{
+                            if let Some(field) = #access{
                                 field.to_message(serialize)?;
                             }
                         });
@@ -166,23 +167,23 @@ impl SourceField {
                     }
                 },
                 _ => {
-                    builder.set_source_user(quote! {source.user(self.#field_name)?;});
+                    builder.set_source_user(quote! {source.user(#access)?;});
                     Ok(())
                 }
             },
             Self::Host => match TypeKind::classify(&field.ty) {
                 Str => {
-                    builder.set_source_host(quote! {source.host(self.#field_name)?;});
+                    builder.set_source_host(quote! {source.host(#access)?;});
                     Ok(())
                 }
                 String => {
-                    builder.set_source_host(quote! {source.host(self.#field_name.as_ref())?;});
+                    builder.set_source_host(quote! {source.host(#access.as_ref())?;});
                     Ok(())
                 }
                 Option(inner) => match TypeKind::classify(inner) {
                     Str => {
                         builder.set_source_host(quote! {
-                            if let Some(field) = self.#field_name{
+                            if let Some(field) = #access{
                                 source.host(field)?;
                             }
                         });
@@ -190,7 +191,7 @@ impl SourceField {
                     }
                     String => {
                         builder.set_source_host(quote! {
-                            if let Some(field) = self.#field_name{
+                            if let Some(field) = #access{
                                 source.host(field.as_ref())?;
                             }
                         });
@@ -198,7 +199,7 @@ impl SourceField {
                     }
                     _ => {
                         builder.custom_source(quote! {
-                            if let Some(field) = self.#field_name{
+                            if let Some(field) = #access{
                                 field.to_message(serialize)?;
                             }
                         });
@@ -206,7 +207,7 @@ impl SourceField {
                     }
                 },
                 _ => {
-                    builder.set_source_host(quote! {source.host(self.#field_name)?;});
+                    builder.set_source_host(quote! {source.host(#access)?;});
                     Ok(())
                 }
             },
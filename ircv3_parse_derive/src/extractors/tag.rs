@@ -2,12 +2,14 @@ use quote::{quote, ToTokens};
 use syn::{Error, Result};
 use syn::{Field, Ident, LitStr};
 
+use crate::extractors::{expand_from_value, expand_to_value};
 use crate::ser::SerializationBuilder;
 use crate::TAG;
 use crate::{error_msg, TypeKind};
 
 pub enum Tag {
     Value(LitStr),
+    Escaped(LitStr),
     Flag(LitStr),
 }
 
@@ -41,11 +43,43 @@ impl Tag {
                 Option(inner) if matches!(TypeKind::classify(inner), String) => {
                     Ok(quote! { #field_name: #tags.map(|s| s.to_string()) })
                 }
+                Option(inner) if matches!(TypeKind::classify(inner), Other) => {
+                    let from_value =
+                        expand_from_value(inner, field_name, quote! { value.as_str() });
+                    Ok(quote! {
+                        #field_name: match #tags {
+                            Some(value) => Some(#from_value),
+                            None => None,
+                        }
+                    })
+                }
+                Other => {
+                    let from_value =
+                        expand_from_value(&field.ty, field_name, quote! { value.as_str() });
+                    Ok(quote! {
+                        #field_name: {
+                            let value = #tags.ok_or(ircv3_parse::DeError::missing_tag(stringify!(#field_name), #key))?;
+                            #from_value
+                        }
+                    })
+                }
                 _ => Err(Error::new_spanned(
                     field,
                     error_msg::unsupported_type(TAG, field_name, field.ty.to_token_stream()),
                 )),
             },
+            Self::Escaped(key) => match TypeKind::classify(&field.ty) {
+                String => Ok(
+                    quote! { #field_name: ircv3_parse::unescape(#tags.ok_or(ircv3_parse::DeError::missing_tag(stringify!(#field_name), #key))?.as_str()) },
+                ),
+                Option(inner) if matches!(TypeKind::classify(inner), String) => {
+                    Ok(quote! { #field_name: #tags.map(|s| ircv3_parse::unescape(s.as_str())) })
+                }
+                _ => Err(Error::new_spanned(
+                    &field.ty,
+                    "escaped tag field must be of type String or Option<String>",
+                )),
+            },
             Self::Flag(key) => match TypeKind::classify(&field.ty) {
                 Bool => Ok(quote! { #field_name: tags.get_flag(#key) }),
                 _ => Err(Error::new_spanned(
@@ -64,45 +98,72 @@ impl Tag {
     ) -> Result<()> {
         use TypeKind::*;
 
+        let access = builder.access(field_name);
+
         match self {
             Self::Value(key) => match TypeKind::classify(&field.ty) {
                 Str => {
-                    builder.tag(quote! { tags.tag(&#key, Some(self.#field_name))?; });
+                    builder.tag(quote! { tags.tag(&#key, Some(#access))?; });
                     Ok(())
                 }
                 String => {
-                    builder.tag(quote! { tags.tag(&#key, Some(self.#field_name.as_ref()))?; });
+                    builder.tag(quote! { tags.tag(&#key, Some(#access.as_ref()))?; });
                     Ok(())
                 }
                 Option(inner) => match TypeKind::classify(inner) {
                     Str => {
-                        builder.tag(quote! { tags.tag(&#key, self.#field_name)?; });
+                        builder.tag(quote! { tags.tag(&#key, #access)?; });
                         Ok(())
                     }
                     String => {
-                        builder.tag(quote! { tags.tag(&#key, self.#field_name.as_deref())?; });
+                        builder.tag(quote! { tags.tag(&#key, #access.as_deref())?; });
                         Ok(())
                     }
                     _ => {
+                        let to_value =
+                            expand_to_value(quote! { value }, quote! { tag(#key, serialize) });
                         builder.custom_tag(quote! {
-                            if let Some(field) = self.#field_name {
-                                field.to_message(serialize)?;
+                            if let Some(value) = &#access {
+                                #to_value
                             }
                         });
                         Ok(())
                     }
                 },
                 _ => {
-                    builder.custom_tag(quote! {
-                            self.#field_name.to_message(serialize)?;
-                    });
+                    builder.custom_tag(expand_to_value(
+                        quote! { &#access },
+                        quote! { tag(#key, serialize) },
+                    ));
                     Ok(())
                 }
             },
+            Self::Escaped(key) => match TypeKind::classify(&field.ty) {
+                Str => {
+                    builder.tag(quote! { tags.tag_escaped(&#key, Some(#access))?; });
+                    Ok(())
+                }
+                String => {
+                    builder.tag(quote! { tags.tag_escaped(&#key, Some(#access.as_ref()))?; });
+                    Ok(())
+                }
+                Option(inner) if matches!(TypeKind::classify(inner), Str) => {
+                    builder.tag(quote! { tags.tag_escaped(&#key, #access)?; });
+                    Ok(())
+                }
+                Option(inner) if matches!(TypeKind::classify(inner), String) => {
+                    builder.tag(quote! { tags.tag_escaped(&#key, #access.as_deref())?; });
+                    Ok(())
+                }
+                _ => Err(Error::new_spanned(
+                    &field.ty,
+                    "escaped tag field must be a string or an optional string",
+                )),
+            },
             Self::Flag(key) => match TypeKind::classify(&field.ty) {
                 Bool => {
                     builder.tag(quote! {
-                        if self.#field_name {
+                        if #access {
                             tags.flag(&#key)?;
                         }
                     });
@@ -110,7 +171,7 @@ impl Tag {
                 }
                 _ => {
                     builder.custom_tag(quote! {
-                        self.#field_name.to_message(serialize)?;
+                        #access.to_message(serialize)?;
                     });
                     Ok(())
                 }
@@ -120,7 +181,7 @@ impl Tag {
 
     fn expand_tag(&self) -> proc_macro2::TokenStream {
         match self {
-            Self::Value(key) => quote! { tags.get(#key) },
+            Self::Value(key) | Self::Escaped(key) => quote! { tags.get(#key) },
             Self::Flag(key) => quote! { tags.get_flag(#key) },
         }
     }
@@ -128,6 +189,9 @@ impl Tag {
     fn expand_tag_with(&self) -> proc_macro2::TokenStream {
         match self {
             Self::Value(key) => quote! { tags.get(#key).map(|s| s.as_str()) },
+            Self::Escaped(key) => {
+                quote! { tags.get(#key).map(|s| ircv3_parse::unescape(s.as_str())) }
+            }
             Self::Flag(key) => quote! { tags.get_flag(#key) },
         }
     }
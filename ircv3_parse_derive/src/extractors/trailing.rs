@@ -2,6 +2,7 @@ use quote::{quote, ToTokens};
 use syn::{Error, Result};
 use syn::{Ident, LitStr};
 
+use crate::extractors::{expand_from_value, expand_to_value};
 use crate::ser::SerializationBuilder;
 use crate::TRAILING;
 use crate::{error_msg, TypeKind};
@@ -22,6 +23,20 @@ impl TrailingField {
         match TypeKind::classify(&field.ty) {
             TypeKind::Str => Ok(quote! { #field_name: params.trailing.as_str() }),
             TypeKind::String => Ok(quote! { #field_name: params.trailing.to_string() }),
+            TypeKind::Option(inner) if matches!(TypeKind::classify(inner), TypeKind::Other) => {
+                let from_value = expand_from_value(inner, field_name, quote! { value });
+                Ok(quote! {
+                    #field_name: match params.trailing.raw() {
+                        Some(value) => Some(#from_value),
+                        None => None,
+                    }
+                })
+            }
+            TypeKind::Other => {
+                let from_value =
+                    expand_from_value(&field.ty, field_name, quote! { params.trailing.as_str() });
+                Ok(quote! { #field_name: #from_value })
+            }
             _ => Err(Error::new_spanned(
                 field,
                 error_msg::unsupported_type(TRAILING, field_name, field.ty.to_token_stream()),
@@ -36,19 +51,21 @@ impl TrailingField {
     ) -> Result<()> {
         use TypeKind::*;
 
+        let access = builder.access(field_name);
+
         match TypeKind::classify(&field.ty) {
             Str => {
-                builder.set_trailing(quote! { serialize.trailing(self.#field_name)?; });
+                builder.set_trailing(quote! { serialize.trailing(#access)?; });
                 Ok(())
             }
             String => {
-                builder.set_trailing(quote! { serialize.trailing(self.#field_name.as_ref())?; });
+                builder.set_trailing(quote! { serialize.trailing(#access.as_ref())?; });
                 Ok(())
             }
             Option(inner) => match TypeKind::classify(inner) {
                 Str => {
                     builder.set_trailing(quote! {
-                        if let Some(t) = self.#field_name{
+                        if let Some(t) = #access{
                             serialize.trailing(t)?;
                         }
                     });
@@ -56,23 +73,27 @@ impl TrailingField {
                 }
                 String => {
                     builder.set_trailing(quote! {
-                        if let Some(t) = self.#field_name{
+                        if let Some(t) = #access{
                             serialize.trailing(t.as_ref())?;
                         }
                     });
                     Ok(())
                 }
                 _ => {
+                    let to_value = expand_to_value(quote! { t }, quote! { trailing(serialize) });
                     builder.custom_trailing(quote! {
-                        if let Some(t) = self.#field_name{
-                            t.to_message(serialize)?;
+                        if let Some(t) = &#access {
+                            #to_value
                         }
                     });
                     Ok(())
                 }
             },
             _ => {
-                builder.custom_trailing(quote! { self.#field_name.to_message(serialize)?; });
+                builder.custom_trailing(expand_to_value(
+                    quote! { &#access },
+                    quote! { trailing(serialize) },
+                ));
                 Ok(())
             }
         }
@@ -0,0 +1,22 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Type};
+
+/// Converts the `&str` produced by `raw` into `ty` through `FromValue`, which covers every
+/// `FromStr` type.
+pub fn expand_from_value(ty: &Type, field_name: &Ident, raw: TokenStream) -> TokenStream {
+    quote! {{
+        let raw: &str = #raw;
+        <#ty as ircv3_parse::message::de::FromValue>::from_value(raw).map_err(|e| {
+            ircv3_parse::DeError::invalid_field_value(stringify!(#field_name), raw, e)
+        })?
+    }}
+}
+
+/// Calls `method` on a reference to the field value, preferring `ToMessage` over `Display`.
+pub fn expand_to_value(value: TokenStream, method: TokenStream) -> TokenStream {
+    quote! {{
+        use ircv3_parse::message::ser::{DisplayField as _, NestedField as _};
+        (&&ircv3_parse::message::ser::Field(#value)).#method?;
+    }}
+}
@@ -1,5 +1,6 @@
 use proc_macro2::Span;
 use quote::{quote, ToTokens};
+use syn::spanned::Spanned;
 use syn::token::Eq;
 use syn::{meta::ParseNestedMeta, Error, Result};
 use syn::{Field, Ident, LitInt, LitStr};
@@ -9,7 +10,7 @@ use crate::extractors::{CommandField, ParamField, SourceField, Tag, TrailingFiel
 use crate::ser::SerializationBuilder;
 use crate::MessageComponents;
 use crate::TypeKind;
-use crate::{COMMAND, IRC, PARAM, PARAMS, SOURCE, TAG, TAG_FLAG, TRAILING, WITH};
+use crate::{COMMAND, ESCAPE, IRC, PARAM, PARAMS, SOURCE, TAG, TAG_FLAG, TRAILING, WITH};
 
 pub struct FieldAttribute {
     kind: FieldKind,
@@ -20,6 +21,7 @@ impl FieldAttribute {
     pub fn parse(field: &Field, field_name: &Ident) -> Result<Self> {
         let mut field_kind: Option<FieldKind> = None;
         let mut with: Option<LitStr> = None;
+        let mut escape: Option<Ident> = None;
 
         for attr in &field.attrs {
             if !attr.path().is_ident(IRC) {
@@ -38,6 +40,15 @@ impl FieldAttribute {
                     return Ok(());
                 }
 
+                if let AttributeType::Escape(ident) = attr_type {
+                    if escape.is_some() {
+                        return Err(meta.error(error_msg::duplicate_attribute(ESCAPE)));
+                    }
+
+                    escape = Some(ident);
+                    return Ok(());
+                }
+
                 if let Some(existing) = &field_kind {
                     return Err(meta.error(format!(
                         "field cannot have multiple extraction attributes (found both `{}` and `{}`)",
@@ -51,13 +62,25 @@ impl FieldAttribute {
             })?;
         }
 
-        let kind = field_kind.ok_or_else(|| {
+        let mut kind = field_kind.ok_or_else(|| {
             Error::new_spanned(
                 field,
                 "field must have at least one IRC extraction attribute",
             )
         })?;
 
+        if let Some(ident) = escape {
+            match kind {
+                FieldKind::Tag(Tag::Value(key)) => kind = FieldKind::Tag(Tag::Escaped(key)),
+                _ => {
+                    return Err(Error::new_spanned(
+                        ident,
+                        "`escape` can only be used with `tag`",
+                    ))
+                }
+            }
+        }
+
         Ok(Self { kind, with })
     }
 
@@ -110,6 +133,7 @@ enum AttributeType {
     Trailing,
     Command(Option<LitStr>),
     With(LitStr),
+    Escape(Ident),
 }
 
 impl AttributeType {
@@ -123,6 +147,7 @@ impl AttributeType {
             Self::Trailing => TRAILING,
             Self::Command(_) => COMMAND,
             Self::With(_) => WITH,
+            Self::Escape(_) => ESCAPE,
         }
     }
 
@@ -193,6 +218,10 @@ impl AttributeType {
             return Ok(Self::With(meta.value()?.parse()?));
         }
 
+        if meta.path.is_ident(ESCAPE) {
+            return Ok(Self::Escape(Ident::new(ESCAPE, meta.path.span())));
+        }
+
         Err(meta.error(error_msg::unknown_irc_attribute(
             meta.path.to_token_stream(),
         )))
@@ -212,7 +241,7 @@ impl FieldKind {
     pub fn name(&self) -> &'static str {
         match self {
             Self::Tag(tag_type) => match tag_type {
-                Tag::Value(_) => TAG,
+                Tag::Value(_) | Tag::Escaped(_) => TAG,
                 Tag::Flag(_) => TAG_FLAG,
             },
             Self::Source(_) => SOURCE,
@@ -236,6 +265,10 @@ impl FieldKind {
                 Span::call_site(),
                 "`with` is not an extraction attribute",
             )),
+            AttributeType::Escape(_) => Err(Error::new(
+                Span::call_site(),
+                "`escape` is not an extraction attribute",
+            )),
         }
     }
 
@@ -303,23 +336,25 @@ fn expand_vec_de(
 ) -> Result<()> {
     use TypeKind::*;
 
+    let access = builder.access(field_name);
+
     match TypeKind::classify(&field.ty) {
         Vec(ty) => match TypeKind::classify(ty) {
             Str => {
                 builder.params_push(quote! {
-                    params.extend(&self.#field_name)?;
+                    params.extend(&#access)?;
                 });
                 Ok(())
             }
             String => {
                 builder.params_push(quote! {
-                    params.extend(&self.#field_name)?;
+                    params.extend(&#access)?;
                 });
                 Ok(())
             }
             _ => {
                 builder.custom_params(quote! {
-                    for p in &self.#field_name {
+                    for p in &#access {
                         p.to_message(serialize)?;
                     }
                 });
@@ -328,7 +363,7 @@ fn expand_vec_de(
         },
         _ => {
             builder.custom_params(quote! {
-                self.#field_name.to_message(serialize)?;
+                #access.to_message(serialize)?;
             });
             Ok(())
         }
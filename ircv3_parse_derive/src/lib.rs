@@ -7,19 +7,22 @@ mod msg_lifetime;
 mod ser;
 mod struct_attribute;
 mod type_check;
+mod variant;
 
 pub(crate) use type_check::TypeKind;
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::LitStr;
-use syn::{parse_macro_input, DeriveInput, Error, Result};
+use syn::{parse_macro_input, Data, DataEnum, DeriveInput, Error, Result};
+use syn::{punctuated::Punctuated, token::Comma};
+use syn::{Field, LitStr};
 
 use components::MessageComponents;
 use extractors::extract_named_fields;
 use field_attribute::FieldAttribute;
 use msg_lifetime::get_or_create_msg_lifetime;
 use struct_attribute::StructAttribute;
+use variant::{parse_variants, VariantKind};
 
 pub(crate) const IRC: &str = "irc";
 pub(crate) const COMMAND: &str = "command";
@@ -30,8 +33,10 @@ pub(crate) const PARAM: &str = "param";
 pub(crate) const PARAMS: &str = "params";
 pub(crate) const TRAILING: &str = "trailing";
 pub(crate) const WITH: &str = "with";
+pub(crate) const ESCAPE: &str = "escape";
+pub(crate) const OTHER: &str = "other";
 
-/// Derives `FromMessage` implementation for structs
+/// Derives `FromMessage` implementation for structs and enums
 ///
 /// # Attributes
 ///
@@ -44,6 +49,7 @@ pub(crate) const WITH: &str = "with";
 /// **Tag Extraction:**
 /// - `#[irc(tag)]` - Extract tag value using field name as key
 /// - `#[irc(tag = "key")]` - Extract tag value with custom key
+/// - `#[irc(tag, escape)]` - Unescape the tag value (requires `String` or `Option<String>`)
 ///
 /// **Tag Flag Extraction:**
 /// - `#[irc(tag_flag)]` - Extract tag flag using field name as key (returns `bool`)
@@ -70,6 +76,23 @@ pub(crate) const WITH: &str = "with";
 /// **Custom Extraction:**
 /// - `#[irc(with = "function")]` - Use custom extraction function
 ///
+/// **Typed Fields:**
+///
+/// `tag`, `param` and `trailing` fields of any other type, or `Option` of it, are converted
+/// through `FromValue`, which every `FromStr` type implements (e.g. `u64`, `IpAddr`,
+/// `twitch::EmoteRanges`). Failures return `DeError::InvalidValue` with the raw text.
+///
+/// ## Variant-level
+///
+/// Enums dispatch on the message command. Every variant needs one of:
+/// - `#[irc(command = "COMMAND")]` - Selected when the command matches (case-insensitive)
+/// - `#[irc(command = 1)]` - Selected for a numeric reply, matched as `001`
+/// - `#[irc(other)]` - Catch-all holding the raw `Message`; without it, unmatched commands
+///   return `DeError::InvalidCommand`
+///
+/// A variant either wraps a type implementing `FromMessage`, declares fields inline with
+/// the field-level attributes above, or is a unit variant.
+///
 #[proc_macro_derive(FromMessage, attributes(irc))]
 pub fn derive_from_message(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -79,16 +102,109 @@ pub fn derive_from_message(input: TokenStream) -> TokenStream {
 }
 
 fn derive_from_message_impl(input: DeriveInput) -> Result<proc_macro2::TokenStream> {
-    let (_, struct_ty_generics, _) = input.generics.split_for_impl();
-
-    let mut impl_block_generics = input.generics.clone();
-    let msg_lifetime = get_or_create_msg_lifetime(&mut impl_block_generics);
-    let (impl_generics, _, where_clause) = impl_block_generics.split_for_impl();
+    if let Data::Enum(data) = &input.data {
+        return derive_enum_from_message(&input, data);
+    }
 
     let struct_attrs = StructAttribute::parse(&input)?;
 
     let fields = extract_named_fields(&input, "FromMessage")?;
+    let (components, expand_fields, commands) = expand_fields(fields)?;
+
+    let command_validation = struct_attrs.expand_validation(commands);
+    let setup_code = components.expand();
+
+    Ok(impl_from_message(
+        &input,
+        quote! {
+            #command_validation
+
+            #(#setup_code)*
+
+            Ok(Self {
+                #(#expand_fields),*
+            })
+        },
+    ))
+}
+
+fn derive_enum_from_message(
+    input: &DeriveInput,
+    data: &DataEnum,
+) -> Result<proc_macro2::TokenStream> {
+    StructAttribute::parse(input)?.reject_enum_command()?;
+
+    let variants = parse_variants(data)?;
+
+    let mut branches = Vec::new();
+    let mut expected = Vec::new();
+    let mut other = None;
+
+    for variant in variants {
+        let ident = variant.ident;
+
+        let command = match variant.command {
+            Some(command) => command,
+            None => {
+                other = Some(quote! { Ok(Self::#ident(*msg)) });
+                continue;
+            }
+        };
+
+        let construct = match variant.kind {
+            VariantKind::Unit => quote! { Ok(Self::#ident) },
+            VariantKind::Wrapped => quote! {
+                Ok(Self::#ident(ircv3_parse::message::de::FromMessage::from_message(msg)?))
+            },
+            VariantKind::Inline(fields) => {
+                let (components, expand_fields, _) = expand_fields(fields)?;
+                let setup_code = components.expand();
+
+                quote! {
+                    #(#setup_code)*
+
+                    Ok(Self::#ident {
+                        #(#expand_fields),*
+                    })
+                }
+            }
+        };
+
+        expected.push(command.value());
+        branches.push(quote! {
+            if msg.command() == #command {
+                return { #construct };
+            }
+        });
+    }
+
+    let fallback = other.unwrap_or_else(|| {
+        let expected = expected.join("|");
+        quote! {
+            Err(ircv3_parse::DeError::invalid_command(
+                #expected,
+                msg.command().as_str()
+            ))
+        }
+    });
+
+    Ok(impl_from_message(
+        input,
+        quote! {
+            #(#branches)*
 
+            #fallback
+        },
+    ))
+}
+
+type ExpandedFields = (
+    MessageComponents,
+    Vec<proc_macro2::TokenStream>,
+    Option<LitStr>,
+);
+
+fn expand_fields(fields: &Punctuated<Field, Comma>) -> Result<ExpandedFields> {
     let mut components = MessageComponents::default();
     let mut expand_fields: Vec<proc_macro2::TokenStream> = Vec::new();
     let mut errors = Vec::new();
@@ -128,28 +244,32 @@ fn derive_from_message_impl(input: DeriveInput) -> Result<proc_macro2::TokenStre
         return Err(e);
     }
 
+    Ok((components, expand_fields, commands))
+}
+
+fn impl_from_message(
+    input: &DeriveInput,
+    body: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let (_, struct_ty_generics, _) = input.generics.split_for_impl();
+
+    let mut impl_block_generics = input.generics.clone();
+    let msg_lifetime = get_or_create_msg_lifetime(&mut impl_block_generics);
+    let (impl_generics, _, where_clause) = impl_block_generics.split_for_impl();
+
     let struct_name = &input.ident;
-    let command_validation = struct_attrs.expand_validation(commands);
-    let setup_code = components.expand();
 
-    Ok(quote! {
+    quote! {
         impl #impl_generics ircv3_parse::message::de::FromMessage<#msg_lifetime>
             for #struct_name #struct_ty_generics #where_clause
         {
             fn from_message(
                 msg: &ircv3_parse::Message<#msg_lifetime>
             ) -> Result<Self, ircv3_parse::DeError> {
-                #command_validation
-
-                #(#setup_code)*
-
-                Ok(Self {
-                    #(#expand_fields),*
-                })
-
+                #body
             }
         }
-    })
+    }
 }
 
 fn combine_errors(errors: Vec<Error>) -> Option<Error> {
@@ -159,7 +279,7 @@ fn combine_errors(errors: Vec<Error>) -> Option<Error> {
     })
 }
 
-/// Derives `ToMessage` implementation for structs to serialize IRC messages.
+/// Derives `ToMessage` implementation for structs and enums to serialize IRC messages.
 ///
 /// # Attributes
 ///
@@ -173,6 +293,8 @@ fn combine_errors(errors: Vec<Error>) -> Option<Error> {
 /// ### Tags
 /// - `#[irc(tag)]` - Serializes field as tag using the field name as key
 /// - `#[irc(tag = "key")]` - Serializes field as tag with custom key
+/// - `#[irc(tag, escape)]` - Escapes `;`, space, `\`, CR and LF in the value instead of
+///   rejecting them
 ///
 /// ### Tag Flags
 /// - `#[irc(tag_flag)]` - Serializes boolean field as tag flag using field name as key
@@ -194,12 +316,23 @@ fn combine_errors(errors: Vec<Error>) -> Option<Error> {
 /// ### Trailing Parameter
 /// - `#[irc(trailing)]` - Serializes field as the trailing parameter
 ///
+/// ### Typed Fields
+/// `tag`, `param` and `trailing` fields of any other type are serialized through
+/// `ToMessage` if they implement it, otherwise through `Display`.
+///
 /// ### Command
 /// - `#[irc(command)]` - Serializes field as the IRC command
 /// - `#[irc(command = "COMMAND")]` - Uses the specified command string
 ///   - If field-level `command` is set, struct-level `command` is ignored
 ///   - If multiple `command` attributes exist, the last one takes precedence
 ///
+/// ## Variant-level
+///
+/// Enums accept the same variant attributes as `FromMessage`:
+/// - Inline and unit variants are serialized with the variant's command
+/// - Wrapped variants, including `#[irc(other)]`, delegate to the wrapped value
+/// - An enum-level `#[irc(crlf)]` applies to inline and unit variants
+///
 #[proc_macro_derive(ToMessage, attributes(irc))]
 pub fn derive_to_message(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -1,17 +1,102 @@
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::LitStr;
-use syn::{DeriveInput, Error, Result};
+use syn::{punctuated::Punctuated, token::Comma};
+use syn::{Data, DataEnum, DeriveInput, Error, Field, Ident, Result};
 
 use crate::extract_named_fields;
+use crate::variant::{parse_variants, VariantKind};
 use crate::FieldAttribute;
 use crate::StructAttribute;
 
 pub fn derive_to_message_impl(input: DeriveInput) -> Result<TokenStream> {
+    if let Data::Enum(data) = &input.data {
+        return derive_enum_to_message(&input, data);
+    }
+
     let fields = extract_named_fields(&input, "ToMessage")?;
 
-    let mut errors = Vec::new();
     let mut builder = SerializationBuilder::new(&input)?;
+    expand_fields_de(fields, &mut builder)?;
+    let body = builder.expand(&input.ident)?;
+
+    Ok(impl_to_message(&input, body))
+}
+
+fn derive_enum_to_message(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream> {
+    let enum_attrs = StructAttribute::parse(input)?;
+    enum_attrs.reject_enum_command()?;
+
+    let variants = parse_variants(data)?;
+
+    let mut arms = Vec::new();
+    let mut errors = Vec::new();
+
+    for variant in variants {
+        let ident = variant.ident;
+
+        let arm = match variant.kind {
+            VariantKind::Wrapped => quote! {
+                Self::#ident(inner) => {
+                    ircv3_parse::message::ser::ToMessage::to_message(inner, serialize)?
+                }
+            },
+            VariantKind::Unit => {
+                let command = variant.command;
+                let crlf_expand = enum_attrs.expand_crlf();
+                quote! {
+                    Self::#ident => {
+                        serialize.command(ircv3_parse::Commands::from(#command));
+                        #crlf_expand
+                    }
+                }
+            }
+            VariantKind::Inline(fields) => {
+                let mut builder =
+                    SerializationBuilder::for_variant(enum_attrs.for_variant(variant.command));
+
+                let body =
+                    expand_fields_de(fields, &mut builder).and_then(|_| builder.expand(ident));
+                let body = match body {
+                    Ok(body) => body,
+                    Err(e) => {
+                        errors.push(e);
+                        continue;
+                    }
+                };
+
+                let bindings = fields.iter().filter_map(|f| f.ident.as_ref());
+                quote! {
+                    Self::#ident { #(#bindings),* } => { #body }
+                }
+            }
+        };
+
+        arms.push(arm);
+    }
+
+    if let Some(e) = crate::combine_errors(errors) {
+        return Err(e);
+    }
+
+    let body = if arms.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            match self {
+                #(#arms)*
+            }
+        }
+    };
+
+    Ok(impl_to_message(input, body))
+}
+
+fn expand_fields_de(
+    fields: &Punctuated<Field, Comma>,
+    builder: &mut SerializationBuilder,
+) -> Result<()> {
+    let mut errors = Vec::new();
 
     for field in fields.iter() {
         let field_name = match field.ident.as_ref() {
@@ -27,7 +112,7 @@ pub fn derive_to_message_impl(input: DeriveInput) -> Result<TokenStream> {
             }
         };
 
-        match attribute.expand_de(field, field_name, &mut builder) {
+        match attribute.expand_de(field, field_name, builder) {
             Ok(_) => {}
             Err(e) => {
                 errors.push(e);
@@ -35,11 +120,30 @@ pub fn derive_to_message_impl(input: DeriveInput) -> Result<TokenStream> {
         };
     }
 
-    if let Some(e) = crate::combine_errors(errors) {
-        return Err(e);
+    match crate::combine_errors(errors) {
+        Some(e) => Err(e),
+        None => Ok(()),
     }
+}
 
-    builder.expand(&input)
+fn impl_to_message(input: &DeriveInput, body: TokenStream) -> TokenStream {
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ircv3_parse::message::ser::ToMessage
+            for #struct_name #ty_generics #where_clause
+        {
+            fn to_message<S: ircv3_parse::message::ser::MessageSerializer>(
+                &self,
+                serialize: &mut S
+            ) -> Result<(), ircv3_parse::IRCError> {
+                #body
+                Ok(())
+            }
+
+        }
+    }
 }
 
 struct SerializeCommand(Option<LitStr>);
@@ -98,22 +202,22 @@ impl Source {
         self.name.is_some() || self.user.is_some() || self.host.is_some()
     }
 
-    fn validate(&self, input: &DeriveInput) -> Result<()> {
+    fn validate(&self, ident: &Ident) -> Result<()> {
         if self.name.is_none() && (self.user.is_some() || self.host.is_some()) {
             return Err(Error::new_spanned(
-                &input.ident,
+                ident,
                 "source `name` field is required when using `user` or `host fields",
             ));
         }
         Ok(())
     }
 
-    pub fn expend(self, input: &DeriveInput) -> Result<TokenStream> {
+    pub fn expend(self, ident: &Ident) -> Result<TokenStream> {
         if !self.has_components() {
             return Ok(quote! {});
         }
 
-        self.validate(input)?;
+        self.validate(ident)?;
 
         let name = self.name;
         let user = self.user;
@@ -131,6 +235,14 @@ impl Source {
     }
 }
 
+/// How generated code reaches a field's value.
+enum FieldAccess {
+    /// `self.field` in a struct.
+    SelfField,
+    /// `(*field)` bound by reference in an enum variant pattern.
+    Binding,
+}
+
 pub struct SerializationBuilder {
     tag_fields: Vec<TokenStream>,
     custom_tags: Vec<TokenStream>,
@@ -142,12 +254,21 @@ pub struct SerializationBuilder {
     custom_trailing: Vec<TokenStream>,
     field_command: SerializeCommand,
     struct_attrs: StructAttribute,
+    access: FieldAccess,
 }
 
 impl SerializationBuilder {
     pub fn new(input: &DeriveInput) -> Result<Self> {
         let struct_attrs = StructAttribute::parse(input)?;
-        Ok(Self {
+        Ok(Self::with_attrs(struct_attrs, FieldAccess::SelfField))
+    }
+
+    pub fn for_variant(attrs: StructAttribute) -> Self {
+        Self::with_attrs(attrs, FieldAccess::Binding)
+    }
+
+    fn with_attrs(struct_attrs: StructAttribute, access: FieldAccess) -> Self {
+        Self {
             tag_fields: Vec::new(),
             custom_tags: Vec::new(),
             source: Source::new(),
@@ -158,7 +279,15 @@ impl SerializationBuilder {
             custom_trailing: Vec::new(),
             field_command: SerializeCommand::new(),
             struct_attrs,
-        })
+            access,
+        }
+    }
+
+    pub fn access(&self, field_name: &Ident) -> TokenStream {
+        match self.access {
+            FieldAccess::SelfField => quote! { self.#field_name },
+            FieldAccess::Binding => quote! { (*#field_name) },
+        }
     }
 
     pub fn tag(&mut self, code: TokenStream) {
@@ -189,7 +318,19 @@ impl SerializationBuilder {
         self.custom_source.push(code);
     }
 
+    /// Pushes code that writes to `params`.
     pub fn params_push(&mut self, code: TokenStream) {
+        self.params.push(quote! {
+            {
+                let mut params = serialize.params();
+                #code
+                params.end();
+            }
+        });
+    }
+
+    /// Pushes code that writes to `serialize`, kept in order with the other parameters.
+    pub fn param_field(&mut self, code: TokenStream) {
         self.params.push(code);
     }
 
@@ -205,7 +346,7 @@ impl SerializationBuilder {
         self.custom_trailing.push(code);
     }
 
-    pub fn expand(self, input: &DeriveInput) -> Result<TokenStream> {
+    pub fn expand(self, ident: &Ident) -> Result<TokenStream> {
         let tags_code = if !self.tag_fields.is_empty() {
             let tags = self.tag_fields;
             quote! {
@@ -220,17 +361,14 @@ impl SerializationBuilder {
             quote! {}
         };
 
-        let source_code = self.source.expend(input)?;
+        let source_code = self.source.expend(ident)?;
         let command_code = self.field_command.expend(self.struct_attrs.command());
         let params_code = if !self.params.is_empty() {
             let p = &self.params;
             quote! {
                 {
                     use ircv3_parse::message::ser::SerializeParams;
-                    let mut params = serialize.params();
                     #(#p)*
-                    params.end();
-
                 }
             }
         } else {
@@ -244,38 +382,23 @@ impl SerializationBuilder {
         let nested_params = self.custom_params;
         let nested_trailing = self.custom_trailing;
 
-        let struct_name = &input.ident;
-        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
-
         let crlf_expand = self.struct_attrs.expand_crlf();
         Ok(quote! {
-            impl #impl_generics ircv3_parse::message::ser::ToMessage
-                for #struct_name #ty_generics #where_clause
-            {
-                fn to_message<S: ircv3_parse::message::ser::MessageSerializer>(
-                    &self,
-                    serialize: &mut S
-                ) -> Result<(), ircv3_parse::IRCError> {
-
-                    #(#nested_tags)*
-                    #tags_code
+            #(#nested_tags)*
+            #tags_code
 
-                    #(#nested_source)*
-                    #source_code
+            #(#nested_source)*
+            #source_code
 
-                    #command_code
+            #command_code
 
-                    #(#nested_params)*
-                    #params_code
+            #(#nested_params)*
+            #params_code
 
-                    #(#nested_trailing)*
-                    #trailing_code
+            #(#nested_trailing)*
+            #trailing_code
 
-                    #crlf_expand
-                    Ok(())
-                }
-
-            }
+            #crlf_expand
         })
     }
 }
@@ -1,6 +1,6 @@
 use quote::{quote, ToTokens};
 use syn::LitStr;
-use syn::{DeriveInput, Result};
+use syn::{DeriveInput, Error, Result};
 
 use crate::error_msg;
 use crate::{COMMAND, IRC};
@@ -80,4 +80,22 @@ impl StructAttribute {
     pub fn command(&self) -> &Option<LitStr> {
         &self.command
     }
+
+    /// Attributes for an enum variant, which carries its own command.
+    pub fn for_variant(&self, command: Option<LitStr>) -> Self {
+        Self {
+            command,
+            crlf: self.crlf,
+        }
+    }
+
+    pub fn reject_enum_command(&self) -> Result<()> {
+        match &self.command {
+            Some(cmd) => Err(Error::new_spanned(
+                cmd,
+                "`command` must be set on each variant of an enum",
+            )),
+            None => Ok(()),
+        }
+    }
 }
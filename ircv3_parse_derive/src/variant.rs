@@ -0,0 +1,144 @@
+use quote::ToTokens;
+use syn::{punctuated::Punctuated, token::Comma};
+use syn::{DataEnum, Error, Field, Fields, Ident, Lit, LitStr, Result};
+
+use crate::error_msg;
+use crate::{COMMAND, IRC, OTHER};
+
+pub enum VariantKind<'a> {
+    /// `Ping`
+    Unit,
+    /// `PrivMsg(PrivMsg<'a>)`
+    Wrapped,
+    /// `Join { #[irc(param)] channel: &'a str }`
+    Inline(&'a Punctuated<Field, Comma>),
+}
+
+pub struct Variant<'a> {
+    pub ident: &'a Ident,
+    /// `None` for the `#[irc(other)]` catch-all.
+    pub command: Option<LitStr>,
+    pub kind: VariantKind<'a>,
+}
+
+impl<'a> Variant<'a> {
+    pub fn parse(variant: &'a syn::Variant) -> Result<Self> {
+        let mut command: Option<LitStr> = None;
+        let mut other = false;
+
+        for attr in &variant.attrs {
+            if !attr.path().is_ident(IRC) {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident(COMMAND) {
+                    if command.is_some() {
+                        return Err(meta.error(error_msg::duplicate_attribute(COMMAND)));
+                    }
+
+                    command = Some(match meta.value()?.parse()? {
+                        Lit::Str(lit) => lit,
+                        Lit::Int(lit) => {
+                            let code: u16 = lit.base10_parse()?;
+                            LitStr::new(&format!("{code:03}"), lit.span())
+                        }
+                        lit => {
+                            return Err(Error::new_spanned(
+                                lit,
+                                "expected a command string or a numeric code",
+                            ))
+                        }
+                    });
+                    return Ok(());
+                }
+
+                if meta.path.is_ident(OTHER) {
+                    if other {
+                        return Err(meta.error(error_msg::duplicate_attribute(OTHER)));
+                    }
+
+                    other = true;
+                    return Ok(());
+                }
+
+                Err(meta.error(error_msg::unknown_irc_attribute(
+                    meta.path.to_token_stream(),
+                )))
+            })?;
+        }
+
+        let ident = &variant.ident;
+
+        if other && command.is_some() {
+            return Err(Error::new_spanned(
+                ident,
+                "variant cannot have both `command` and `other`",
+            ));
+        }
+
+        if !other && command.is_none() {
+            return Err(Error::new_spanned(
+                ident,
+                format!(
+                    "variant `{ident}` must have `#[irc(command = \"...\")]` or `#[irc(other)]`"
+                ),
+            ));
+        }
+
+        let kind = match &variant.fields {
+            Fields::Unit => VariantKind::Unit,
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => VariantKind::Wrapped,
+            Fields::Unnamed(fields) => {
+                return Err(Error::new_spanned(
+                    fields,
+                    "tuple variants must wrap exactly one type",
+                ))
+            }
+            Fields::Named(fields) => VariantKind::Inline(&fields.named),
+        };
+
+        if other && !matches!(kind, VariantKind::Wrapped) {
+            return Err(Error::new_spanned(
+                ident,
+                "`other` variant must wrap a single `Message`",
+            ));
+        }
+
+        Ok(Self {
+            ident,
+            command,
+            kind,
+        })
+    }
+}
+
+pub fn parse_variants(data: &DataEnum) -> Result<Vec<Variant<'_>>> {
+    let mut variants = Vec::new();
+    let mut errors = Vec::new();
+    let mut has_other = false;
+
+    for variant in &data.variants {
+        match Variant::parse(variant) {
+            Ok(variant) => {
+                if variant.command.is_none() {
+                    if has_other {
+                        errors.push(Error::new_spanned(
+                            variant.ident,
+                            "only one variant can be marked `other`",
+                        ));
+                    }
+                    has_other = true;
+                }
+
+                variants.push(variant);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    match crate::combine_errors(errors) {
+        Some(e) => Err(e),
+        None => Ok(variants),
+    }
+}
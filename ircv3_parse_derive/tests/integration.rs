@@ -42,6 +42,140 @@ fn with_tags() {
     assert_eq!("Hello", msg.content);
 }
 
+#[test]
+fn with_escaped_tag() {
+    #[derive(FromMessage)]
+    struct Tag {
+        #[irc(tag = "reason", escape)]
+        reason: String,
+        #[irc(tag, escape)]
+        missing: Option<String>,
+    }
+
+    let input = "@reason=see\\syou\\:\\sbye :nick QUIT";
+    let msg: Tag = ircv3_parse::from_str(input).unwrap();
+    assert_eq!("see you; bye", msg.reason);
+    assert_eq!(None, msg.missing);
+}
+
+#[test]
+fn typed_tag() {
+    use ircv3_parse::message::de::FromValue;
+
+    #[derive(Debug, PartialEq)]
+    struct Color(u8, u8, u8);
+
+    impl<'a> FromValue<'a> for Color {
+        type Error = &'static str;
+
+        fn from_value(value: &'a str) -> Result<Self, Self::Error> {
+            let hex = value.strip_prefix('#').ok_or("missing `#`")?;
+            let rgb = u32::from_str_radix(hex, 16).map_err(|_| "invalid hex")?;
+            Ok(Color((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+        }
+    }
+
+    #[derive(FromMessage)]
+    struct Tag {
+        #[irc(tag)]
+        color: Color,
+        #[irc(tag)]
+        background: Option<Color>,
+    }
+
+    let msg: Tag = ircv3_parse::from_str("@color=#FF8000 PRIVMSG").unwrap();
+    assert_eq!(Color(255, 128, 0), msg.color);
+    assert_eq!(None, msg.background);
+
+    let err = ircv3_parse::from_str::<Tag>("@color=red PRIVMSG")
+        .err()
+        .unwrap();
+    assert_eq!(
+        "invalid field value for field 'color': `red`: missing `#`",
+        err.to_string()
+    );
+
+    assert!(ircv3_parse::from_str::<Tag>("@background=#000000 PRIVMSG")
+        .err()
+        .unwrap()
+        .is_missing_tag());
+}
+
+#[test]
+fn from_str_fields() {
+    use std::net::IpAddr;
+    use std::str::FromStr;
+
+    #[derive(Debug, PartialEq)]
+    enum Mode {
+        Voice,
+        Op,
+    }
+
+    impl FromStr for Mode {
+        type Err = &'static str;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "+v" => Ok(Self::Voice),
+                "+o" => Ok(Self::Op),
+                _ => Err("unknown mode"),
+            }
+        }
+    }
+
+    #[derive(FromMessage)]
+    struct Typed {
+        #[irc(tag = "room-id")]
+        room_id: u64,
+        #[irc(tag = "tmi-sent-ts")]
+        sent_ts: Option<u64>,
+        #[irc(param = 1)]
+        mode: Mode,
+        #[irc(param = 2)]
+        limit: Option<i32>,
+        #[irc(trailing)]
+        addr: IpAddr,
+    }
+
+    let msg: Typed = ircv3_parse::from_str("@room-id=12345 MODE #channel +o :127.0.0.1").unwrap();
+    assert_eq!(12345, msg.room_id);
+    assert_eq!(None, msg.sent_ts);
+    assert_eq!(Mode::Op, msg.mode);
+    assert_eq!(None, msg.limit);
+    assert_eq!(IpAddr::from([127, 0, 0, 1]), msg.addr);
+
+    let msg: Typed =
+        ircv3_parse::from_str("@room-id=1;tmi-sent-ts=1700000000000 MODE #c +v -5 :::1").unwrap();
+    assert_eq!(Some(1_700_000_000_000), msg.sent_ts);
+    assert_eq!(Some(-5), msg.limit);
+    assert!(msg.addr.is_ipv6());
+
+    let err = ircv3_parse::from_str::<Typed>("@room-id=abc MODE #channel +o :127.0.0.1")
+        .err()
+        .unwrap();
+    assert_eq!(
+        "invalid field value for field 'room_id': `abc`: invalid digit found in string",
+        err.to_string()
+    );
+
+    let err = ircv3_parse::from_str::<Typed>("@room-id=1 MODE #channel +x :127.0.0.1")
+        .err()
+        .unwrap();
+    assert_eq!(
+        "invalid field value for field 'mode': `+x`: unknown mode",
+        err.to_string()
+    );
+
+    let err = ircv3_parse::from_str::<Typed>("@room-id=1 MODE #channel")
+        .err()
+        .unwrap();
+    assert_eq!(
+        "missing parameter at index 1 for field 'mode'",
+        err.to_string()
+    );
+}
+
 #[test]
 fn with_function() {
     fn parse_num(s: Option<&str>) -> u32 {
@@ -191,3 +325,93 @@ fn param_empty_attribute_value_return_first() {
 
     assert_eq!("#channel", msg.param);
 }
+
+#[test]
+fn enum_dispatch() {
+    #[derive(FromMessage)]
+    #[irc(command = "PRIVMSG")]
+    struct PrivMsg<'a> {
+        #[irc(param)]
+        channel: &'a str,
+        #[irc(trailing)]
+        message: &'a str,
+    }
+
+    #[derive(FromMessage)]
+    enum Event<'a> {
+        #[irc(command = "PRIVMSG")]
+        PrivMsg(PrivMsg<'a>),
+        #[irc(command = "JOIN")]
+        Join {
+            #[irc(source)]
+            nick: &'a str,
+            #[irc(param)]
+            channel: &'a str,
+        },
+        #[irc(command = 1)]
+        Welcome {
+            #[irc(trailing)]
+            message: String,
+        },
+        #[irc(command = "PING")]
+        Ping,
+        #[irc(other)]
+        Other(ircv3_parse::Message<'a>),
+    }
+
+    let event: Event = ircv3_parse::from_str(":nick!user@host privmsg #channel :hi").unwrap();
+    let Event::PrivMsg(msg) = event else {
+        panic!("expected PRIVMSG");
+    };
+    assert_eq!("#channel", msg.channel);
+    assert_eq!("hi", msg.message);
+
+    let event: Event = ircv3_parse::from_str(":nick!user@host JOIN #channel").unwrap();
+    let Event::Join { nick, channel } = event else {
+        panic!("expected JOIN");
+    };
+    assert_eq!("nick", nick);
+    assert_eq!("#channel", channel);
+
+    let event: Event = ircv3_parse::from_str(":irc.example.com 001 nick :Welcome").unwrap();
+    assert!(matches!(event, Event::Welcome { message } if message == "Welcome"));
+
+    let event: Event = ircv3_parse::from_str("PING :irc.example.com").unwrap();
+    assert!(matches!(event, Event::Ping));
+
+    let event: Event = ircv3_parse::from_str("NOTICE #channel :hi").unwrap();
+    let Event::Other(msg) = event else {
+        panic!("expected other");
+    };
+    assert_eq!("NOTICE", msg.command());
+
+    let err = ircv3_parse::from_str::<Event>("JOIN #channel")
+        .err()
+        .unwrap();
+    assert!(err.is_missing_source());
+}
+
+#[test]
+fn enum_without_other() {
+    #[derive(FromMessage)]
+    enum Event<'a> {
+        #[irc(command = "PRIVMSG")]
+        PrivMsg {
+            #[irc(trailing)]
+            message: &'a str,
+        },
+        #[irc(command = "NOTICE")]
+        Notice {
+            #[irc(trailing)]
+            message: &'a str,
+        },
+    }
+
+    let err = ircv3_parse::from_str::<Event>("JOIN #channel")
+        .err()
+        .unwrap();
+    assert_eq!(
+        "invalid command: expected `PRIVMSG|NOTICE`, got `JOIN`",
+        err.to_string()
+    );
+}
@@ -102,6 +102,27 @@ fn tag_str() {
     assert_eq!(10, size)
 }
 
+#[test]
+fn tag_escaped() {
+    #[derive(ToMessage)]
+    struct Tag<'a> {
+        #[irc(tag = "key", escape)]
+        field: &'a str,
+        #[irc(tag, escape)]
+        other: Option<String>,
+    }
+
+    let tag = Tag {
+        field: "a;b c\\d",
+        other: Some("\r\n".to_string()),
+    };
+
+    let size = tag.serialized_size();
+    let msg = tag.to_bytes().unwrap();
+    assert_eq!("@key=a\\:b\\sc\\\\d;other=\\r\\n", msg);
+    assert_eq!(26, size)
+}
+
 #[test]
 fn tag_opt_none() {
     #[derive(ToMessage)]
@@ -407,3 +428,157 @@ fn full() {
     );
     assert_eq!(100, size)
 }
+
+#[test]
+fn enum_variants() {
+    #[derive(ToMessage)]
+    #[irc(command = "PRIVMSG")]
+    struct PrivMsg<'a> {
+        #[irc(param)]
+        channel: &'a str,
+        #[irc(trailing)]
+        message: &'a str,
+    }
+
+    #[derive(ToMessage)]
+    enum Event<'a> {
+        #[irc(command = "PRIVMSG")]
+        PrivMsg(PrivMsg<'a>),
+        #[irc(command = "JOIN")]
+        Join {
+            #[irc(tag)]
+            label: Option<&'a str>,
+            #[irc(param)]
+            channel: String,
+        },
+        #[irc(command = 1)]
+        Welcome {
+            #[irc(param)]
+            nick: &'a str,
+            #[irc(trailing)]
+            message: &'a str,
+        },
+        #[irc(command = "PING")]
+        Ping,
+        #[irc(other)]
+        Other(ircv3_parse::Message<'a>),
+    }
+
+    let msg = Event::PrivMsg(PrivMsg {
+        channel: "#channel",
+        message: "hi",
+    });
+    assert_eq!("PRIVMSG #channel :hi", msg.to_bytes().unwrap());
+
+    let msg = Event::Join {
+        label: Some("abc"),
+        channel: "#channel".to_string(),
+    };
+    assert_eq!("@label=abc JOIN #channel", msg.to_bytes().unwrap());
+    assert_eq!(24, msg.serialized_size());
+
+    let msg = Event::Welcome {
+        nick: "nick",
+        message: "Welcome",
+    };
+    assert_eq!("001 nick :Welcome", msg.to_bytes().unwrap());
+
+    assert_eq!("PING", Event::Ping.to_bytes().unwrap());
+
+    let input = "@id=1;flag :nick!user@host NOTICE #channel :a\\sb c";
+    let msg = Event::Other(ircv3_parse::parse(input).unwrap());
+    assert_eq!(input, msg.to_bytes().unwrap());
+
+    for input in [
+        ":nick!user@user/foo PRIVMSG #c :hi",
+        ":nick!u@2001:db8::1 PRIVMSG #c :hi",
+    ] {
+        let msg = Event::Other(ircv3_parse::parse(input).unwrap());
+        assert_eq!(input, msg.to_bytes().unwrap());
+    }
+}
+
+#[test]
+fn enum_crlf() {
+    #[derive(ToMessage)]
+    #[irc(crlf)]
+    enum Event {
+        #[irc(command = "PING")]
+        Ping,
+        #[irc(command = "PONG")]
+        Pong {
+            #[irc(trailing)]
+            server: String,
+        },
+    }
+
+    assert_eq!("PING\r\n", Event::Ping.to_bytes().unwrap());
+    let pong = Event::Pong {
+        server: "irc.example.com".to_string(),
+    };
+    assert_eq!("PONG :irc.example.com\r\n", pong.to_bytes().unwrap());
+}
+
+#[test]
+fn display_fields() {
+    use std::fmt;
+    use std::net::IpAddr;
+
+    enum Mode {
+        Voice,
+        Op,
+    }
+
+    impl fmt::Display for Mode {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Voice => f.write_str("+v"),
+                Self::Op => f.write_str("+o"),
+            }
+        }
+    }
+
+    #[derive(ToMessage)]
+    #[irc(command = "MODE")]
+    struct Typed<'a> {
+        #[irc(tag = "room-id")]
+        room_id: u64,
+        #[irc(tag = "tmi-sent-ts")]
+        sent_ts: Option<u64>,
+        #[irc(param)]
+        channel: &'a str,
+        #[irc(param)]
+        mode: Mode,
+        #[irc(param)]
+        limit: Option<i32>,
+        #[irc(param)]
+        nick: &'a str,
+        #[irc(trailing)]
+        addr: IpAddr,
+    }
+
+    let msg = Typed {
+        room_id: 12345,
+        sent_ts: None,
+        channel: "#channel",
+        mode: Mode::Op,
+        limit: Some(-5),
+        nick: "nick",
+        addr: IpAddr::from([127, 0, 0, 1]),
+    };
+
+    let actual = msg.to_bytes().unwrap();
+    assert_eq!("@room-id=12345 MODE #channel +o -5 nick :127.0.0.1", actual);
+    assert_eq!(actual.len(), msg.serialized_size());
+
+    let msg = Typed {
+        sent_ts: Some(1),
+        mode: Mode::Voice,
+        limit: None,
+        ..msg
+    };
+    assert_eq!(
+        "@room-id=12345;tmi-sent-ts=1 MODE #channel +v nick :127.0.0.1",
+        msg.to_bytes().unwrap()
+    );
+}
@@ -1,8 +1,8 @@
 use ircv3_parse_derive::FromMessage;
 
 #[derive(FromMessage)]
-enum M1 {
-    Field,
+union M1 {
+    field: u32,
 }
 
 #[derive(FromMessage)]
@@ -0,0 +1,9 @@
+use ircv3_parse_derive::ToMessage;
+
+#[derive(ToMessage)]
+struct M {
+    #[irc(trailing, escape)]
+    field: String,
+}
+
+fn main() {}
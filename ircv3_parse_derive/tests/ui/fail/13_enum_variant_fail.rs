@@ -0,0 +1,28 @@
+use ircv3_parse_derive::{FromMessage, ToMessage};
+
+#[derive(FromMessage)]
+#[irc(command = "PRIVMSG")]
+enum E1<'a> {
+    #[irc(command = "PRIVMSG")]
+    PrivMsg(&'a str),
+}
+
+#[derive(FromMessage)]
+enum E2<'a> {
+    #[irc(command = "PRIVMSG", other)]
+    PrivMsg(ircv3_parse::Message<'a>),
+    #[irc(other)]
+    Other,
+    #[irc(command = "JOIN")]
+    Join(&'a str, &'a str),
+}
+
+#[derive(ToMessage)]
+enum E3<'a> {
+    #[irc(other)]
+    First(ircv3_parse::Message<'a>),
+    #[irc(other)]
+    Second(ircv3_parse::Message<'a>),
+}
+
+fn main() {}
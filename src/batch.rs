@@ -0,0 +1,351 @@
+//! Reassembly of IRCv3 batches.
+//!
+//! Messages tagged `batch=<ref>` between `BATCH +<ref> <type>` and `BATCH -<ref>` may be
+//! interleaved with other traffic. [`BatchCollector`] holds them back until the batch
+//! closes and returns it whole, with nested batches as its children.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ircv3_parse::batch::{BatchCollector, Collected};
+//! use ircv3_parse::MessageBuf;
+//!
+//! let mut collector = BatchCollector::new();
+//! let lines = [
+//!     ":irc.example.com BATCH +yXNAbvnRHTRBv netsplit irc.hub other.host",
+//!     "@batch=yXNAbvnRHTRBv :aji!a@a QUIT :irc.hub other.host",
+//!     ":nick!user@host PRIVMSG #channel :not batched",
+//!     "@batch=yXNAbvnRHTRBv :bob!b@b QUIT :irc.hub other.host",
+//!     ":irc.example.com BATCH -yXNAbvnRHTRBv",
+//! ];
+//!
+//! let mut collected = Vec::new();
+//! for line in lines {
+//!     collected.extend(collector.push(MessageBuf::parse(line)?)?);
+//! }
+//!
+//! assert!(matches!(&collected[0], Collected::Message(msg) if msg.command().is_privmsg()));
+//! let Collected::Batch(batch) = &collected[1] else { panic!() };
+//! assert_eq!("netsplit", batch.kind);
+//! assert_eq!(["irc.hub", "other.host"], &batch.params[..]);
+//! assert_eq!(2, batch.messages.len());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::compat::{BTreeMap, String, ToString, Vec};
+
+use crate::error::BatchError;
+use crate::{Commands, DeError, MessageBuf};
+
+/// A closed batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Batch {
+    /// Reference tag, without the `+` or `-`.
+    pub reference: String,
+    /// Batch type, e.g. `netsplit` or `chathistory`.
+    pub kind: String,
+    pub params: Vec<String>,
+    /// The `BATCH +<ref>` line, with its own tags such as `label`.
+    pub open: MessageBuf,
+    /// Messages tagged with this batch, in order of arrival.
+    pub messages: Vec<MessageBuf>,
+    /// Batches nested inside this one, in the order they closed.
+    pub children: Vec<Batch>,
+}
+
+impl Batch {
+    /// Number of messages in this batch and its children.
+    fn total(&self) -> usize {
+        self.messages.len() + self.children.iter().map(Batch::total).sum::<usize>()
+    }
+}
+
+/// Output of [`BatchCollector::push`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Collected {
+    /// A message outside any batch, passed through unchanged.
+    Message(MessageBuf),
+    /// An outermost batch that has just closed.
+    Batch(Batch),
+}
+
+#[derive(Debug, Clone)]
+struct Open {
+    batch: Batch,
+    parent: Option<String>,
+}
+
+/// Collects batched messages until their batch closes.
+///
+/// Limits on open batches and held messages protect against servers that never close
+/// their batches; a message that would exceed them is rejected with
+/// [`BatchError`] and dropped.
+#[derive(Debug, Clone)]
+pub struct BatchCollector {
+    open: BTreeMap<String, Open>,
+    held: usize,
+    max_batches: usize,
+    max_messages: usize,
+}
+
+impl Default for BatchCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchCollector {
+    pub fn new() -> Self {
+        Self {
+            open: BTreeMap::new(),
+            held: 0,
+            max_batches: 64,
+            max_messages: 10_000,
+        }
+    }
+
+    /// Sets the number of batches that may be open at once, nested ones included.
+    /// Defaults to 64.
+    pub fn max_batches(mut self, max: usize) -> Self {
+        self.max_batches = max;
+        self
+    }
+
+    /// Sets the number of messages held across all open batches. Defaults to 10 000.
+    pub fn max_messages(mut self, max: usize) -> Self {
+        self.max_messages = max;
+        self
+    }
+
+    /// Returns the number of open batches.
+    pub fn open_batches(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Returns `true` if batch `reference` is open.
+    pub fn is_open(&self, reference: &str) -> bool {
+        self.open.contains_key(reference)
+    }
+
+    /// Drops every open batch, e.g. after reconnecting.
+    pub fn clear(&mut self) {
+        self.open.clear();
+        self.held = 0;
+    }
+
+    /// Feeds a message. Returns it unchanged if it is not part of a batch, the outermost
+    /// batch once its `BATCH -<ref>` arrives, or `None` while a batch is still open.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BatchError`] if a limit is reached, a reference is not open or is opened
+    /// twice, or a `BATCH` line lacks its reference or type.
+    pub fn push(&mut self, msg: MessageBuf) -> Result<Option<Collected>, BatchError> {
+        let parent = msg
+            .tags()
+            .and_then(|tags| tags.get("batch"))
+            .map(|reference| reference.as_str().to_string());
+
+        if matches!(msg.command(), Commands::BATCH) {
+            let params = msg.params();
+            let reference = params.middles.first().ok_or_else(DeError::missing_param)?;
+
+            if let Some(reference) = reference.strip_prefix('+') {
+                return self.start(reference.to_string(), parent, msg);
+            }
+            if let Some(reference) = reference.strip_prefix('-') {
+                return self.end(reference);
+            }
+        }
+
+        let Some(parent) = parent else {
+            return Ok(Some(Collected::Message(msg)));
+        };
+
+        if self.held >= self.max_messages {
+            return Err(BatchError::TooManyMessages {
+                max: self.max_messages,
+            });
+        }
+
+        let open = self
+            .open
+            .get_mut(&parent)
+            .ok_or(BatchError::UnknownBatch(parent))?;
+        open.batch.messages.push(msg);
+        self.held += 1;
+
+        Ok(None)
+    }
+
+    fn start(
+        &mut self,
+        reference: String,
+        parent: Option<String>,
+        msg: MessageBuf,
+    ) -> Result<Option<Collected>, BatchError> {
+        if self.open.len() >= self.max_batches {
+            return Err(BatchError::TooManyBatches {
+                max: self.max_batches,
+            });
+        }
+        if self.open.contains_key(&reference) {
+            return Err(BatchError::DuplicateBatch(reference));
+        }
+        if let Some(parent) = parent.as_ref().filter(|p| !self.open.contains_key(*p)) {
+            return Err(BatchError::UnknownBatch(parent.clone()));
+        }
+
+        let params = msg.params();
+        let mut args = params.middles.iter().skip(1).chain(params.trailing.raw());
+        let kind = args.next().ok_or_else(DeError::missing_param)?.to_string();
+        let params = args.map(ToString::to_string).collect();
+
+        let batch = Batch {
+            reference: reference.clone(),
+            kind,
+            params,
+            open: msg,
+            messages: Vec::new(),
+            children: Vec::new(),
+        };
+        self.open.insert(reference, Open { batch, parent });
+
+        Ok(None)
+    }
+
+    fn end(&mut self, reference: &str) -> Result<Option<Collected>, BatchError> {
+        let Open { batch, parent } = self
+            .open
+            .remove(reference)
+            .ok_or_else(|| BatchError::UnknownBatch(reference.to_string()))?;
+
+        match parent.and_then(|parent| self.open.get_mut(&parent)) {
+            Some(parent) => {
+                parent.batch.children.push(batch);
+                Ok(None)
+            }
+            None => {
+                self.held -= batch.total();
+                Ok(Some(Collected::Batch(batch)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BatchCollector, Collected};
+    use crate::error::BatchError;
+    use crate::MessageBuf;
+
+    fn push(collector: &mut BatchCollector, line: &str) -> Result<Option<Collected>, BatchError> {
+        collector.push(MessageBuf::parse(line).unwrap())
+    }
+
+    #[test]
+    fn nested() {
+        let mut collector = BatchCollector::new();
+
+        for line in [
+            "BATCH +outer chathistory #chan",
+            "@batch=outer :a!a@a PRIVMSG #chan :one",
+            "@batch=outer BATCH +inner draft/multiline #chan",
+            "@batch=inner :a!a@a PRIVMSG #chan :two",
+            "@batch=inner :a!a@a PRIVMSG #chan :three",
+            "BATCH -inner",
+        ] {
+            assert_eq!(None, push(&mut collector, line).unwrap());
+        }
+        assert_eq!(1, collector.open_batches());
+
+        let Some(Collected::Batch(batch)) = push(&mut collector, "BATCH -outer").unwrap() else {
+            panic!("batch not closed");
+        };
+        assert_eq!("outer", batch.reference);
+        assert_eq!("chathistory", batch.kind);
+        assert_eq!(vec!["#chan"], batch.params);
+        assert_eq!(1, batch.messages.len());
+        assert_eq!(1, batch.children.len());
+
+        let inner = &batch.children[0];
+        assert_eq!("draft/multiline", inner.kind);
+        assert_eq!(
+            Some("outer"),
+            inner
+                .open
+                .tags()
+                .and_then(|t| t.get("batch"))
+                .map(|v| v.as_str())
+        );
+        assert_eq!("three", inner.messages[1].params().trailing.as_str());
+
+        assert_eq!(0, collector.open_batches());
+        assert_eq!(0, collector.held);
+    }
+
+    #[test]
+    fn pass_through() {
+        let mut collector = BatchCollector::new();
+
+        let msg = MessageBuf::parse("PING :irc.example.com").unwrap();
+        assert_eq!(
+            Some(Collected::Message(msg.clone())),
+            collector.push(msg).unwrap()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut collector = BatchCollector::new();
+
+        assert_eq!(
+            Err(BatchError::UnknownBatch("nope".to_string())),
+            push(&mut collector, "@batch=nope PRIVMSG #chan :hi")
+        );
+        assert_eq!(
+            Err(BatchError::UnknownBatch("nope".to_string())),
+            push(&mut collector, "BATCH -nope")
+        );
+        assert_eq!(
+            Err(BatchError::UnknownBatch("nope".to_string())),
+            push(&mut collector, "@batch=nope BATCH +child netsplit")
+        );
+
+        push(&mut collector, "BATCH +a netsplit").unwrap();
+        assert_eq!(
+            Err(BatchError::DuplicateBatch("a".to_string())),
+            push(&mut collector, "BATCH +a netsplit")
+        );
+
+        assert_eq!(
+            "MISSING_COMPONENT",
+            push(&mut collector, "BATCH +b").unwrap_err().code()
+        );
+        assert!(push(&mut collector, "BATCH").is_err());
+    }
+
+    #[test]
+    fn limits() {
+        let mut collector = BatchCollector::new().max_batches(2).max_messages(2);
+
+        push(&mut collector, "BATCH +a netsplit").unwrap();
+        push(&mut collector, "BATCH +b netsplit").unwrap();
+        assert_eq!(
+            Err(BatchError::TooManyBatches { max: 2 }),
+            push(&mut collector, "BATCH +c netsplit")
+        );
+
+        push(&mut collector, "@batch=a QUIT").unwrap();
+        push(&mut collector, "@batch=b QUIT").unwrap();
+        let err = push(&mut collector, "@batch=a QUIT").unwrap_err();
+        assert!(err.is_limit());
+
+        // Closing a batch frees its messages.
+        push(&mut collector, "BATCH -a").unwrap();
+        push(&mut collector, "@batch=b QUIT").unwrap();
+
+        collector.clear();
+        assert_eq!(0, collector.open_batches());
+    }
+}
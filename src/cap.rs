@@ -0,0 +1,512 @@
+//! Client capability negotiation.
+//!
+//! [`CapNegotiator`] is a sans-IO state machine: it is fed parsed [`Message`]s and returns
+//! the lines to send, leaving reading and writing to the caller. [`CapMessage`] reads a
+//! single `CAP` reply.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ircv3_parse::cap::{CapNegotiator, CapState};
+//!
+//! let mut cap = CapNegotiator::new(["multi-prefix", "sasl", "away-notify"]);
+//! assert_eq!("CAP LS 302\r\n", cap.start());
+//!
+//! let msg = ircv3_parse::parse(":irc.example.com CAP * LS :multi-prefix sasl=PLAIN")?;
+//! let out = cap.handle(&msg)?;
+//! assert_eq!(["CAP REQ :multi-prefix sasl\r\n"], &out[..]);
+//!
+//! let msg = ircv3_parse::parse(":irc.example.com CAP * ACK :multi-prefix sasl")?;
+//! let out = cap.handle(&msg)?;
+//! assert_eq!(["CAP END\r\n"], &out[..]);
+//!
+//! assert_eq!(CapState::Done, cap.state());
+//! assert!(cap.is_enabled("sasl"));
+//! assert_eq!(Some("PLAIN"), cap.value("sasl"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use bytes::Bytes;
+
+use crate::compat::{BTreeMap, BTreeSet, String, ToString, Vec};
+
+use crate::components::{CapList, CapSubCommands};
+use crate::message::de::FromMessage;
+use crate::{Commands, DeError, Limits, Message, MessageBuilder};
+
+const CAP_VERSION: &str = "302";
+
+/// A `CAP` reply from the server, e.g. `CAP * LS * :multi-prefix sasl=PLAIN`.
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::cap::CapMessage;
+/// use ircv3_parse::components::CapSubCommands;
+///
+/// let msg: CapMessage =
+///     ircv3_parse::from_str(":irc.example.com CAP * LS * :multi-prefix sasl=PLAIN")?;
+///
+/// assert_eq!("*", msg.target);
+/// assert_eq!(CapSubCommands::LS, msg.subcommand);
+/// assert!(msg.more);
+/// assert_eq!(Some("PLAIN"), msg.caps.get("sasl").and_then(|cap| cap.value));
+/// # Ok::<(), ircv3_parse::DeError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapMessage<'a> {
+    /// The client's nickname, or `*` before registration.
+    pub target: &'a str,
+    pub subcommand: CapSubCommands,
+    /// Set by the `*` continuation marker when more lines of the list follow.
+    pub more: bool,
+    pub caps: CapList<'a>,
+}
+
+impl<'a> FromMessage<'a> for CapMessage<'a> {
+    fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+        if !matches!(msg.command(), Commands::CAP) {
+            return Err(DeError::invalid_command("CAP", msg.command().as_str()));
+        }
+
+        let params = msg.params();
+        let mut middles = params.middles.iter();
+
+        let target = middles.next().ok_or_else(DeError::missing_param)?;
+        let subcommand = middles.next().ok_or_else(DeError::missing_param)?;
+
+        // The list is the trailing parameter, or the last middle when it is a single word.
+        let (more, list) = match (middles.next(), middles.next(), params.trailing.raw()) {
+            (Some("*"), None, Some(list)) => (true, list),
+            (Some("*"), Some(list), None) => (true, list),
+            (Some("*"), None, None) => (true, ""),
+            (None, None, Some(list)) | (Some(list), None, None) => (false, list),
+            _ => (false, ""),
+        };
+
+        Ok(Self {
+            target,
+            subcommand: CapSubCommands::from(subcommand),
+            more,
+            caps: CapList::new(list),
+        })
+    }
+}
+
+/// Progress of a [`CapNegotiator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapState {
+    /// `CAP LS` has not been sent.
+    Idle,
+    /// Waiting for the last line of `CAP LS`.
+    Listing,
+    /// Waiting for `ACK` or `NAK`.
+    Requesting,
+    /// Every request is settled and `CAP END` is held back.
+    Ready,
+    /// `CAP END` has been sent.
+    Done,
+}
+
+/// Negotiates capabilities with `CAP LS 302`.
+///
+/// Requests every wanted capability the server offers, follows `CAP NEW` and `CAP DEL`
+/// after registration, and keeps track of the enabled set.
+#[derive(Debug, Clone)]
+pub struct CapNegotiator {
+    wanted: Vec<String>,
+    available: BTreeMap<String, Option<String>>,
+    enabled: BTreeSet<String>,
+    pending: BTreeSet<String>,
+    state: CapState,
+    hold_end: bool,
+}
+
+impl CapNegotiator {
+    /// Creates a negotiator that requests `wanted` capabilities when they are available.
+    pub fn new<I, S>(wanted: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            wanted: wanted.into_iter().map(Into::into).collect(),
+            available: BTreeMap::new(),
+            enabled: BTreeSet::new(),
+            pending: BTreeSet::new(),
+            state: CapState::Idle,
+            hold_end: false,
+        }
+    }
+
+    /// Stops in [`CapState::Ready`] instead of sending `CAP END`, e.g. to authenticate
+    /// first. Call [`end`](Self::end) to finish.
+    pub fn hold_end(mut self, hold: bool) -> Self {
+        self.hold_end = hold;
+        self
+    }
+
+    pub fn state(&self) -> CapState {
+        self.state
+    }
+
+    /// Returns `CAP LS 302`, the first line to send.
+    pub fn start(&mut self) -> Bytes {
+        self.state = CapState::Listing;
+        self.available.clear();
+
+        let mut builder = MessageBuilder::new(Commands::CAP);
+        builder.add_params(["LS", CAP_VERSION]).unwrap();
+        builder.build()
+    }
+
+    /// Returns `CAP END`.
+    pub fn end(&mut self) -> Bytes {
+        self.state = CapState::Done;
+
+        let mut builder = MessageBuilder::new(Commands::CAP);
+        builder.add_param("END").unwrap();
+        builder.build()
+    }
+
+    /// Feeds a message from the server and returns the lines to send in response.
+    ///
+    /// Messages other than `CAP` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeError`] if a `CAP` message lacks its target or subcommand.
+    pub fn handle(&mut self, msg: &Message<'_>) -> Result<Vec<Bytes>, DeError> {
+        let mut out = Vec::new();
+
+        if !matches!(msg.command(), Commands::CAP) {
+            return Ok(out);
+        }
+
+        let CapMessage {
+            subcommand,
+            more,
+            caps,
+            ..
+        } = CapMessage::from_message(msg)?;
+
+        match subcommand {
+            CapSubCommands::LS => {
+                for cap in caps {
+                    self.available
+                        .insert(cap.name.to_string(), cap.value.map(ToString::to_string));
+                }
+
+                if !more && self.state == CapState::Listing {
+                    self.request(&mut out);
+                    self.settle(&mut out);
+                }
+            }
+            CapSubCommands::ACK => {
+                for cap in caps {
+                    if cap.disabled {
+                        self.enabled.remove(cap.name);
+                    } else {
+                        self.enabled.insert(cap.name.to_string());
+                    }
+                    self.pending.remove(cap.name);
+                }
+                self.settle(&mut out);
+            }
+            CapSubCommands::NAK => {
+                for cap in caps {
+                    self.pending.remove(cap.name);
+                }
+                self.settle(&mut out);
+            }
+            CapSubCommands::NEW => {
+                for cap in caps {
+                    self.available
+                        .insert(cap.name.to_string(), cap.value.map(ToString::to_string));
+                }
+                self.request(&mut out);
+            }
+            CapSubCommands::DEL => {
+                for cap in caps {
+                    self.available.remove(cap.name);
+                    self.enabled.remove(cap.name);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(out)
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.enabled.contains(name)
+    }
+
+    /// Returns the enabled capabilities.
+    pub fn enabled(&self) -> impl Iterator<Item = &str> {
+        self.enabled.iter().map(String::as_str)
+    }
+
+    /// Returns the capabilities the server offers, with their values.
+    pub fn available(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.available
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+
+    /// Returns the value the server advertised for `name`, such as the mechanisms of `sasl`.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.available.get(name)?.as_deref()
+    }
+
+    /// Requests every wanted capability that is available and not yet enabled or pending,
+    /// in as many `CAP REQ` lines as needed.
+    fn request(&mut self, out: &mut Vec<Bytes>) {
+        let max = Limits::CLIENT.body - "CAP REQ :\r\n".len();
+        let mut line = String::new();
+
+        for name in &self.wanted {
+            if !self.available.contains_key(name)
+                || self.enabled.contains(name)
+                || self.pending.contains(name)
+            {
+                continue;
+            }
+
+            if !line.is_empty() && line.len() + 1 + name.len() > max {
+                out.push(req(&line));
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(name);
+            self.pending.insert(name.clone());
+        }
+
+        if !line.is_empty() {
+            out.push(req(&line));
+        }
+
+        if !self.pending.is_empty() && self.state == CapState::Listing {
+            self.state = CapState::Requesting;
+        }
+    }
+
+    /// Finishes negotiation once nothing is pending.
+    fn settle(&mut self, out: &mut Vec<Bytes>) {
+        if !self.pending.is_empty()
+            || !matches!(self.state, CapState::Listing | CapState::Requesting)
+        {
+            return;
+        }
+
+        if self.hold_end {
+            self.state = CapState::Ready;
+        } else {
+            out.push(self.end());
+        }
+    }
+}
+
+fn req(caps: &str) -> Bytes {
+    let mut builder = MessageBuilder::new(Commands::CAP);
+    builder
+        .add_param("REQ")
+        .unwrap()
+        .set_trailing(caps)
+        .unwrap();
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CapMessage, CapNegotiator, CapState};
+    use crate::components::CapSubCommands;
+
+    /// Feeds server lines and collects everything sent back.
+    fn script(cap: &mut CapNegotiator, lines: &[&str]) -> Vec<String> {
+        let mut sent = Vec::new();
+        for line in lines {
+            let msg = crate::parse(line).unwrap();
+            for out in cap.handle(&msg).unwrap() {
+                sent.push(String::from_utf8(out.to_vec()).unwrap());
+            }
+        }
+        sent
+    }
+
+    #[test]
+    fn cap_message() {
+        let msg: CapMessage = crate::from_str(":irc.example.com CAP nick ACK sasl").unwrap();
+        assert_eq!("nick", msg.target);
+        assert_eq!(CapSubCommands::ACK, msg.subcommand);
+        assert!(!msg.more);
+        assert!(msg.caps.contains("sasl"));
+
+        let msg: CapMessage = crate::from_str(":irc.example.com CAP * LS * sasl").unwrap();
+        assert!(msg.more);
+        assert!(msg.caps.contains("sasl"));
+
+        let msg: CapMessage = crate::from_str(":irc.example.com CAP * LS *").unwrap();
+        assert!(msg.more);
+        assert!(msg.caps.is_empty());
+
+        let msg: CapMessage = crate::from_str(":irc.example.com CAP * LS :").unwrap();
+        assert!(msg.caps.is_empty());
+
+        let msg: CapMessage = crate::from_str(":irc.example.com CAP * LIST").unwrap();
+        assert_eq!(CapSubCommands::LIST, msg.subcommand);
+        assert!(msg.caps.is_empty());
+        assert_eq!(Ok(CapSubCommands::LIST), "LIST".parse());
+
+        assert!(crate::from_str::<CapMessage>("CAP *").is_err());
+        assert!(crate::from_str::<CapMessage>("PING :x")
+            .unwrap_err()
+            .is_invalid_command());
+    }
+
+    #[test]
+    fn multiline_ls() {
+        let mut cap = CapNegotiator::new(["multi-prefix", "sasl", "server-time", "batch"]);
+        cap.start();
+
+        let sent = script(
+            &mut cap,
+            &[
+                ":irc.example.com CAP * LS * :multi-prefix extended-join sasl=PLAIN,EXTERNAL",
+                ":irc.example.com CAP * LS * :account-notify server-time",
+                ":irc.example.com CAP * LS :away-notify",
+            ],
+        );
+        assert_eq!(vec!["CAP REQ :multi-prefix sasl server-time\r\n"], sent);
+        assert_eq!(CapState::Requesting, cap.state());
+        assert_eq!(Some("PLAIN,EXTERNAL"), cap.value("sasl"));
+        assert_eq!(6, cap.available().count());
+
+        let sent = script(
+            &mut cap,
+            &[":irc.example.com CAP * ACK :multi-prefix sasl server-time"],
+        );
+        assert_eq!(vec!["CAP END\r\n"], sent);
+        assert_eq!(
+            vec!["multi-prefix", "sasl", "server-time"],
+            cap.enabled().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn nak() {
+        let mut cap = CapNegotiator::new(["multi-prefix", "sasl"]);
+        cap.start();
+
+        let sent = script(
+            &mut cap,
+            &[
+                ":irc.example.com CAP * LS :multi-prefix sasl",
+                ":irc.example.com CAP * NAK :multi-prefix sasl",
+            ],
+        );
+        assert_eq!(vec!["CAP REQ :multi-prefix sasl\r\n", "CAP END\r\n"], sent);
+        assert_eq!(0, cap.enabled().count());
+    }
+
+    #[test]
+    fn nothing_to_request() {
+        let mut cap = CapNegotiator::new(["sasl"]);
+        cap.start();
+
+        let sent = script(&mut cap, &[":irc.example.com CAP * LS :multi-prefix"]);
+        assert_eq!(vec!["CAP END\r\n"], sent);
+        assert_eq!(CapState::Done, cap.state());
+    }
+
+    #[test]
+    fn hold_end() {
+        let mut cap = CapNegotiator::new(["sasl"]).hold_end(true);
+        cap.start();
+
+        let sent = script(
+            &mut cap,
+            &[
+                ":irc.example.com CAP * LS :sasl",
+                ":irc.example.com CAP * ACK sasl",
+            ],
+        );
+        assert_eq!(vec!["CAP REQ :sasl\r\n"], sent);
+        assert_eq!(CapState::Ready, cap.state());
+
+        assert_eq!("CAP END\r\n", cap.end());
+        assert_eq!(CapState::Done, cap.state());
+    }
+
+    #[test]
+    fn new_and_del() {
+        let mut cap = CapNegotiator::new(["away-notify", "batch"]);
+        cap.start();
+
+        let sent = script(
+            &mut cap,
+            &[
+                ":irc.example.com CAP * LS :batch",
+                ":irc.example.com CAP * ACK :batch",
+                "PING :irc.example.com",
+                ":irc.example.com CAP nick NEW :away-notify chghost",
+                ":irc.example.com CAP nick ACK :away-notify",
+                ":irc.example.com CAP nick DEL :batch",
+            ],
+        );
+        assert_eq!(
+            vec![
+                "CAP REQ :batch\r\n",
+                "CAP END\r\n",
+                "CAP REQ :away-notify\r\n"
+            ],
+            sent
+        );
+        assert_eq!(CapState::Done, cap.state());
+        assert_eq!(vec!["away-notify"], cap.enabled().collect::<Vec<_>>());
+        assert!(cap.value("batch").is_none());
+    }
+
+    #[test]
+    fn ack_removal() {
+        let mut cap = CapNegotiator::new(["echo-message"]);
+        cap.start();
+
+        script(
+            &mut cap,
+            &[
+                ":irc.example.com CAP * LS :echo-message",
+                ":irc.example.com CAP * ACK :echo-message",
+                ":irc.example.com CAP nick ACK :-echo-message",
+            ],
+        );
+        assert!(!cap.is_enabled("echo-message"));
+    }
+
+    #[test]
+    fn long_request() {
+        let wanted: Vec<String> = (0..60)
+            .map(|i| format!("vendor.example/cap-{i:02}"))
+            .collect();
+        let mut cap = CapNegotiator::new(wanted.clone());
+        cap.start();
+
+        let ls = format!(":irc.example.com CAP * LS :{}", wanted.join(" "));
+        let sent = script(&mut cap, &[&ls]);
+
+        assert_eq!(3, sent.len());
+        assert!(sent.iter().all(|line| line.len() <= 512));
+
+        let acks: Vec<String> = sent
+            .iter()
+            .map(|line| line.replace("CAP REQ", ":irc.example.com CAP * ACK"))
+            .collect();
+        let acks: Vec<&str> = acks.iter().map(|line| line.trim_end()).collect();
+        let sent = script(&mut cap, &acks);
+
+        assert_eq!(vec!["CAP END\r\n"], sent);
+        assert_eq!(60, cap.enabled().count());
+    }
+}
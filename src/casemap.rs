@@ -0,0 +1,272 @@
+//! Nickname and channel name comparison under an IRC casemapping.
+//!
+//! Servers advertise how names fold with the `CASEMAPPING` token of `RPL_ISUPPORT`. Under
+//! `rfc1459`, the default, `[]\~` are the upper case forms of `{}|^`, so `[nick]` and
+//! `{NICK}` are the same user.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ircv3_parse::casemap::{self, CaseMappedMap, CaseMapping};
+//!
+//! assert!(casemap::eq_ignore_case("[Nick]", "{nick}", CaseMapping::Rfc1459));
+//! assert!(!casemap::eq_ignore_case("[Nick]", "{nick}", CaseMapping::Ascii));
+//!
+//! let mut users = CaseMappedMap::new(CaseMapping::Rfc1459);
+//! users.insert("Dan^", "away");
+//! assert_eq!(Some(&"away"), users.get("dan~"));
+//! ```
+
+use crate::compat::{BTreeMap, Cow, Display, FmtResult, Formatter, String};
+
+/// How letters fold to lower case when comparing names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CaseMapping {
+    /// Only `A`-`Z` fold.
+    Ascii,
+    /// `A`-`Z` and `[]\~` fold to `a`-`z` and `{}|^`.
+    #[default]
+    Rfc1459,
+    /// `A`-`Z` and `[]\` fold to `a`-`z` and `{}|`.
+    StrictRfc1459,
+}
+
+impl CaseMapping {
+    /// Reads a `CASEMAPPING` value. Returns `None` for mappings other than `ascii`,
+    /// `rfc1459` and `strict-rfc1459`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "ascii" => Some(Self::Ascii),
+            "rfc1459" => Some(Self::Rfc1459),
+            "strict-rfc1459" => Some(Self::StrictRfc1459),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ascii => "ascii",
+            Self::Rfc1459 => "rfc1459",
+            Self::StrictRfc1459 => "strict-rfc1459",
+        }
+    }
+
+    /// Folds a single character to lower case.
+    pub fn to_lower_char(&self, c: char) -> char {
+        match (self, c) {
+            (_, 'A'..='Z') => c.to_ascii_lowercase(),
+            (Self::Rfc1459 | Self::StrictRfc1459, '[') => '{',
+            (Self::Rfc1459 | Self::StrictRfc1459, ']') => '}',
+            (Self::Rfc1459 | Self::StrictRfc1459, '\\') => '|',
+            (Self::Rfc1459, '~') => '^',
+            _ => c,
+        }
+    }
+}
+
+impl Display for CaseMapping {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Compares two names under `mapping`.
+pub fn eq_ignore_case(a: &str, b: &str, mapping: CaseMapping) -> bool {
+    a.len() == b.len()
+        && a.chars()
+            .zip(b.chars())
+            .all(|(a, b)| mapping.to_lower_char(a) == mapping.to_lower_char(b))
+}
+
+/// Folds `name` to lower case under `mapping`, borrowing it when it is already folded.
+pub fn to_lower(name: &str, mapping: CaseMapping) -> Cow<'_, str> {
+    if name.chars().all(|c| mapping.to_lower_char(c) == c) {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(name.chars().map(|c| mapping.to_lower_char(c)).collect())
+    }
+}
+
+/// A map keyed by nickname or channel name, with keys compared under a [`CaseMapping`].
+///
+/// Keys are returned as first inserted. Take the mapping from
+/// [`ISupport::casemapping`](crate::isupport::ISupport::casemapping) once the server has
+/// sent it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaseMappedMap<V> {
+    mapping: CaseMapping,
+    entries: BTreeMap<String, (String, V)>,
+}
+
+impl<V> CaseMappedMap<V> {
+    pub fn new(mapping: CaseMapping) -> Self {
+        Self {
+            mapping,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    pub fn mapping(&self) -> CaseMapping {
+        self.mapping
+    }
+
+    /// Switches to `mapping`, e.g. once `RPL_ISUPPORT` arrives. Of the keys that now fold to
+    /// the same name, only one is kept.
+    pub fn set_mapping(&mut self, mapping: CaseMapping) {
+        if mapping == self.mapping {
+            return;
+        }
+
+        self.mapping = mapping;
+        let entries = core::mem::take(&mut self.entries);
+        for (_, (key, value)) in entries {
+            self.insert(key, value);
+        }
+    }
+
+    /// Inserts `value` under `key`, returning the previous value. An existing key keeps
+    /// the spelling it was first inserted with.
+    pub fn insert(&mut self, key: impl Into<String>, value: V) -> Option<V> {
+        let key = key.into();
+        let folded = to_lower(&key, self.mapping).into_owned();
+
+        match self.entries.get_mut(&folded) {
+            Some((_, old)) => Some(core::mem::replace(old, value)),
+            None => {
+                self.entries.insert(folded, (key, value));
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.entries
+            .get(to_lower(key, self.mapping).as_ref())
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.entries
+            .get_mut(to_lower(key, self.mapping).as_ref())
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the key as it was inserted.
+    pub fn key(&self, key: &str) -> Option<&str> {
+        self.entries
+            .get(to_lower(key, self.mapping).as_ref())
+            .map(|(key, _)| key.as_str())
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        self.entries
+            .remove(to_lower(key, self.mapping).as_ref())
+            .map(|(_, value)| value)
+    }
+
+    /// Moves the value of `from` to `to`, e.g. after a `NICK` change.
+    pub fn rename(&mut self, from: &str, to: impl Into<String>) -> bool {
+        match self.entries.remove(to_lower(from, self.mapping).as_ref()) {
+            Some((_, value)) => {
+                let to = to.into();
+                self.entries
+                    .insert(to_lower(&to, self.mapping).into_owned(), (to, value));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the keys as inserted and their values, sorted by folded key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &V)> {
+        self.entries
+            .values()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eq_ignore_case, to_lower, CaseMappedMap, CaseMapping};
+    use crate::compat::Cow;
+
+    #[test]
+    fn mappings() {
+        let name = "Nick[]\\~";
+
+        assert_eq!("nick[]\\~", to_lower(name, CaseMapping::Ascii));
+        assert_eq!("nick{}|^", to_lower(name, CaseMapping::Rfc1459));
+        assert_eq!("nick{}|~", to_lower(name, CaseMapping::StrictRfc1459));
+
+        assert!(eq_ignore_case("a~", "A^", CaseMapping::Rfc1459));
+        assert!(!eq_ignore_case("a~", "A^", CaseMapping::StrictRfc1459));
+        assert!(!eq_ignore_case("a", "ab", CaseMapping::Rfc1459));
+        assert!(eq_ignore_case("ÉA", "Éa", CaseMapping::Ascii));
+        assert!(!eq_ignore_case("É", "é", CaseMapping::Ascii));
+
+        assert!(matches!(
+            to_lower("nick{}", CaseMapping::Rfc1459),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn parse() {
+        for mapping in [
+            CaseMapping::Ascii,
+            CaseMapping::Rfc1459,
+            CaseMapping::StrictRfc1459,
+        ] {
+            assert_eq!(Some(mapping), CaseMapping::parse(mapping.as_str()));
+        }
+        assert_eq!(None, CaseMapping::parse("rfc7613"));
+    }
+
+    #[test]
+    fn map() {
+        let mut map = CaseMappedMap::new(CaseMapping::Rfc1459);
+
+        assert_eq!(None, map.insert("#Rust[1]", 1));
+        assert_eq!(Some(1), map.insert("#rust{1}", 2));
+        assert_eq!(1, map.len());
+        assert_eq!(Some("#Rust[1]"), map.key("#RUST{1}"));
+
+        *map.get_mut("#rust[1]").unwrap() += 1;
+        assert_eq!(Some(&3), map.get("#rust{1}"));
+
+        assert!(map.rename("#RUST{1}", "#Go"));
+        assert!(!map.contains_key("#rust[1]"));
+        assert_eq!(vec![("#Go", &3)], map.iter().collect::<Vec<_>>());
+
+        assert_eq!(Some(3), map.remove("#go"));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn set_mapping() {
+        let mut map = CaseMappedMap::new(CaseMapping::Ascii);
+        map.insert("a[", 1);
+        map.insert("A{", 2);
+        assert_eq!(2, map.len());
+
+        map.set_mapping(CaseMapping::Rfc1459);
+        assert_eq!(1, map.len());
+        assert_eq!(Some(&2), map.get("a["));
+    }
+}
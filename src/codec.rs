@@ -0,0 +1,304 @@
+//! [`tokio_util::codec`] integration.
+//!
+//! [`IrcCodec`] frames an async byte stream into lines, decodes each line into an owned
+//! message type, and encodes any [`ToMessage`] value into outgoing lines.
+use core::marker::PhantomData;
+
+use bytes::{BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{
+    error::{CodecError, FrameError},
+    framer::{LineDecoder, DEFAULT_MAX_LINE_LENGTH},
+    message::{
+        de::FromMessage,
+        ser::{IRCSerializer, ToMessage},
+    },
+    IRCError, Limits, MessageBuf,
+};
+
+/// Codec for use with `tokio_util::codec::{FramedRead, FramedWrite, Framed}`.
+///
+/// Decodes complete lines into `T`, which must own its data and defaults to [`MessageBuf`].
+/// Encodes any [`ToMessage`] value, appending `\r\n` when the value does not end the message
+/// itself.
+///
+/// Lines longer than the configured maximum are rejected in both directions, as are lines
+/// over the [`with_limits`](Self::with_limits) limits, if set.
+///
+/// Decode errors end a `FramedRead` stream, so `T` should accept every message the peer may
+/// send.
+///
+/// # Examples
+///
+/// ```rust
+/// use bytes::BytesMut;
+/// use ircv3_parse::{codec::IrcCodec, Commands, DeError, Message, MessageBuilder};
+/// use ircv3_parse::message::de::FromMessage;
+/// use tokio_util::codec::{Decoder, Encoder};
+///
+/// struct Ping(String);
+///
+/// impl<'a> FromMessage<'a> for Ping {
+///     fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+///         Ok(Ping(msg.params().trailing.to_string()))
+///     }
+/// }
+///
+/// let mut codec = IrcCodec::<Ping>::new();
+///
+/// let mut buf = BytesMut::from(&b"PING :irc.example.com\r\n"[..]);
+/// let ping = codec.decode(&mut buf)?.unwrap();
+/// assert_eq!("irc.example.com", ping.0);
+///
+/// let mut pong = MessageBuilder::new(Commands::PONG);
+/// pong.set_trailing(&ping.0)?;
+///
+/// let mut out = BytesMut::new();
+/// codec.encode(pong, &mut out)?;
+/// assert_eq!(&b"PONG :irc.example.com\r\n"[..], &out[..]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct IrcCodec<T = MessageBuf> {
+    decoder: LineDecoder,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> IrcCodec<T> {
+    /// Creates a codec using [`DEFAULT_MAX_LINE_LENGTH`].
+    pub fn new() -> Self {
+        Self::with_max_line_length(DEFAULT_MAX_LINE_LENGTH)
+    }
+
+    /// Creates a codec that rejects lines longer than `max` bytes, excluding the line ending.
+    pub fn with_max_line_length(max: usize) -> Self {
+        Self {
+            decoder: LineDecoder::new(max),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Rejects incoming and outgoing lines over `limits`. Defaults to [`Limits::NONE`].
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.decoder.set_limits(limits);
+        self
+    }
+
+    #[inline]
+    pub fn max_line_length(&self) -> usize {
+        self.decoder.max_line_length()
+    }
+
+    #[inline]
+    pub fn limits(&self) -> Limits {
+        self.decoder.limits()
+    }
+}
+
+impl<T> Default for IrcCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for IrcCodec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            decoder: self.decoder.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Decoder for IrcCodec<T>
+where
+    T: for<'a> FromMessage<'a>,
+{
+    type Item = T;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let Some(line) = self.decoder.decode(src)? else {
+            return Ok(None);
+        };
+
+        let input = core::str::from_utf8(&line).map_err(|e| IRCError::InvalidUtf8 {
+            valid_up_to: e.valid_up_to(),
+        })?;
+
+        Ok(Some(<T as FromMessage>::from_str(input)?))
+    }
+}
+
+impl<T, M> Encoder<M> for IrcCodec<T>
+where
+    M: ToMessage,
+{
+    type Error = CodecError;
+
+    fn encode(&mut self, item: M, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut buffer = dst.split_off(dst.len());
+        buffer.reserve(item.serialized_size() + 2);
+
+        let mut serializer = IRCSerializer::with_buffer(buffer).with_limits(self.limits());
+        item.to_message(&mut serializer)?;
+
+        let mut buffer = serializer.into_buffer();
+        if !buffer.ends_with(b"\r\n") {
+            buffer.put_slice(b"\r\n");
+        }
+
+        let max = self.max_line_length();
+        if buffer.len() - 2 > max {
+            return Err(FrameError::LineTooLong { max }.into());
+        }
+
+        dst.unsplit(buffer);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    use super::IrcCodec;
+    use crate::{
+        error::{CodecError, FrameError},
+        message::de::FromMessage,
+        Commands, DeError, IRCError, Limits, Message, MessageBuilder,
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct Line {
+        command: String,
+        trailing: String,
+    }
+
+    impl<'a> FromMessage<'a> for Line {
+        fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+            Ok(Self {
+                command: msg.command().to_string(),
+                trailing: msg.params().trailing.to_string(),
+            })
+        }
+    }
+
+    #[test]
+    fn decode() {
+        let mut codec = IrcCodec::<Line>::new();
+        let mut buf = BytesMut::from(&b"PING :a\r\nPRIVMSG #channel :b\nPI"[..]);
+
+        let line = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!("PING", line.command);
+        assert_eq!("a", line.trailing);
+
+        let line = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!("PRIVMSG", line.command);
+        assert_eq!("b", line.trailing);
+
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        assert_eq!(&b"PI"[..], &buf[..]);
+    }
+
+    #[test]
+    fn decode_message_buf() {
+        let mut codec: IrcCodec = IrcCodec::new();
+        let mut buf = BytesMut::from(&b"@id=1 :nick PRIVMSG #channel :hi\r\n"[..]);
+
+        let msg = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!("1", msg.tags().unwrap().get("id").unwrap().as_str());
+        assert_eq!("nick", msg.source().unwrap().name);
+        assert_eq!("hi", msg.params().trailing.as_str());
+    }
+
+    #[test]
+    fn decode_line_too_long() {
+        let mut codec = IrcCodec::<Line>::with_max_line_length(4);
+        let mut buf = BytesMut::from(&b"PRIVMSG\r\nPING\r\n"[..]);
+
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::Frame(FrameError::LineTooLong { max: 4 }))
+        ));
+        assert_eq!("PING", codec.decode(&mut buf).unwrap().unwrap().command);
+    }
+
+    #[test]
+    fn decode_limits() {
+        let mut codec = IrcCodec::<Line>::new().with_limits(Limits::SERVER);
+        let line = format!("PRIVMSG #channel :{}\r\nPING :a\r\n", "a".repeat(493));
+        let mut buf = BytesMut::from(line.as_bytes());
+
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::Frame(FrameError::Message(
+                IRCError::BodyTooLong { len: 513, max: 512 }
+            )))
+        ));
+        assert_eq!("PING", codec.decode(&mut buf).unwrap().unwrap().command);
+    }
+
+    #[test]
+    fn decode_parse_error() {
+        let mut codec = IrcCodec::<Line>::new();
+        let mut buf = BytesMut::from(&b"@tags-only\r\n"[..]);
+
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::Extract(DeError::ParseError(_)))
+        ));
+    }
+
+    #[test]
+    fn encode_appends_crlf() {
+        let mut codec = IrcCodec::<Line>::new();
+        let mut dst = BytesMut::from(&b"PING\r\n"[..]);
+
+        let mut msg = MessageBuilder::new(Commands::PRIVMSG);
+        msg.add_param("#channel").unwrap();
+        msg.set_trailing("hi").unwrap();
+        codec.encode(msg, &mut dst).unwrap();
+
+        codec.encode(&Commands::QUIT, &mut dst).unwrap();
+
+        assert_eq!(&b"PING\r\nPRIVMSG #channel :hi\r\nQUIT\r\n"[..], &dst[..]);
+    }
+
+    #[test]
+    fn encode_limits() {
+        let mut codec = IrcCodec::<Line>::new().with_limits(Limits::CLIENT);
+        let mut dst = BytesMut::new();
+        let long = "a".repeat(493);
+
+        let mut msg = MessageBuilder::new(Commands::PRIVMSG);
+        msg.add_param("#channel").unwrap();
+        msg.set_trailing(&long).unwrap();
+        assert!(matches!(
+            codec.encode(&msg, &mut dst),
+            Err(CodecError::Message(IRCError::BodyTooLong { max: 512, .. }))
+        ));
+        assert!(dst.is_empty());
+
+        msg.set_trailing(&long[1..]).unwrap();
+        codec.encode(&msg, &mut dst).unwrap();
+        assert_eq!(512, dst.len());
+    }
+
+    #[test]
+    fn encode_line_too_long() {
+        let mut codec = IrcCodec::<Line>::with_max_line_length(8);
+        let mut dst = BytesMut::new();
+
+        let mut msg = MessageBuilder::new(Commands::PRIVMSG);
+        msg.set_trailing("too long").unwrap();
+
+        assert!(matches!(
+            codec.encode(msg, &mut dst),
+            Err(CodecError::Frame(FrameError::LineTooLong { max: 8 }))
+        ));
+        assert!(dst.is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+use core::convert::Infallible;
+
+use crate::message::de::FromValue;
+
+/// A capability from a `CAP` list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capability<'a> {
+    pub name: &'a str,
+    /// Value after `=`, e.g. `PLAIN,EXTERNAL` for `sasl=PLAIN,EXTERNAL`.
+    pub value: Option<&'a str>,
+    /// Set by a leading `-`, which disables the capability in `CAP ACK` and `CAP REQ`.
+    pub disabled: bool,
+}
+
+/// Iterator over a space-separated capability list, as sent with `CAP LS`, `ACK`, `NAK`,
+/// `NEW` and `DEL`.
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::components::{CapList, Capability};
+///
+/// let caps = CapList::new("multi-prefix sasl=PLAIN,EXTERNAL -away-notify");
+///
+/// assert_eq!(Some("PLAIN,EXTERNAL"), caps.get("sasl").and_then(|cap| cap.value));
+/// assert!(caps.contains("multi-prefix"));
+///
+/// let all: Vec<Capability> = caps.collect();
+/// assert_eq!(
+///     Capability { name: "away-notify", value: None, disabled: true },
+///     all[2]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapList<'a> {
+    rest: &'a str,
+}
+
+impl<'a> CapList<'a> {
+    pub fn new(list: &'a str) -> Self {
+        Self { rest: list }
+    }
+
+    /// Returns the remaining list as written.
+    pub fn as_str(&self) -> &'a str {
+        self.rest
+    }
+
+    /// Returns the capability called `name`.
+    pub fn get(&self, name: &str) -> Option<Capability<'a>> {
+        let mut caps = *self;
+        caps.find(|cap| cap.name == name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Returns `true` if there are no capabilities left.
+    pub fn is_empty(&self) -> bool {
+        let mut caps = *self;
+        caps.next().is_none()
+    }
+}
+
+impl<'a> Iterator for CapList<'a> {
+    type Item = Capability<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest.trim_start_matches(' ');
+        if rest.is_empty() {
+            self.rest = rest;
+            return None;
+        }
+
+        let (cap, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+        self.rest = rest;
+
+        let (cap, disabled) = match cap.strip_prefix('-') {
+            Some(cap) => (cap, true),
+            None => (cap, false),
+        };
+        let (name, value) = match cap.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (cap, None),
+        };
+
+        Some(Capability {
+            name,
+            value,
+            disabled,
+        })
+    }
+}
+
+impl<'a> FromValue<'a> for CapList<'a> {
+    type Error = Infallible;
+
+    fn from_value(value: &'a str) -> Result<Self, Self::Error> {
+        Ok(Self::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CapList, Capability};
+
+    #[test]
+    fn values_and_removal() {
+        let caps: Vec<_> = CapList::new("a  b=1,2 -c d= ").collect();
+
+        assert_eq!(
+            vec![
+                Capability {
+                    name: "a",
+                    value: None,
+                    disabled: false
+                },
+                Capability {
+                    name: "b",
+                    value: Some("1,2"),
+                    disabled: false
+                },
+                Capability {
+                    name: "c",
+                    value: None,
+                    disabled: true
+                },
+                Capability {
+                    name: "d",
+                    value: Some(""),
+                    disabled: false
+                },
+            ],
+            caps
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert!(CapList::new("").is_empty());
+        assert!(CapList::new("   ").is_empty());
+        assert!(CapList::new("").get("sasl").is_none());
+    }
+}
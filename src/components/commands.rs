@@ -1,8 +1,13 @@
+use core::convert::Infallible;
+use core::str::FromStr;
+
 use crate::compat::{Display, FmtResult, Formatter, String, ToString};
 
 use crate::{error::CommandError, validators};
 
-/// IRC command types following RFC 1459 and RFC 2812.
+use super::Numeric;
+
+/// IRC command types following RFC 1459, RFC 2812 and IRCv3 extensions.
 ///
 /// # Case Insensitivity
 ///
@@ -61,6 +66,32 @@ pub enum Commands<'a> {
     LINKS,
     USERHOST,
     WALLOPS,
+    // IRCv3 Extensions
+    TAGMSG,
+    BATCH,
+    ACCOUNT,
+    CHGHOST,
+    SETNAME,
+    CHATHISTORY,
+    MONITOR,
+    FAIL,
+    WARN,
+    NOTE,
+    MARKREAD,
+    REDACT,
+    WEBIRC,
+    RENAME,
+    ACK,
+    // Twitch
+    CLEARCHAT,
+    CLEARMSG,
+    GLOBALUSERSTATE,
+    USERSTATE,
+    ROOMSTATE,
+    USERNOTICE,
+    WHISPER,
+    HOSTTARGET,
+    RECONNECT,
     CUSTOM(&'a str),
 }
 
@@ -112,6 +143,30 @@ impl<'a> Commands<'a> {
             Self::LINKS => "LINKS",
             Self::USERHOST => "USERHOST",
             Self::WALLOPS => "WALLOPS",
+            Self::TAGMSG => "TAGMSG",
+            Self::BATCH => "BATCH",
+            Self::ACCOUNT => "ACCOUNT",
+            Self::CHGHOST => "CHGHOST",
+            Self::SETNAME => "SETNAME",
+            Self::CHATHISTORY => "CHATHISTORY",
+            Self::MONITOR => "MONITOR",
+            Self::FAIL => "FAIL",
+            Self::WARN => "WARN",
+            Self::NOTE => "NOTE",
+            Self::MARKREAD => "MARKREAD",
+            Self::REDACT => "REDACT",
+            Self::WEBIRC => "WEBIRC",
+            Self::RENAME => "RENAME",
+            Self::ACK => "ACK",
+            Self::CLEARCHAT => "CLEARCHAT",
+            Self::CLEARMSG => "CLEARMSG",
+            Self::GLOBALUSERSTATE => "GLOBALUSERSTATE",
+            Self::USERSTATE => "USERSTATE",
+            Self::ROOMSTATE => "ROOMSTATE",
+            Self::USERNOTICE => "USERNOTICE",
+            Self::WHISPER => "WHISPER",
+            Self::HOSTTARGET => "HOSTTARGET",
+            Self::RECONNECT => "RECONNECT",
             Self::CUSTOM(custom) => custom,
         }
     }
@@ -162,6 +217,30 @@ impl<'a> Commands<'a> {
             Self::LINKS => 5,
             Self::USERHOST => 8,
             Self::WALLOPS => 7,
+            Self::TAGMSG => 6,
+            Self::BATCH => 5,
+            Self::ACCOUNT => 7,
+            Self::CHGHOST => 7,
+            Self::SETNAME => 7,
+            Self::CHATHISTORY => 11,
+            Self::MONITOR => 7,
+            Self::FAIL => 4,
+            Self::WARN => 4,
+            Self::NOTE => 4,
+            Self::MARKREAD => 8,
+            Self::REDACT => 6,
+            Self::WEBIRC => 6,
+            Self::RENAME => 6,
+            Self::ACK => 3,
+            Self::CLEARCHAT => 9,
+            Self::CLEARMSG => 8,
+            Self::GLOBALUSERSTATE => 15,
+            Self::USERSTATE => 9,
+            Self::ROOMSTATE => 9,
+            Self::USERNOTICE => 10,
+            Self::WHISPER => 7,
+            Self::HOSTTARGET => 10,
+            Self::RECONNECT => 9,
             Self::CUSTOM(unknown) => unknown.len(),
         }
     }
@@ -210,10 +289,52 @@ impl<'a> Commands<'a> {
             Self::LINKS => b"LINKS",
             Self::USERHOST => b"USERHOST",
             Self::WALLOPS => b"WALLOPS",
+            Self::TAGMSG => b"TAGMSG",
+            Self::BATCH => b"BATCH",
+            Self::ACCOUNT => b"ACCOUNT",
+            Self::CHGHOST => b"CHGHOST",
+            Self::SETNAME => b"SETNAME",
+            Self::CHATHISTORY => b"CHATHISTORY",
+            Self::MONITOR => b"MONITOR",
+            Self::FAIL => b"FAIL",
+            Self::WARN => b"WARN",
+            Self::NOTE => b"NOTE",
+            Self::MARKREAD => b"MARKREAD",
+            Self::REDACT => b"REDACT",
+            Self::WEBIRC => b"WEBIRC",
+            Self::RENAME => b"RENAME",
+            Self::ACK => b"ACK",
+            Self::CLEARCHAT => b"CLEARCHAT",
+            Self::CLEARMSG => b"CLEARMSG",
+            Self::GLOBALUSERSTATE => b"GLOBALUSERSTATE",
+            Self::USERSTATE => b"USERSTATE",
+            Self::ROOMSTATE => b"ROOMSTATE",
+            Self::USERNOTICE => b"USERNOTICE",
+            Self::WHISPER => b"WHISPER",
+            Self::HOSTTARGET => b"HOSTTARGET",
+            Self::RECONNECT => b"RECONNECT",
             Self::CUSTOM(unknown) => unknown.as_bytes(),
         }
     }
 
+    /// Returns the [`Numeric`] reply if this is a numeric command.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ircv3_parse::{components::Numeric, Commands};
+    ///
+    /// assert_eq!(Some(Numeric::RPL_WELCOME), Commands::from("001").numeric());
+    /// assert_eq!(None, Commands::PRIVMSG.numeric());
+    /// ```
+    #[inline]
+    pub fn numeric(&self) -> Option<Numeric> {
+        match self {
+            Self::NUMERIC(num) => num.parse::<u16>().ok().map(Numeric::from),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn is_ping(&self) -> bool {
         *self == Self::PING
@@ -234,6 +355,93 @@ impl<'a> Commands<'a> {
         *self == Self::NOTICE
     }
 
+    #[inline]
+    pub fn is_tagmsg(&self) -> bool {
+        *self == Self::TAGMSG
+    }
+
+    #[inline]
+    pub fn is_batch(&self) -> bool {
+        *self == Self::BATCH
+    }
+
+    /// Returns the [`CommandCategory`] this command belongs to.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ircv3_parse::{components::CommandCategory, Commands};
+    ///
+    /// assert_eq!(CommandCategory::Messaging, Commands::PRIVMSG.category());
+    /// assert_eq!(CommandCategory::Channel, Commands::MODE.category());
+    /// assert_eq!(CommandCategory::Extension, Commands::from("tagmsg").category());
+    /// assert_eq!(CommandCategory::Numeric, Commands::from("001").category());
+    /// assert_eq!(CommandCategory::Unknown, Commands::from("FOO").category());
+    /// ```
+    pub fn category(&self) -> CommandCategory {
+        use CommandCategory::*;
+
+        match self {
+            Self::NUMERIC(_) => Numeric,
+            Self::CAP
+            | Self::AUTHENTICATE
+            | Self::PASS
+            | Self::NICK
+            | Self::USER
+            | Self::PING
+            | Self::PONG
+            | Self::OPER
+            | Self::QUIT
+            | Self::ERROR => Connection,
+            Self::JOIN
+            | Self::PART
+            | Self::TOPIC
+            | Self::NAMES
+            | Self::LIST
+            | Self::INVITE
+            | Self::KICK
+            | Self::MODE => Channel,
+            Self::MOTD
+            | Self::VERSION
+            | Self::ADMIN
+            | Self::CONNECT
+            | Self::LUSERS
+            | Self::TIME
+            | Self::STATS
+            | Self::HELP
+            | Self::INFO => Server,
+            Self::PRIVMSG | Self::NOTICE => Messaging,
+            Self::WHO | Self::WHOIS | Self::WHOWAS => UserQuery,
+            Self::KILL | Self::REHASH | Self::RESTART | Self::SQUIT => Operator,
+            Self::AWAY | Self::LINKS | Self::USERHOST | Self::WALLOPS => Optional,
+            Self::TAGMSG
+            | Self::BATCH
+            | Self::ACCOUNT
+            | Self::CHGHOST
+            | Self::SETNAME
+            | Self::CHATHISTORY
+            | Self::MONITOR
+            | Self::FAIL
+            | Self::WARN
+            | Self::NOTE
+            | Self::MARKREAD
+            | Self::REDACT
+            | Self::WEBIRC
+            | Self::RENAME
+            | Self::ACK => Extension,
+            Self::CLEARCHAT
+            | Self::CLEARMSG
+            | Self::GLOBALUSERSTATE
+            | Self::USERSTATE
+            | Self::ROOMSTATE
+            | Self::USERNOTICE
+            | Self::WHISPER
+            | Self::HOSTTARGET
+            | Self::RECONNECT => Twitch,
+            Self::CUSTOM(_) => Unknown,
+        }
+    }
+
     pub fn validate(&self) -> Result<(), CommandError> {
         validators::command(self.as_str())
     }
@@ -292,6 +500,30 @@ impl<'a> From<&'a str> for Commands<'a> {
             "LINKS" => Self::LINKS,
             "USERHOST" => Self::USERHOST,
             "WALLOPS" => Self::WALLOPS,
+            "TAGMSG" => Self::TAGMSG,
+            "BATCH" => Self::BATCH,
+            "ACCOUNT" => Self::ACCOUNT,
+            "CHGHOST" => Self::CHGHOST,
+            "SETNAME" => Self::SETNAME,
+            "CHATHISTORY" => Self::CHATHISTORY,
+            "MONITOR" => Self::MONITOR,
+            "FAIL" => Self::FAIL,
+            "WARN" => Self::WARN,
+            "NOTE" => Self::NOTE,
+            "MARKREAD" => Self::MARKREAD,
+            "REDACT" => Self::REDACT,
+            "WEBIRC" => Self::WEBIRC,
+            "RENAME" => Self::RENAME,
+            "ACK" => Self::ACK,
+            "CLEARCHAT" => Self::CLEARCHAT,
+            "CLEARMSG" => Self::CLEARMSG,
+            "GLOBALUSERSTATE" => Self::GLOBALUSERSTATE,
+            "USERSTATE" => Self::USERSTATE,
+            "ROOMSTATE" => Self::ROOMSTATE,
+            "USERNOTICE" => Self::USERNOTICE,
+            "WHISPER" => Self::WHISPER,
+            "HOSTTARGET" => Self::HOSTTARGET,
+            "RECONNECT" => Self::RECONNECT,
         );
 
         if value.parse::<u16>().is_ok() {
@@ -362,6 +594,33 @@ impl<'a> crate::message::ser::ToMessage for Commands<'a> {
     }
 }
 
+/// Group of related commands, as returned by [`Commands::category()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandCategory {
+    /// Connection registration and lifetime, e.g. `NICK`, `CAP`, `PING`.
+    Connection,
+    /// Channel operations and modes, e.g. `JOIN`, `TOPIC`, `MODE`.
+    Channel,
+    /// Server queries and commands, e.g. `MOTD`, `VERSION`.
+    Server,
+    /// `PRIVMSG` and `NOTICE`.
+    Messaging,
+    /// User-based queries, e.g. `WHO`, `WHOIS`.
+    UserQuery,
+    /// Operator messages, e.g. `KILL`, `SQUIT`.
+    Operator,
+    /// Optional messages, e.g. `AWAY`, `WALLOPS`.
+    Optional,
+    /// IRCv3 extension commands, e.g. `TAGMSG`, `BATCH`.
+    Extension,
+    /// Twitch-specific commands, e.g. `USERNOTICE`, `ROOMSTATE`.
+    Twitch,
+    /// Numeric replies.
+    Numeric,
+    /// Commands not known to this crate.
+    Unknown,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CapSubCommands {
     LS,
@@ -421,6 +680,14 @@ impl From<&str> for CapSubCommands {
     }
 }
 
+impl FromStr for CapSubCommands {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
 impl Display for CapSubCommands {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         f.write_str(self.as_str())
@@ -450,7 +717,7 @@ impl serde::Serialize for CapSubCommands {
 #[cfg(test)]
 mod tests {
     use crate::{
-        components::Commands,
+        components::{CommandCategory, Commands},
         message::{de::FromMessage, ser::ToMessage},
     };
 
@@ -466,6 +733,57 @@ mod tests {
         assert_eq!(Commands::from("PiNg"), Commands::PING);
     }
 
+    #[test]
+    fn ircv3_commands() {
+        for cmd in [
+            Commands::TAGMSG,
+            Commands::BATCH,
+            Commands::ACCOUNT,
+            Commands::CHGHOST,
+            Commands::SETNAME,
+            Commands::CHATHISTORY,
+            Commands::MONITOR,
+            Commands::FAIL,
+            Commands::WARN,
+            Commands::NOTE,
+            Commands::MARKREAD,
+            Commands::REDACT,
+            Commands::WEBIRC,
+            Commands::RENAME,
+            Commands::ACK,
+        ] {
+            let parsed = Commands::from(cmd.as_str());
+            assert!(matches!(parsed, c if c.as_str() == cmd.as_str()));
+            assert!(!matches!(parsed, Commands::CUSTOM(_)));
+            assert_eq!(cmd.as_str().len(), cmd.len());
+            assert_eq!(cmd.as_str().as_bytes(), cmd.as_bytes());
+            assert_eq!(CommandCategory::Extension, cmd.category());
+        }
+
+        assert!(Commands::from("tagmsg").is_tagmsg());
+        assert!(Commands::from("BATCH").is_batch());
+    }
+
+    #[test]
+    fn twitch_commands() {
+        for cmd in [
+            Commands::CLEARCHAT,
+            Commands::CLEARMSG,
+            Commands::GLOBALUSERSTATE,
+            Commands::USERSTATE,
+            Commands::ROOMSTATE,
+            Commands::USERNOTICE,
+            Commands::WHISPER,
+            Commands::HOSTTARGET,
+            Commands::RECONNECT,
+        ] {
+            assert_eq!(cmd, Commands::from(cmd.as_str().to_lowercase().as_str()));
+            assert!(!matches!(Commands::from(cmd.as_str()), Commands::CUSTOM(_)));
+            assert_eq!(cmd.as_str().len(), cmd.len());
+            assert_eq!(CommandCategory::Twitch, cmd.category());
+        }
+    }
+
     #[test]
     fn partialeq_case_insensitive() {
         let cmd = Commands::PRIVMSG;
@@ -1,9 +1,13 @@
+mod capabilities;
 mod commands;
+mod numeric;
 mod params;
 mod source;
 mod tags;
 
-pub use commands::{CapSubCommands, Commands};
+pub use capabilities::{CapList, Capability};
+pub use commands::{CapSubCommands, CommandCategory, Commands};
+pub use numeric::Numeric;
 pub use params::{Middles, Params};
 pub use source::Source;
 pub use tags::{TagValue, Tags};
@@ -0,0 +1,278 @@
+use crate::compat::{Display, FmtResult, Formatter};
+
+macro_rules! numerics {
+    ($($name:ident = $code:literal => $description:literal,)*) => {
+        /// Numeric replies from RFC 1459, RFC 2812 and modern IRC extensions.
+        ///
+        /// Obtained from a message with [`Commands::numeric()`](crate::Commands::numeric).
+        /// Codes without a known name are kept as [`Numeric::UNKNOWN`].
+        ///
+        /// # Examples
+        ///
+        /// ```rust
+        /// use ircv3_parse::components::Numeric;
+        ///
+        /// let msg = ircv3_parse::parse(":irc.example.com 433 * nick :Nickname is already in use")?;
+        /// let numeric = msg.command().numeric().unwrap();
+        ///
+        /// assert_eq!(Numeric::ERR_NICKNAMEINUSE, numeric);
+        /// assert_eq!(433, numeric.code());
+        /// assert_eq!(Some("ERR_NICKNAMEINUSE"), numeric.name());
+        /// assert!(numeric.is_error());
+        /// # Ok::<(), Box<dyn std::error::Error>>(())
+        /// ```
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Numeric {
+            $(
+                #[doc = $description]
+                $name,
+            )*
+            /// Any other three-digit code.
+            UNKNOWN(u16),
+        }
+
+        impl Numeric {
+            /// Returns the numeric code.
+            #[inline]
+            pub fn code(&self) -> u16 {
+                match self {
+                    $(Self::$name => $code,)*
+                    Self::UNKNOWN(code) => *code,
+                }
+            }
+
+            /// Returns the conventional name, e.g. `RPL_WELCOME`.
+            pub fn name(&self) -> Option<&'static str> {
+                match self {
+                    $(Self::$name => Some(stringify!($name)),)*
+                    Self::UNKNOWN(_) => None,
+                }
+            }
+
+            pub fn get_description(&self) -> &'static str {
+                match self {
+                    $(Self::$name => $description,)*
+                    Self::UNKNOWN(_) => "Unknown numeric reply",
+                }
+            }
+        }
+
+        impl From<u16> for Numeric {
+            fn from(code: u16) -> Self {
+                match code {
+                    $($code => Self::$name,)*
+                    _ => Self::UNKNOWN(code),
+                }
+            }
+        }
+    };
+}
+
+numerics! {
+    RPL_WELCOME = 1 => "Welcome to the network",
+    RPL_YOURHOST = 2 => "Name and version of the server",
+    RPL_CREATED = 3 => "Server creation date",
+    RPL_MYINFO = 4 => "Server name, version and available modes",
+    RPL_ISUPPORT = 5 => "Parameters supported by the server",
+    RPL_BOUNCE = 10 => "Redirect to another server",
+    RPL_STATSCOMMANDS = 212 => "Command usage statistics",
+    RPL_ENDOFSTATS = 219 => "End of STATS report",
+    RPL_UMODEIS = 221 => "Current user modes",
+    RPL_STATSUPTIME = 242 => "Server uptime",
+    RPL_LUSERCLIENT = 251 => "Number of users and servers",
+    RPL_LUSEROP = 252 => "Number of operators online",
+    RPL_LUSERUNKNOWN = 253 => "Number of unknown connections",
+    RPL_LUSERCHANNELS = 254 => "Number of channels formed",
+    RPL_LUSERME = 255 => "Number of local clients and servers",
+    RPL_ADMINME = 256 => "Start of ADMIN reply",
+    RPL_ADMINLOC1 = 257 => "Server location",
+    RPL_ADMINLOC2 = 258 => "Server hosting details",
+    RPL_ADMINEMAIL = 259 => "Server administrator email",
+    RPL_TRYAGAIN = 263 => "Command dropped, try again later",
+    RPL_LOCALUSERS = 265 => "Number of local users",
+    RPL_GLOBALUSERS = 266 => "Number of global users",
+    RPL_WHOISCERTFP = 276 => "WHOIS client certificate fingerprint",
+    RPL_NONE = 300 => "Dummy reply",
+    RPL_AWAY = 301 => "Target user is away",
+    RPL_USERHOST = 302 => "USERHOST reply",
+    RPL_UNAWAY = 305 => "No longer marked as away",
+    RPL_NOWAWAY = 306 => "Marked as away",
+    RPL_WHOISREGNICK = 307 => "WHOIS nickname is registered",
+    RPL_WHOISUSER = 311 => "WHOIS user information",
+    RPL_WHOISSERVER = 312 => "WHOIS server information",
+    RPL_WHOISOPERATOR = 313 => "WHOIS user is an operator",
+    RPL_WHOWASUSER = 314 => "WHOWAS user information",
+    RPL_ENDOFWHO = 315 => "End of WHO list",
+    RPL_WHOISIDLE = 317 => "WHOIS idle time",
+    RPL_ENDOFWHOIS = 318 => "End of WHOIS list",
+    RPL_WHOISCHANNELS = 319 => "WHOIS channels",
+    RPL_WHOISSPECIAL = 320 => "WHOIS special information",
+    RPL_LISTSTART = 321 => "Start of LIST reply",
+    RPL_LIST = 322 => "LIST channel entry",
+    RPL_LISTEND = 323 => "End of LIST reply",
+    RPL_CHANNELMODEIS = 324 => "Current channel modes",
+    RPL_CREATIONTIME = 329 => "Channel creation time",
+    RPL_WHOISACCOUNT = 330 => "WHOIS account name",
+    RPL_NOTOPIC = 331 => "No topic is set",
+    RPL_TOPIC = 332 => "Channel topic",
+    RPL_TOPICWHOTIME = 333 => "Who set the topic and when",
+    RPL_INVITELIST = 336 => "Channel invited to",
+    RPL_ENDOFINVITELIST = 337 => "End of invite list",
+    RPL_WHOISACTUALLY = 338 => "WHOIS actual host",
+    RPL_INVITING = 341 => "Invitation sent",
+    RPL_INVEXLIST = 346 => "Invite exception list entry",
+    RPL_ENDOFINVEXLIST = 347 => "End of invite exception list",
+    RPL_EXCEPTLIST = 348 => "Ban exception list entry",
+    RPL_ENDOFEXCEPTLIST = 349 => "End of ban exception list",
+    RPL_VERSION = 351 => "Server version",
+    RPL_WHOREPLY = 352 => "WHO reply entry",
+    RPL_NAMREPLY = 353 => "NAMES reply entry",
+    RPL_WHOSPCRPL = 354 => "WHOX reply entry",
+    RPL_LINKS = 364 => "LINKS reply entry",
+    RPL_ENDOFLINKS = 365 => "End of LINKS list",
+    RPL_ENDOFNAMES = 366 => "End of NAMES list",
+    RPL_BANLIST = 367 => "Ban list entry",
+    RPL_ENDOFBANLIST = 368 => "End of ban list",
+    RPL_ENDOFWHOWAS = 369 => "End of WHOWAS list",
+    RPL_INFO = 371 => "INFO reply line",
+    RPL_MOTD = 372 => "Message of the day line",
+    RPL_ENDOFINFO = 374 => "End of INFO reply",
+    RPL_MOTDSTART = 375 => "Start of message of the day",
+    RPL_ENDOFMOTD = 376 => "End of message of the day",
+    RPL_WHOISHOST = 378 => "WHOIS connecting host",
+    RPL_WHOISMODES = 379 => "WHOIS user modes",
+    RPL_YOUREOPER = 381 => "Now an operator",
+    RPL_REHASHING = 382 => "Rehashing configuration",
+    RPL_TIME = 391 => "Server local time",
+    RPL_VISIBLEHOST = 396 => "Displayed host changed",
+    ERR_UNKNOWNERROR = 400 => "Unknown error",
+    ERR_NOSUCHNICK = 401 => "No such nick",
+    ERR_NOSUCHSERVER = 402 => "No such server",
+    ERR_NOSUCHCHANNEL = 403 => "No such channel",
+    ERR_CANNOTSENDTOCHAN = 404 => "Cannot send to channel",
+    ERR_TOOMANYCHANNELS = 405 => "Joined too many channels",
+    ERR_WASNOSUCHNICK = 406 => "There was no such nick",
+    ERR_TOOMANYTARGETS = 407 => "Too many targets",
+    ERR_NOORIGIN = 409 => "No origin specified",
+    ERR_INVALIDCAPCMD = 410 => "Invalid CAP subcommand",
+    ERR_NORECIPIENT = 411 => "No recipient given",
+    ERR_NOTEXTTOSEND = 412 => "No text to send",
+    ERR_INPUTTOOLONG = 417 => "Input line too long",
+    ERR_UNKNOWNCOMMAND = 421 => "Unknown command",
+    ERR_NOMOTD = 422 => "No message of the day",
+    ERR_NONICKNAMEGIVEN = 431 => "No nickname given",
+    ERR_ERRONEUSNICKNAME = 432 => "Erroneous nickname",
+    ERR_NICKNAMEINUSE = 433 => "Nickname is already in use",
+    ERR_NICKCOLLISION = 436 => "Nickname collision",
+    ERR_USERNOTINCHANNEL = 441 => "User is not on that channel",
+    ERR_NOTONCHANNEL = 442 => "You are not on that channel",
+    ERR_USERONCHANNEL = 443 => "User is already on channel",
+    ERR_NOTREGISTERED = 451 => "You have not registered",
+    ERR_NEEDMOREPARAMS = 461 => "Not enough parameters",
+    ERR_ALREADYREGISTERED = 462 => "You may not reregister",
+    ERR_PASSWDMISMATCH = 464 => "Password incorrect",
+    ERR_YOUREBANNEDCREEP = 465 => "You are banned from this server",
+    ERR_CHANNELISFULL = 471 => "Channel is full",
+    ERR_UNKNOWNMODE = 472 => "Unknown mode character",
+    ERR_INVITEONLYCHAN = 473 => "Channel is invite only",
+    ERR_BANNEDFROMCHAN = 474 => "Banned from channel",
+    ERR_BADCHANNELKEY = 475 => "Bad channel key",
+    ERR_BADCHANMASK = 476 => "Bad channel mask",
+    ERR_NOPRIVILEGES = 481 => "Permission denied, not an operator",
+    ERR_CHANOPRIVSNEEDED = 482 => "Not a channel operator",
+    ERR_CANTKILLSERVER = 483 => "Cannot kill a server",
+    ERR_NOOPERHOST = 491 => "No operator block for your host",
+    ERR_UMODEUNKNOWNFLAG = 501 => "Unknown user mode flag",
+    ERR_USERSDONTMATCH = 502 => "Cannot change mode for other users",
+    ERR_HELPNOTFOUND = 524 => "Help topic not found",
+    ERR_INVALIDKEY = 525 => "Invalid channel key",
+    RPL_STARTTLS = 670 => "STARTTLS successful, proceed with handshake",
+    RPL_WHOISSECURE = 671 => "WHOIS user is using a secure connection",
+    ERR_STARTTLS = 691 => "STARTTLS failed",
+    ERR_INVALIDMODEPARAM = 696 => "Invalid mode parameter",
+    RPL_HELPSTART = 704 => "Start of HELP reply",
+    RPL_HELPTXT = 705 => "HELP reply line",
+    RPL_ENDOFHELP = 706 => "End of HELP reply",
+    ERR_NOPRIVS = 723 => "Insufficient operator privileges",
+    RPL_MONONLINE = 730 => "Monitored nicks are online",
+    RPL_MONOFFLINE = 731 => "Monitored nicks are offline",
+    RPL_MONLIST = 732 => "MONITOR list entry",
+    RPL_ENDOFMONLIST = 733 => "End of MONITOR list",
+    ERR_MONLISTFULL = 734 => "MONITOR list is full",
+    RPL_LOGGEDIN = 900 => "Logged in to an account",
+    RPL_LOGGEDOUT = 901 => "Logged out of an account",
+    ERR_NICKLOCKED = 902 => "Nickname is locked, cannot authenticate",
+    RPL_SASLSUCCESS = 903 => "SASL authentication successful",
+    ERR_SASLFAIL = 904 => "SASL authentication failed",
+    ERR_SASLTOOLONG = 905 => "SASL message too long",
+    ERR_SASLABORTED = 906 => "SASL authentication aborted",
+    ERR_SASLALREADY = 907 => "Already authenticated with SASL",
+    RPL_SASLMECHS = 908 => "Available SASL mechanisms",
+}
+
+impl Numeric {
+    /// Returns `true` for error replies.
+    ///
+    /// Known numerics are classified by their `ERR_` name, unknown ones by the 400-599
+    /// range reserved for errors.
+    pub fn is_error(&self) -> bool {
+        match self.name() {
+            Some(name) => name.starts_with("ERR_"),
+            None => (400..600).contains(&self.code()),
+        }
+    }
+}
+
+/// Formats the code as three digits, e.g. `001`.
+impl Display for Numeric {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:03}", self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Numeric;
+    use crate::Commands;
+
+    #[test]
+    fn from_command() {
+        assert_eq!(Some(Numeric::RPL_WELCOME), Commands::from("001").numeric());
+        assert_eq!(
+            Some(Numeric::RPL_WHOSPCRPL),
+            Commands::from("354").numeric()
+        );
+        assert_eq!(Some(Numeric::UNKNOWN(999)), Commands::from("999").numeric());
+        assert_eq!(None, Commands::PRIVMSG.numeric());
+    }
+
+    #[test]
+    fn code_round_trip() {
+        for code in 0..1000 {
+            assert_eq!(code, Numeric::from(code).code());
+        }
+    }
+
+    #[test]
+    fn names_and_descriptions() {
+        assert_eq!(Some("RPL_ISUPPORT"), Numeric::RPL_ISUPPORT.name());
+        assert_eq!(
+            "SASL authentication successful",
+            Numeric::RPL_SASLSUCCESS.get_description()
+        );
+        assert_eq!(None, Numeric::UNKNOWN(42).name());
+        assert_eq!("005", Numeric::RPL_ISUPPORT.to_string());
+    }
+
+    #[test]
+    fn is_error() {
+        assert!(Numeric::ERR_NICKNAMEINUSE.is_error());
+        assert!(Numeric::ERR_SASLFAIL.is_error());
+        assert!(Numeric::ERR_MONLISTFULL.is_error());
+        assert!(!Numeric::RPL_LOGGEDIN.is_error());
+        assert!(!Numeric::RPL_MONONLINE.is_error());
+        assert!(Numeric::UNKNOWN(499).is_error());
+        assert!(!Numeric::UNKNOWN(600).is_error());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::compat::{Display, FmtResult, Formatter};
+
+use crate::PositionedError;
+
+/// Renders a [`PositionedError`] with the input line and a caret under the offending bytes.
+///
+/// Created with [`PositionedError::diagnostic()`].
+///
+/// # Examples
+///
+/// ```rust
+/// let input = "@id=1 :nick.example.com";
+/// let err = ircv3_parse::parse_positioned(input).unwrap_err();
+///
+/// assert_eq!(
+///     "error[SOURCE]: SOURCE must be followed by a space\n\
+///      |\n\
+///      | @id=1 :nick.example.com\n\
+///      |       ^^^^^^^^^^^^^^^^^",
+///     err.diagnostic(input).to_string()
+/// );
+/// ```
+pub struct Diagnostic<'a> {
+    input: &'a str,
+    error: &'a PositionedError,
+}
+
+impl<'a> Diagnostic<'a> {
+    pub fn new(input: &'a str, error: &'a PositionedError) -> Self {
+        Self { input, error }
+    }
+}
+
+impl Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "error[{}]: {}", self.error.code(), self.error.error)?;
+
+        let span = self.error.span;
+
+        let line = self.input.trim_end_matches(['\r', '\n']);
+        let start = floor_char_boundary(line, span.start as usize);
+        let end = floor_char_boundary(line, span.end as usize).max(start);
+
+        let column = line[..start].chars().count();
+        let width = line[start..end].chars().count().max(1);
+
+        write!(f, "\n|\n| {line}\n| ")?;
+        for _ in 0..column {
+            f.write_str(" ")?;
+        }
+        for _ in 0..width {
+            f.write_str("^")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{CommandError, ParamError, SourceError};
+    use crate::{ByteSpan, IRCError, PositionedError};
+
+    #[test]
+    fn caret_under_command() {
+        let input = ":nick!user@host !CMD #channel\r\n";
+        let err = crate::parse_positioned(input).unwrap_err();
+
+        assert_eq!(
+            "error[CMD]: command must start with a letter or digit, got '!'\n\
+             |\n\
+             | :nick!user@host !CMD #channel\n\
+             |                 ^",
+            err.diagnostic(input).to_string()
+        );
+    }
+
+    #[test]
+    fn multibyte_columns() {
+        let input = "@a=ñandú :ñ PRIVMSG";
+        let err = PositionedError {
+            error: IRCError::Source(SourceError::InvalidNickFirstChar { char: 'ñ' }),
+            span: ByteSpan::new(12, 14),
+        };
+
+        assert_eq!(
+            "error[SOURCE]: nickname must start with a letter, got 'ñ'\n\
+             |\n\
+             | @a=ñandú :ñ PRIVMSG\n\
+             |           ^",
+            err.diagnostic(input).to_string()
+        );
+    }
+
+    #[test]
+    fn end_of_line() {
+        let input = "PRIVMSG ";
+        let err = crate::parse_positioned(input).unwrap_err();
+
+        assert_eq!(
+            "error[PARAM]: parameter middle cannot be empty\n\
+             |\n\
+             | PRIVMSG \n\
+             |         ^",
+            err.diagnostic(input).to_string()
+        );
+    }
+
+    #[test]
+    fn validator_positions() {
+        let input = "@+vendor.example/ke_y=1 :nick!user@host PRIVMSG #a\x07b :hi";
+        let err = crate::parse(input).unwrap().validate().unwrap_err();
+        assert_eq!(ByteSpan::new(19, 20), err.span);
+
+        let input = "@id=1 :nick!us\0er@host PRIVMSG #chan :hi";
+        let err = crate::parse(input).unwrap().validate().unwrap_err();
+        assert_eq!(ByteSpan::new(14, 15), err.span);
+
+        let input = ":nick!user@-host PRIVMSG #chan :hi";
+        let err = crate::parse(input).unwrap().validate().unwrap_err();
+        assert_eq!(ByteSpan::new(11, 16), err.span);
+
+        let input = "PRIVMSG #chan:nel :hi";
+        let err = crate::parse(input).unwrap().validate().unwrap_err();
+        assert_eq!(
+            "error[PARAM]: parameter middle contains invalid character ':' at position 5\n\
+             |\n\
+             | PRIVMSG #chan:nel :hi\n\
+             |              ^",
+            err.diagnostic(input).to_string()
+        );
+    }
+
+    #[test]
+    fn empty_input() {
+        let err = crate::parse_positioned("").unwrap_err();
+        assert_eq!(ByteSpan::new(0, 0), err.span);
+        assert_eq!(
+            "error[SCAN]: cannot parse empty message\n|\n| \n| ^",
+            err.diagnostic("").to_string()
+        );
+    }
+
+    #[test]
+    fn plain_errors_stay_matchable() {
+        assert!(matches!(
+            crate::parse(":nick !CMD").unwrap_err(),
+            IRCError::Command(CommandError::InvalidFirstChar { char: '!' })
+        ));
+        assert!(matches!(
+            crate::parse("@id=1 :nick.example.com").unwrap_err(),
+            IRCError::MissingSpace { .. }
+        ));
+        assert!(matches!(
+            crate::parse("PRIVMSG ").unwrap_err(),
+            IRCError::Param(ParamError::EmptyMiddle)
+        ));
+    }
+}
@@ -1,9 +1,13 @@
-use crate::compat::{Debug, FmtResult, Formatter, String};
+use crate::compat::{format, Debug, Display, FmtResult, Formatter, String};
+use crate::diagnostic::Diagnostic;
+use crate::scanner::ByteSpan;
 
 #[derive(Clone, PartialEq, thiserror::Error)]
 pub enum IRCError {
     #[error("cannot parse empty message")]
     EmptyInput,
+    #[error("input is not valid UTF-8 (valid up to byte {valid_up_to})")]
+    InvalidUtf8 { valid_up_to: usize },
     #[error("{component} must be followed by a space")]
     MissingSpace { component: &'static str },
 
@@ -23,6 +27,11 @@ pub enum IRCError {
 
     #[error(transparent)]
     Hostname(#[from] HostnameError),
+
+    #[error("tag section is {len} bytes, over the limit of {max}")]
+    TagsTooLong { len: usize, max: usize },
+    #[error("message body is {len} bytes, over the limit of {max}")]
+    BodyTooLong { len: usize, max: usize },
 }
 
 impl Debug for IRCError {
@@ -34,7 +43,7 @@ impl Debug for IRCError {
 impl IRCError {
     pub fn code(&self) -> &'static str {
         match self {
-            Self::EmptyInput => "SCAN",
+            Self::EmptyInput | Self::InvalidUtf8 { .. } => "SCAN",
             Self::MissingSpace { component } => component,
 
             Self::SourceNotSet { .. } => "BUILD_SOURCE",
@@ -45,6 +54,7 @@ impl IRCError {
             Self::Command(cmd) => cmd.code(),
             Self::Param(param) => param.code(),
             Self::Hostname(host) => host.code(),
+            Self::TagsTooLong { .. } | Self::BodyTooLong { .. } => "LENGTH",
         }
     }
 
@@ -53,7 +63,10 @@ impl IRCError {
     }
 
     pub fn is_parser_error(&self) -> bool {
-        matches!(self, Self::EmptyInput | Self::MissingSpace { .. })
+        matches!(
+            self,
+            Self::EmptyInput | Self::InvalidUtf8 { .. } | Self::MissingSpace { .. }
+        )
     }
 
     pub fn is_validation_error(&self) -> bool {
@@ -62,6 +75,58 @@ impl IRCError {
             Self::Tag(_) | Self::Source(_) | Self::Command(_) | Self::Param(_) | Self::Hostname(_)
         )
     }
+
+    pub fn is_length_error(&self) -> bool {
+        matches!(self, Self::TagsTooLong { .. } | Self::BodyTooLong { .. })
+    }
+
+    /// Attaches the bytes of the line this error refers to.
+    pub(crate) fn at(self, span: ByteSpan) -> PositionedError {
+        PositionedError { error: self, span }
+    }
+}
+
+/// An [`IRCError`] together with the bytes of the line it refers to.
+///
+/// Returned by [`parse_positioned()`](crate::parse_positioned),
+/// [`Parser::parse()`](crate::Parser::parse) and
+/// [`Message::validate()`](crate::Message::validate). `?` converts it into the bare
+/// [`IRCError`].
+///
+/// ```rust
+/// use ircv3_parse::{error::CommandError, IRCError};
+///
+/// let err = ircv3_parse::parse_positioned(":nick !CMD").unwrap_err();
+/// assert_eq!(6, err.span.start);
+/// assert_eq!(
+///     IRCError::Command(CommandError::InvalidFirstChar { char: '!' }),
+///     err.error
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{error} at byte {}", span.start)]
+pub struct PositionedError {
+    pub error: IRCError,
+    pub span: ByteSpan,
+}
+
+impl PositionedError {
+    pub fn code(&self) -> &'static str {
+        self.error.code()
+    }
+
+    /// Returns a [`Diagnostic`] that renders `input` with the error position marked.
+    ///
+    /// `input` must be the line that produced this error.
+    pub fn diagnostic<'a>(&'a self, input: &'a str) -> Diagnostic<'a> {
+        Diagnostic::new(input, self)
+    }
+}
+
+impl From<PositionedError> for IRCError {
+    fn from(error: PositionedError) -> Self {
+        error.error
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, thiserror::Error)]
@@ -175,6 +240,128 @@ impl HostnameError {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum FrameError {
+    #[error("line exceeds maximum length of {max} bytes")]
+    LineTooLong { max: usize },
+    #[error(transparent)]
+    Message(#[from] IRCError),
+}
+
+impl FrameError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::LineTooLong { .. } => "FRAME",
+            Self::Message(e) => e.code(),
+        }
+    }
+
+    pub fn is_line_too_long(&self) -> bool {
+        matches!(self, Self::LineTooLong { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum BatchError {
+    #[error("more than {max} batches are open")]
+    TooManyBatches { max: usize },
+    #[error("more than {max} messages are held in open batches")]
+    TooManyMessages { max: usize },
+    #[error("batch `{0}` is not open")]
+    UnknownBatch(String),
+    #[error("batch `{0}` is already open")]
+    DuplicateBatch(String),
+    #[error(transparent)]
+    Message(#[from] DeError),
+}
+
+impl BatchError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::TooManyBatches { .. } | Self::TooManyMessages { .. } => "BATCH_LIMIT",
+            Self::UnknownBatch(_) | Self::DuplicateBatch(_) => "BATCH",
+            Self::Message(e) => e.code(),
+        }
+    }
+
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self,
+            Self::TooManyBatches { .. } | Self::TooManyMessages { .. }
+        )
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[derive(Debug, thiserror::Error)]
+pub enum CodecError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Frame(#[from] FrameError),
+    #[error(transparent)]
+    Message(#[from] IRCError),
+    #[error(transparent)]
+    Extract(#[from] DeError),
+}
+
+#[cfg(feature = "tokio")]
+impl CodecError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "IO",
+            Self::Frame(e) => e.code(),
+            Self::Message(e) => e.code(),
+            Self::Extract(e) => e.code(),
+        }
+    }
+}
+
+#[cfg(feature = "sasl")]
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SaslError {
+    #[error("AUTHENTICATE payload is not valid base64")]
+    InvalidBase64,
+    #[error("malformed SCRAM server message")]
+    InvalidScram,
+    #[error("SCRAM server signature does not match")]
+    ServerSignature,
+    #[error("SCRAM server error: {0}")]
+    Server(String),
+    #[error("AUTHENTICATE challenge exceeds {max} bytes")]
+    ChallengeTooLong { max: usize },
+    #[error(transparent)]
+    Message(#[from] DeError),
+}
+
+#[cfg(feature = "sasl")]
+impl SaslError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidBase64 => "BASE64",
+            Self::InvalidScram | Self::ServerSignature | Self::Server(_) => "SCRAM",
+            Self::ChallengeTooLong { .. } => "SASL_LIMIT",
+            Self::Message(e) => e.code(),
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ServerTimeError {
+    #[error("server time is not in the form `YYYY-MM-DDThh:mm:ss.sssZ`")]
+    InvalidFormat,
+    #[error("server time is not a valid date and time between years 0 and 9999")]
+    OutOfRange,
+}
+
+#[cfg(feature = "time")]
+impl ServerTimeError {
+    pub fn code(&self) -> &'static str {
+        "SERVER_TIME"
+    }
+}
+
 #[derive(Clone, PartialEq, thiserror::Error)]
 pub enum DeError {
     #[error("invalid command: expected `{expected}`, got `{actual}`")]
@@ -307,4 +494,16 @@ impl DeError {
             reason: reason.into(),
         }
     }
+
+    /// Like [`invalid_value`](Self::invalid_value), with the raw `value` quoted in the reason.
+    pub fn invalid_field_value(
+        field: impl Into<String>,
+        value: &str,
+        reason: impl Display,
+    ) -> Self {
+        Self::InvalidValue {
+            field: field.into(),
+            reason: format!("`{value}`: {reason}"),
+        }
+    }
 }
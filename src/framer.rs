@@ -0,0 +1,374 @@
+//! Line framing for streamed IRC input.
+//!
+//! [`LineFramer`] buffers raw bytes as they arrive from a socket and splits them into
+//! complete lines that can be handed to [`parse`](crate::parse).
+use bytes::{Buf, Bytes, BytesMut};
+
+use crate::{
+    error::FrameError, scanner::find_line_ending, IRCError, Limits, Message, MessageSize, CR, LF,
+};
+
+/// Default maximum line length in bytes, excluding the line ending.
+///
+/// IRCv3 allows up to 8191 bytes for the tags section and 512 bytes for the rest of the
+/// message.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 8191 + 512;
+
+/// Splits a byte buffer into lines.
+///
+/// Shared by [`LineFramer`] and codecs that receive their buffer from the caller.
+#[derive(Debug, Clone)]
+pub(crate) struct LineDecoder {
+    max_line_length: usize,
+    limits: Limits,
+    next_index: usize,
+    discarding: bool,
+    skip_lf: bool,
+}
+
+impl LineDecoder {
+    pub fn new(max_line_length: usize) -> Self {
+        Self {
+            max_line_length,
+            limits: Limits::NONE,
+            next_index: 0,
+            discarding: false,
+            skip_lf: false,
+        }
+    }
+
+    #[inline]
+    pub fn max_line_length(&self) -> usize {
+        self.max_line_length
+    }
+
+    #[inline]
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Removes the next complete line from `buf`, without its line ending.
+    ///
+    /// Empty lines are skipped. After [`FrameError::LineTooLong`] is returned, input is
+    /// discarded up to the next line ending so that decoding can continue. A line over the
+    /// [`Limits`] is consumed and returned as an error.
+    pub fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, FrameError> {
+        loop {
+            if self.skip_lf {
+                match buf.first() {
+                    Some(&LF) => {
+                        buf.advance(1);
+                        self.skip_lf = false;
+                    }
+                    Some(_) => self.skip_lf = false,
+                    None => return Ok(None),
+                }
+            }
+
+            let Some(offset) = find_line_ending(&buf[self.next_index..]) else {
+                if self.discarding {
+                    buf.clear();
+                    self.next_index = 0;
+                    return Ok(None);
+                }
+
+                if buf.len() > self.max_line_length {
+                    buf.clear();
+                    self.next_index = 0;
+                    self.discarding = true;
+                    return Err(FrameError::LineTooLong {
+                        max: self.max_line_length,
+                    });
+                }
+
+                self.next_index = buf.len();
+                return Ok(None);
+            };
+
+            let end = self.next_index + offset;
+            self.next_index = 0;
+
+            let mut line = buf.split_to(end + 1);
+            if line[end] == CR {
+                match buf.first() {
+                    Some(&LF) => buf.advance(1),
+                    Some(_) => {}
+                    None => self.skip_lf = true,
+                }
+            }
+            line.truncate(end);
+
+            if self.discarding {
+                self.discarding = false;
+                continue;
+            }
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.len() > self.max_line_length {
+                return Err(FrameError::LineTooLong {
+                    max: self.max_line_length,
+                });
+            }
+            self.limits.check(MessageSize::of_line(&line))?;
+
+            return Ok(Some(line.freeze()));
+        }
+    }
+}
+
+/// Incremental line framer for IRC streams.
+///
+/// Feed arbitrary chunks of bytes with [`feed`](Self::feed) or
+/// [`extend_from_slice`](Self::extend_from_slice), then pull complete lines with
+/// [`next_frame`](Self::next_frame) or parsed messages with
+/// [`next_message`](Self::next_message).
+///
+/// Lines may end with `\r\n`, a bare `\n`, or a stray `\r`. Empty lines are skipped.
+/// Lines longer than the configured maximum are rejected with
+/// [`FrameError::LineTooLong`] instead of being buffered without bound. The tag section
+/// and body are only checked separately when [`with_limits`](Self::with_limits) is set.
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::LineFramer;
+///
+/// let mut framer = LineFramer::new();
+/// framer.extend_from_slice(b"PING :irc.exa");
+/// assert!(framer.next_message()?.is_none());
+///
+/// framer.extend_from_slice(b"mple.com\r\nPRIVMSG #channel :hi\n");
+///
+/// let msg = framer.next_message()?.unwrap();
+/// assert!(msg.command().is_ping());
+/// assert_eq!("irc.example.com", msg.params().trailing.as_str());
+///
+/// let msg = framer.next_message()?.unwrap();
+/// assert!(msg.command().is_privmsg());
+///
+/// assert!(framer.next_message()?.is_none());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineFramer {
+    buffer: BytesMut,
+    decoder: LineDecoder,
+    current: Bytes,
+}
+
+impl LineFramer {
+    /// Creates a framer using [`DEFAULT_MAX_LINE_LENGTH`].
+    pub fn new() -> Self {
+        Self::with_max_line_length(DEFAULT_MAX_LINE_LENGTH)
+    }
+
+    /// Creates a framer that rejects lines longer than `max` bytes.
+    pub fn with_max_line_length(max: usize) -> Self {
+        Self {
+            buffer: BytesMut::new(),
+            decoder: LineDecoder::new(max),
+            current: Bytes::new(),
+        }
+    }
+
+    /// Rejects lines over `limits` with [`FrameError::Message`]. Defaults to
+    /// [`Limits::NONE`].
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.decoder.set_limits(limits);
+        self
+    }
+
+    #[inline]
+    pub fn max_line_length(&self) -> usize {
+        self.decoder.max_line_length()
+    }
+
+    #[inline]
+    pub fn limits(&self) -> Limits {
+        self.decoder.limits()
+    }
+
+    /// Appends a chunk received from the stream.
+    pub fn feed(&mut self, chunk: BytesMut) {
+        self.buffer.unsplit(chunk);
+    }
+
+    /// Appends bytes received from the stream.
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Returns the internal buffer so that a reader can fill it directly.
+    #[inline]
+    pub fn buffer_mut(&mut self) -> &mut BytesMut {
+        &mut self.buffer
+    }
+
+    /// Returns the number of buffered bytes not yet returned as a line.
+    #[inline]
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the next complete line as an owned frame, without its line ending.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FrameError::LineTooLong`] if a line exceeds the maximum length, or
+    /// [`FrameError::Message`] if it exceeds the [`Limits`].
+    pub fn next_frame(&mut self) -> Result<Option<Bytes>, FrameError> {
+        self.decoder.decode(&mut self.buffer)
+    }
+
+    /// Returns the next complete line parsed as a [`Message`].
+    ///
+    /// The message borrows from the framer until the next call.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FrameError`] if a line exceeds the maximum length or the [`Limits`], is not
+    /// valid UTF-8, or cannot be parsed.
+    pub fn next_message(&mut self) -> Result<Option<Message<'_>>, FrameError> {
+        match self.next_frame()? {
+            Some(frame) => self.current = frame,
+            None => return Ok(None),
+        }
+
+        let input = core::str::from_utf8(&self.current).map_err(|e| IRCError::InvalidUtf8 {
+            valid_up_to: e.valid_up_to(),
+        })?;
+
+        Ok(Some(crate::parse(input)?))
+    }
+}
+
+impl Default for LineFramer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use super::LineFramer;
+    use crate::{error::FrameError, IRCError, Limits};
+
+    #[test]
+    fn line_endings() {
+        let mut framer = LineFramer::new();
+        framer.extend_from_slice(b"PING a\r\nPING b\nPING c\rPING d\r\n");
+
+        assert_eq!("PING a", framer.next_frame().unwrap().unwrap());
+        assert_eq!("PING b", framer.next_frame().unwrap().unwrap());
+        assert_eq!("PING c", framer.next_frame().unwrap().unwrap());
+        assert_eq!("PING d", framer.next_frame().unwrap().unwrap());
+        assert!(framer.next_frame().unwrap().is_none());
+        assert_eq!(0, framer.buffered());
+    }
+
+    #[test]
+    fn split_chunks() {
+        let mut framer = LineFramer::new();
+        framer.feed(BytesMut::from(&b"PRIVMSG #chan"[..]));
+        assert!(framer.next_frame().unwrap().is_none());
+
+        framer.feed(BytesMut::from(&b"nel :hi\r"[..]));
+        assert_eq!(
+            "PRIVMSG #channel :hi",
+            framer.next_frame().unwrap().unwrap()
+        );
+
+        framer.feed(BytesMut::from(&b"\nPING x\r\n"[..]));
+        assert_eq!("PING x", framer.next_frame().unwrap().unwrap());
+        assert!(framer.next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn skip_empty_lines() {
+        let mut framer = LineFramer::new();
+        framer.extend_from_slice(b"\r\n\n\rPING\r\n\r\n");
+
+        assert_eq!("PING", framer.next_frame().unwrap().unwrap());
+        assert!(framer.next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn line_too_long() {
+        let mut framer = LineFramer::with_max_line_length(8);
+        framer.extend_from_slice(b"PRIVMSG #channel");
+
+        assert_eq!(Err(FrameError::LineTooLong { max: 8 }), framer.next_frame());
+        assert_eq!(0, framer.buffered());
+
+        framer.extend_from_slice(b" :rest of the line\r\nPING\r\n");
+        assert_eq!("PING", framer.next_frame().unwrap().unwrap());
+    }
+
+    #[test]
+    fn complete_line_too_long() {
+        let mut framer = LineFramer::with_max_line_length(4);
+        framer.extend_from_slice(b"PRIVMSG\r\nPING\r\n");
+
+        assert!(framer.next_frame().unwrap_err().is_line_too_long());
+        assert_eq!("PING", framer.next_frame().unwrap().unwrap());
+    }
+
+    #[test]
+    fn limits() {
+        let mut framer = LineFramer::new().with_limits(Limits::CLIENT);
+        let tags = "a".repeat(4094);
+        framer.extend_from_slice(format!("@{tags}a PING\r\n@{tags} PING\r\n").as_bytes());
+
+        assert_eq!(
+            Err(FrameError::Message(IRCError::TagsTooLong {
+                len: 4097,
+                max: 4096
+            })),
+            framer.next_frame()
+        );
+        assert_eq!(4100, framer.next_frame().unwrap().unwrap().len());
+
+        framer.extend_from_slice(format!("PRIVMSG #c :{}\r\n", "a".repeat(499)).as_bytes());
+        assert_eq!(
+            Err(FrameError::Message(IRCError::BodyTooLong {
+                len: 513,
+                max: 512
+            })),
+            framer.next_frame()
+        );
+        assert!(framer.next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn next_message() {
+        let mut framer = LineFramer::new();
+        framer.extend_from_slice(b"@id=1 :nick!user@host PRIVMSG #channel :hi\r\n");
+
+        let msg = framer.next_message().unwrap().unwrap();
+        assert_eq!("1", msg.tags().unwrap().get("id").unwrap().as_str());
+        assert_eq!("nick", msg.source().unwrap().name);
+        assert_eq!("hi", msg.params().trailing.as_str());
+    }
+
+    #[test]
+    fn next_message_invalid_utf8() {
+        let mut framer = LineFramer::new();
+        framer.extend_from_slice(b"PRIVMSG #channel :\xff\r\n");
+
+        assert_eq!(
+            Err(FrameError::Message(IRCError::InvalidUtf8 {
+                valid_up_to: 18
+            })),
+            framer.next_message().map(|m| m.is_some())
+        );
+    }
+}
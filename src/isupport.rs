@@ -0,0 +1,333 @@
+//! Server features from `RPL_ISUPPORT` (`005`).
+//!
+//! [`ISupport`] collects the tokens of every `005` line and reads the common ones into
+//! typed values. Accessors fall back to the RFC 1459 and RFC 2811 values when the server
+//! does not send a token, and limits without a value are [`usize::MAX`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ircv3_parse::casemap::CaseMapping;
+//! use ircv3_parse::isupport::ISupport;
+//!
+//! let mut isupport = ISupport::new();
+//! let msg = ircv3_parse::parse(
+//!     ":irc.example.com 005 nick CHANTYPES=# PREFIX=(qaohv)~&@%+ NETWORK=Example\\x20Net \
+//!     :are supported by this server",
+//! )?;
+//! assert!(isupport.handle(&msg));
+//!
+//! assert_eq!("#", isupport.chantypes());
+//! assert_eq!(('o', '@'), isupport.prefix()[2]);
+//! assert_eq!(Some("Example Net"), isupport.network());
+//! assert_eq!(CaseMapping::Rfc1459, isupport.casemapping());
+//! # Ok::<(), ircv3_parse::IRCError>(())
+//! ```
+
+use crate::compat::{BTreeMap, String, ToString, Vec};
+
+use crate::casemap::CaseMapping;
+use crate::components::Numeric;
+use crate::Message;
+
+/// Channel modes by the kind of argument they take, from `CHANMODES=A,B,C,D`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChanModes<'a> {
+    /// Modes that add or remove an address from a list, e.g. `b`. Always take an argument.
+    pub a: &'a str,
+    /// Modes that change a setting and always take an argument, e.g. `k`.
+    pub b: &'a str,
+    /// Modes that take an argument only when set, e.g. `l`.
+    pub c: &'a str,
+    /// Modes that never take an argument, e.g. `n`.
+    pub d: &'a str,
+}
+
+impl ChanModes<'static> {
+    /// The RFC 2811 channel modes.
+    pub const RFC: Self = Self {
+        a: "beI",
+        b: "k",
+        c: "l",
+        d: "aimnqpsrt",
+    };
+}
+
+impl<'a> ChanModes<'a> {
+    /// Parses `A,B,C,D`. Missing groups are empty and groups after the fourth are ignored.
+    pub fn parse(value: &'a str) -> Self {
+        let mut groups = value.split(',');
+        let mut next = || groups.next().unwrap_or_default();
+
+        Self {
+            a: next(),
+            b: next(),
+            c: next(),
+            d: next(),
+        }
+    }
+}
+
+impl Default for ChanModes<'_> {
+    fn default() -> Self {
+        ChanModes::RFC
+    }
+}
+
+/// Accumulated `RPL_ISUPPORT` tokens.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ISupport {
+    tokens: BTreeMap<String, String>,
+}
+
+impl ISupport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the tokens of an `RPL_ISUPPORT` message. `-TOKEN` removes a token sent earlier.
+    ///
+    /// Returns `false`, and does nothing, for any other message.
+    pub fn handle(&mut self, msg: &Message<'_>) -> bool {
+        if msg.command().numeric() != Some(Numeric::RPL_ISUPPORT) {
+            return false;
+        }
+
+        // The first parameter is the client's nickname.
+        for token in msg.params().middles.iter().skip(1) {
+            if let Some(key) = token.strip_prefix('-') {
+                self.tokens.remove(key);
+                continue;
+            }
+
+            let (key, value) = token.split_once('=').unwrap_or((token, ""));
+            self.tokens.insert(key.to_string(), unescape_value(value));
+        }
+
+        true
+    }
+
+    /// Returns the unescaped value of `key`, or `""` for a token without a value.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.tokens.get(key).map(String::as_str)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.tokens.contains_key(key)
+    }
+
+    /// Returns every token and its value, sorted by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tokens
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Returns the channel prefixes from `CHANTYPES`, `#&` by default.
+    pub fn chantypes(&self) -> &str {
+        self.get("CHANTYPES").unwrap_or("#&")
+    }
+
+    /// Returns `(mode, symbol)` pairs from `PREFIX`, highest rank first. Defaults to
+    /// `(ov)@+`.
+    pub fn prefix(&self) -> Vec<(char, char)> {
+        let value = self.get("PREFIX").unwrap_or("(ov)@+");
+
+        value
+            .strip_prefix('(')
+            .and_then(|value| value.split_once(')'))
+            .map(|(modes, symbols)| modes.chars().zip(symbols.chars()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the channel modes from `CHANMODES`, [`ChanModes::RFC`] by default.
+    pub fn chanmodes(&self) -> ChanModes<'_> {
+        self.get("CHANMODES")
+            .map(ChanModes::parse)
+            .unwrap_or_default()
+    }
+
+    /// Returns `CASEMAPPING`. Defaults to [`CaseMapping::Rfc1459`], which is also used for
+    /// mappings this crate does not know.
+    pub fn casemapping(&self) -> CaseMapping {
+        self.get("CASEMAPPING")
+            .and_then(CaseMapping::parse)
+            .unwrap_or_default()
+    }
+
+    /// Returns the maximum number of targets per command from `TARGMAX`, e.g.
+    /// `("PRIVMSG", 4)`. Commands without a limit have [`usize::MAX`].
+    pub fn targmax(&self) -> Vec<(&str, usize)> {
+        self.get("TARGMAX")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (command, max) = entry.split_once(':').unwrap_or((entry, ""));
+                (command, limit(max).unwrap_or(usize::MAX))
+            })
+            .collect()
+    }
+
+    /// Returns `NICKLEN`, 9 by default.
+    pub fn nicklen(&self) -> usize {
+        self.limit("NICKLEN").unwrap_or(9)
+    }
+
+    /// Returns `CHANNELLEN`, 200 by default.
+    pub fn channellen(&self) -> usize {
+        self.limit("CHANNELLEN").unwrap_or(200)
+    }
+
+    /// Returns `TOPICLEN`, if the server sets one.
+    pub fn topiclen(&self) -> Option<usize> {
+        self.limit("TOPICLEN")
+    }
+
+    /// Returns the number of mode changes per `MODE` line from `MODES`, 3 by default.
+    pub fn modes(&self) -> usize {
+        self.limit("MODES").unwrap_or(3)
+    }
+
+    /// Returns the `MONITOR` list size, or `None` if the server does not support it.
+    pub fn monitor(&self) -> Option<usize> {
+        self.limit("MONITOR")
+    }
+
+    pub fn network(&self) -> Option<&str> {
+        self.get("NETWORK")
+    }
+
+    /// Reads a numeric token. A token without a value has no limit.
+    fn limit(&self, key: &str) -> Option<usize> {
+        self.get(key)
+            .map(|value| limit(value).unwrap_or(usize::MAX))
+    }
+}
+
+fn limit(value: &str) -> Option<usize> {
+    value.parse().ok()
+}
+
+/// Decodes the `\xHH` escapes of a token value. Malformed escapes are kept as is.
+fn unescape_value(value: &str) -> String {
+    if !value.contains("\\x") {
+        return value.to_string();
+    }
+
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = match tail {
+            [b'x', high, low, ..] if byte == b'\\' => hex(*high).zip(hex(*low)),
+            _ => None,
+        };
+
+        match escaped {
+            Some((high, low)) => {
+                bytes.push(high << 4 | low);
+                rest = &tail[3..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8(bytes).unwrap_or_else(|_| value.to_string())
+}
+
+fn hex(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChanModes, ISupport};
+    use crate::casemap::CaseMapping;
+
+    fn isupport(lines: &[&str]) -> ISupport {
+        let mut isupport = ISupport::new();
+        for line in lines {
+            assert!(isupport.handle(&crate::parse(line).unwrap()));
+        }
+        isupport
+    }
+
+    #[test]
+    fn defaults() {
+        let isupport = ISupport::new();
+
+        assert_eq!("#&", isupport.chantypes());
+        assert_eq!(vec![('o', '@'), ('v', '+')], isupport.prefix());
+        assert_eq!(ChanModes::RFC, isupport.chanmodes());
+        assert_eq!(CaseMapping::Rfc1459, isupport.casemapping());
+        assert!(isupport.targmax().is_empty());
+        assert_eq!(9, isupport.nicklen());
+        assert_eq!(3, isupport.modes());
+        assert_eq!(None, isupport.monitor());
+        assert_eq!(None, isupport.network());
+    }
+
+    #[test]
+    fn tokens() {
+        let isupport = isupport(&[
+            ":irc.example.com 005 nick CASEMAPPING=ascii CHANMODES=beI,k,l,imnpst \
+                NICKLEN=30 MODES MONITOR=100 :are supported by this server",
+            ":irc.example.com 005 nick TARGMAX=PRIVMSG:4,NOTICE:,JOIN: PREFIX= \
+                :are supported by this server",
+        ]);
+
+        assert_eq!(CaseMapping::Ascii, isupport.casemapping());
+        assert_eq!(
+            ChanModes {
+                a: "beI",
+                b: "k",
+                c: "l",
+                d: "imnpst"
+            },
+            isupport.chanmodes()
+        );
+        assert_eq!(30, isupport.nicklen());
+        assert_eq!(usize::MAX, isupport.modes());
+        assert_eq!(Some(100), isupport.monitor());
+        assert_eq!(
+            vec![("PRIVMSG", 4), ("NOTICE", usize::MAX), ("JOIN", usize::MAX)],
+            isupport.targmax()
+        );
+        assert!(isupport.prefix().is_empty());
+        assert_eq!(Some(""), isupport.get("MODES"));
+        assert!(!isupport.contains("NETWORK"));
+    }
+
+    #[test]
+    fn negation() {
+        let isupport = isupport(&[
+            ":irc.example.com 005 nick NICKLEN=30 MONITOR :are supported by this server",
+            ":irc.example.com 005 nick -NICKLEN -MONITOR :are supported by this server",
+        ]);
+
+        assert_eq!(9, isupport.nicklen());
+        assert_eq!(None, isupport.monitor());
+        assert_eq!(0, isupport.iter().count());
+    }
+
+    #[test]
+    fn escapes() {
+        let isupport = isupport(&[
+            r":irc.example.com 005 nick NETWORK=Ex\x20Net\x3Dx\x2 A=\x5Cx20 :are supported",
+        ]);
+
+        assert_eq!(Some(r"Ex Net=x\x2"), isupport.network());
+        assert_eq!(Some(r"\x20"), isupport.get("A"));
+    }
+
+    #[test]
+    fn other_messages() {
+        let mut isupport = ISupport::new();
+        assert!(!isupport.handle(&crate::parse(":irc.example.com 001 nick :Welcome").unwrap()));
+        assert_eq!(0, isupport.iter().count());
+    }
+}
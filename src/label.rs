@@ -0,0 +1,378 @@
+//! Matching replies to commands sent with the `labeled-response` capability.
+//!
+//! A command tagged `label=<id>` is answered by a single message carrying the same label,
+//! an `ACK` if there is nothing else to say, or a `labeled-response` batch whose opening
+//! `BATCH` line carries it. [`LabelTracker`] hands out labels, remembers which are waiting,
+//! and turns the output of a [`BatchCollector`] into [`LabeledReply`] values.
+//!
+//! The tracker has no timers of its own: every call that needs the time takes `now`, the
+//! time since any fixed point, such as `Instant::elapsed` on an instant taken at startup.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use core::time::Duration;
+//!
+//! use ircv3_parse::batch::BatchCollector;
+//! use ircv3_parse::label::{LabelTracker, Reply, Resolved};
+//! use ircv3_parse::message::ser::ToMessage;
+//! use ircv3_parse::{Commands, MessageBuf, MessageBuilder};
+//!
+//! let mut tracker = LabelTracker::new();
+//! let mut collector = BatchCollector::new();
+//!
+//! let mut whois = MessageBuilder::new(Commands::WHOIS);
+//! whois.add_param("alice")?;
+//! let whois = tracker.stamp(whois, Duration::ZERO);
+//! assert_eq!("@label=1 WHOIS alice\r\n", whois.to_bytes()?);
+//!
+//! let mut replies = Vec::new();
+//! for line in [
+//!     "@label=1 :irc.example.com BATCH +a labeled-response",
+//!     "@batch=a :irc.example.com 311 me alice ~a host * :Alice",
+//!     "@batch=a :irc.example.com 318 me alice :End of /WHOIS list",
+//!     ":irc.example.com BATCH -a",
+//! ] {
+//!     if let Some(collected) = collector.push(MessageBuf::parse(line)?)? {
+//!         if let Resolved::Reply(reply) = tracker.resolve(collected) {
+//!             replies.push(reply);
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!("1", replies[0].label);
+//! let Reply::Batch(batch) = &replies[0].reply else { panic!() };
+//! assert_eq!(2, batch.messages.len());
+//! assert_eq!(0, tracker.pending());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`BatchCollector`]: crate::batch::BatchCollector
+
+use core::time::Duration;
+
+use crate::compat::{BTreeMap, String, ToString, Vec};
+
+use crate::batch::{Batch, Collected};
+use crate::message::ser::{MessageSerializer, SerializeTags, ToMessage};
+use crate::{Commands, IRCError, MessageBuf};
+
+/// How a labeled command was answered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// A single message carrying the label.
+    Message(MessageBuf),
+    /// `ACK`: the command succeeded without any other reply.
+    Ack,
+    /// A `labeled-response` batch.
+    Batch(Batch),
+    /// No reply arrived before the timeout.
+    TimedOut,
+}
+
+/// The completed reply to a labeled command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabeledReply {
+    pub label: String,
+    pub reply: Reply,
+}
+
+/// Output of [`LabelTracker::resolve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolved {
+    /// The reply to a pending label.
+    Reply(LabeledReply),
+    /// Anything else, passed through unchanged.
+    Other(Collected),
+}
+
+/// A message with a `label` tag written before its own tags.
+///
+/// Created by [`LabelTracker::stamp`].
+#[derive(Debug, Clone)]
+pub struct Labeled<T> {
+    label: String,
+    message: T,
+}
+
+impl<T> Labeled<T> {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn into_inner(self) -> T {
+        self.message
+    }
+}
+
+impl<T: ToMessage> ToMessage for Labeled<T> {
+    fn to_message<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError> {
+        let mut tags = serialize.tags();
+        tags.tag("label", Some(&self.label))?;
+        tags.end();
+
+        self.message.to_message(serialize)
+    }
+}
+
+/// Hands out labels and matches replies to them.
+#[derive(Debug, Clone)]
+pub struct LabelTracker {
+    next: u64,
+    timeout: Duration,
+    /// Deadline of each label still waiting for a reply.
+    pending: BTreeMap<String, Duration>,
+}
+
+impl Default for LabelTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LabelTracker {
+    pub fn new() -> Self {
+        Self {
+            next: 1,
+            timeout: Duration::from_secs(30),
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Sets how long to wait for a reply. Defaults to 30 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns a new label and starts waiting for its reply.
+    pub fn next_label(&mut self, now: Duration) -> String {
+        let label = self.next.to_string();
+        self.next += 1;
+
+        self.pending
+            .insert(label.clone(), now.saturating_add(self.timeout));
+        label
+    }
+
+    /// Labels `message` with [`next_label`](Self::next_label). `message` should not carry a
+    /// `label` tag of its own.
+    pub fn stamp<T: ToMessage>(&mut self, message: T, now: Duration) -> Labeled<T> {
+        Labeled {
+            label: self.next_label(now),
+            message,
+        }
+    }
+
+    /// Returns the number of labels waiting for a reply.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_pending(&self, label: &str) -> bool {
+        self.pending.contains_key(label)
+    }
+
+    /// Stops waiting for `label`, e.g. because the command could not be sent. Returns
+    /// `false` if it was not pending.
+    pub fn cancel(&mut self, label: &str) -> bool {
+        self.pending.remove(label).is_some()
+    }
+
+    /// Stops waiting for every label, e.g. after reconnecting.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Returns the earliest time [`expire`](Self::expire) has something to report.
+    pub fn next_deadline(&self) -> Option<Duration> {
+        self.pending.values().min().copied()
+    }
+
+    /// Completes a pending label with a message or batch from
+    /// [`BatchCollector::push`](crate::batch::BatchCollector::push).
+    ///
+    /// Gives `collected` back as [`Resolved::Other`] if it carries no label, or one that is
+    /// not pending, for the caller to handle as ordinary traffic.
+    pub fn resolve(&mut self, collected: Collected) -> Resolved {
+        let msg = match &collected {
+            Collected::Message(msg) => msg,
+            Collected::Batch(batch) => &batch.open,
+        };
+
+        let Some(label) = msg
+            .tags()
+            .and_then(|tags| tags.get("label"))
+            .map(|label| label.as_str())
+            .filter(|label| self.pending.contains_key(*label))
+            .map(ToString::to_string)
+        else {
+            return Resolved::Other(collected);
+        };
+        self.pending.remove(&label);
+
+        let reply = match collected {
+            Collected::Message(msg) if matches!(msg.command(), Commands::ACK) => Reply::Ack,
+            Collected::Message(msg) => Reply::Message(msg),
+            Collected::Batch(batch) => Reply::Batch(batch),
+        };
+
+        Resolved::Reply(LabeledReply { label, reply })
+    }
+
+    /// Gives up on the labels whose timeout has passed at `now`, oldest first.
+    pub fn expire(&mut self, now: Duration) -> Vec<LabeledReply> {
+        let mut expired: Vec<(String, Duration)> = Vec::new();
+        self.pending.retain(|label, deadline| {
+            if *deadline > now {
+                return true;
+            }
+            expired.push((label.clone(), *deadline));
+            false
+        });
+        expired.sort_by_key(|(_, deadline)| *deadline);
+
+        expired
+            .into_iter()
+            .map(|(label, _)| LabeledReply {
+                label,
+                reply: Reply::TimedOut,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::{LabelTracker, LabeledReply, Reply, Resolved};
+    use crate::batch::{BatchCollector, Collected};
+    use crate::message::ser::ToMessage;
+    use crate::{Commands, MessageBuf, MessageBuilder};
+
+    fn message(line: &str) -> Collected {
+        Collected::Message(MessageBuf::parse(line).unwrap())
+    }
+
+    #[test]
+    fn stamp() {
+        let mut tracker = LabelTracker::new();
+
+        let mut privmsg = MessageBuilder::new(Commands::PRIVMSG);
+        privmsg
+            .add_tag("+draft/reply", Some("abc"))
+            .unwrap()
+            .add_param("#chan")
+            .unwrap()
+            .set_trailing("hi")
+            .unwrap();
+        let first = tracker.stamp(&privmsg, Duration::ZERO);
+        let second = tracker.stamp(MessageBuilder::new(Commands::PING), Duration::ZERO);
+
+        assert_eq!(
+            "@label=1;+draft/reply=abc PRIVMSG #chan :hi\r\n",
+            first.to_bytes().unwrap()
+        );
+        assert_eq!("@label=2 PING\r\n", second.to_bytes().unwrap());
+        assert_eq!(first.to_bytes().unwrap().len(), first.serialized_size());
+        assert_eq!(2, tracker.pending());
+    }
+
+    #[test]
+    fn replies() {
+        let mut tracker = LabelTracker::new();
+        let a = tracker.next_label(Duration::ZERO);
+        let b = tracker.next_label(Duration::ZERO);
+
+        let ack = format!("@label={b} :irc.example.com ACK");
+        assert_eq!(
+            Resolved::Reply(LabeledReply {
+                label: b.clone(),
+                reply: Reply::Ack
+            }),
+            tracker.resolve(message(&ack))
+        );
+
+        let line = format!("@label={a} :irc.example.com NOTICE me :done");
+        let Resolved::Reply(LabeledReply {
+            reply: Reply::Message(msg),
+            ..
+        }) = tracker.resolve(message(&line))
+        else {
+            panic!("label not resolved");
+        };
+        assert_eq!("done", msg.params().trailing.as_str());
+
+        // Neither a second reply nor an unlabeled message matches.
+        assert_eq!(
+            Resolved::Other(message(&ack)),
+            tracker.resolve(message(&ack))
+        );
+        assert_eq!(
+            Resolved::Other(message("PING :x")),
+            tracker.resolve(message("PING :x"))
+        );
+        assert_eq!(0, tracker.pending());
+    }
+
+    #[test]
+    fn batch() {
+        let mut tracker = LabelTracker::new();
+        let mut collector = BatchCollector::new();
+        let label = tracker.next_label(Duration::ZERO);
+
+        let mut resolved = Vec::new();
+        for line in [
+            format!("@label={label} BATCH +b labeled-response"),
+            "@batch=b :irc.example.com 353 me = #chan :me".to_string(),
+            "@batch=b :irc.example.com 366 me #chan :End".to_string(),
+            "BATCH -b".to_string(),
+        ] {
+            let msg = MessageBuf::parse(&line).unwrap();
+            if let Some(collected) = collector.push(msg).unwrap() {
+                let Resolved::Reply(reply) = tracker.resolve(collected) else {
+                    panic!("label not resolved");
+                };
+                resolved.push(reply);
+            }
+        }
+
+        assert_eq!(1, resolved.len());
+        let Reply::Batch(batch) = &resolved[0].reply else {
+            panic!("expected a batch");
+        };
+        assert_eq!("labeled-response", batch.kind);
+        assert_eq!(2, batch.messages.len());
+    }
+
+    #[test]
+    fn timeouts() {
+        let mut tracker = LabelTracker::new().timeout(Duration::from_secs(10));
+        let a = tracker.next_label(Duration::from_secs(5));
+        let b = tracker.next_label(Duration::from_secs(1));
+        let c = tracker.next_label(Duration::from_secs(20));
+
+        assert_eq!(Some(Duration::from_secs(11)), tracker.next_deadline());
+        assert!(tracker.expire(Duration::from_secs(10)).is_empty());
+
+        let expired = tracker.expire(Duration::from_secs(15));
+        assert_eq!(
+            vec![b.as_str(), a.as_str()],
+            expired.iter().map(|r| r.label.as_str()).collect::<Vec<_>>()
+        );
+        assert!(expired.iter().all(|r| r.reply == Reply::TimedOut));
+        assert!(tracker.is_pending(&c));
+
+        // A late reply is ordinary traffic.
+        let late = format!("@label={a} ACK");
+        assert!(matches!(
+            tracker.resolve(message(&late)),
+            Resolved::Other(_)
+        ));
+
+        assert!(tracker.cancel(&c));
+        assert!(!tracker.cancel(&c));
+        assert_eq!(None, tracker.next_deadline());
+    }
+}
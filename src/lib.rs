@@ -3,10 +3,13 @@
 //! ## Key Features
 //!
 //! - **Zero-copy parsing**: Message components are slices into the original input string
+//! - **Owned messages**: Keep parsed messages beyond the input's lifetime with [`MessageBuf`]
 //! - **IRCv3 support**: Full support for message tags, source, and all IRCv3 features
 //! - **Derive macros**: `FromMessage` and `ToMessage` for easy message extraction and generation
 //! - **Manual implementations**: Full control over parsing and serialization when needed
 //! - **Builder pattern**: Flexible, order-independent message construction with [`MessageBuilder`]
+//! - **Raw bytes**: Parse lines in legacy encodings without UTF-8 conversion with [`parse_bytes`]
+//! - **Stream framing**: Split buffered socket input into lines with [`LineFramer`]
 //! - **`no_std` compatible**: Works in embedded and `no_std` environments (requires `alloc`)
 //!
 //! ## Quick Start
@@ -62,7 +65,9 @@
 //!
 //! ## FromMessage Derive Attributes
 //!
-//! The `FromMessage` derive macro supports both `&str` and `String` field types.
+//! The `FromMessage` derive macro supports both `&str` and `String` field types. `tag`,
+//! `param` and `trailing` fields also accept any [`FromStr`](core::str::FromStr) type, such
+//! as `u64` or `IpAddr`, through [`FromValue`](message::de::FromValue).
 //!
 //! ### Struct-Level Attributes
 //!
@@ -74,6 +79,7 @@
 //!
 //! - `#[irc(tag)]` - Extract tag value using field name as key
 //! - `#[irc(tag = "key")]` - Extract tag value with custom key
+//! - `#[irc(tag, escape)]` - Unescape the tag value (requires `String` or `Option<String>`)
 //!
 //! #### Tag Flag
 //!
@@ -106,6 +112,57 @@
 //!
 //! - `#[irc(with = "function")]` - Use custom extraction function
 //!
+//! ### Enums
+//!
+//! Enums dispatch on the command. Each variant has `#[irc(command = "COMMAND")]`, or
+//! `#[irc(command = N)]` for a numeric reply, and wraps a `FromMessage` type, declares
+//! fields inline, or is a unit variant. An optional `#[irc(other)]` variant receives any
+//! other message. The same attributes work with `ToMessage`.
+//!
+//! ```rust
+//! use ircv3_parse::{FromMessage, Message, ToMessage};
+//!
+//! #[derive(FromMessage, ToMessage)]
+//! #[irc(command = "PRIVMSG")]
+//! struct PrivMsg<'a> {
+//!     #[irc(param)]
+//!     channel: &'a str,
+//!     #[irc(trailing)]
+//!     message: &'a str,
+//! }
+//!
+//! #[derive(FromMessage, ToMessage)]
+//! enum Event<'a> {
+//!     #[irc(command = "PRIVMSG")]
+//!     PrivMsg(PrivMsg<'a>),
+//!     #[irc(command = "JOIN")]
+//!     Join {
+//!         #[irc(param)]
+//!         channel: &'a str,
+//!     },
+//!     #[irc(command = 1)]
+//!     Welcome {
+//!         #[irc(trailing)]
+//!         message: &'a str,
+//!     },
+//!     #[irc(other)]
+//!     Other(Message<'a>),
+//! }
+//!
+//! let event: Event = ircv3_parse::from_str("JOIN #rust")?;
+//! assert!(matches!(event, Event::Join { channel: "#rust" }));
+//!
+//! let event: Event = ircv3_parse::from_str(":irc.example.com 001 nick :Welcome")?;
+//! assert!(matches!(event, Event::Welcome { message: "Welcome" }));
+//!
+//! let event: Event = ircv3_parse::from_str("PING :irc.example.com")?;
+//! assert!(matches!(event, Event::Other(_)));
+//!
+//! let output = ircv3_parse::to_message(&Event::Join { channel: "#irc" })?;
+//! assert_eq!(b"JOIN #irc", output.as_ref());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
 //! ## Manual [`FromMessage`](message::de::FromMessage) Implementation
 //!
 //! For more complex parsing logic, implement the `FromMessage` trait manually.
@@ -168,7 +225,8 @@
 //!
 //! ## ToMessage Derive Attributes
 //!
-//! The `ToMessage` derive macro supports both `&str` and `String` field types.
+//! The `ToMessage` derive macro supports both `&str` and `String` field types. `tag`,
+//! `param` and `trailing` fields also accept any [`Display`](core::fmt::Display) type.
 //!
 //! ### Struct-Level Attributes
 //!
@@ -181,6 +239,8 @@
 //!
 //! - `#[irc(tag)]` - Serializes field as tag using the field name as key
 //! - `#[irc(tag = "key")]` - Serializes field as tag with custom key
+//! - `#[irc(tag, escape)]` - Escapes `;`, space, `\`, CR and LF in the value instead of
+//!   rejecting them
 //!
 //! #### Tag Flag
 //!
@@ -369,6 +429,13 @@
 //! - **`std`** (enabled by default) - Enables standard library support
 //! - **`derive`** - Enables `FromMessage` and `ToMessage` derive macros (recommended)
 //! - **`serde`** - Enables `Serialize` implementation for [`Message`]
+//! - **`tokio`** - Enables `IrcCodec` for `tokio_util::codec` framed streams
+//! - **`encoding`** - Enables decoding non-UTF-8 input with an `encoding_rs` fallback
+//! - **`sasl`** - Enables SASL `PLAIN`, `EXTERNAL` and `SCRAM-SHA-256` authentication in `sasl`
+//! - **`time`** - Enables parsing the IRCv3 `server-time` tag into `ServerTime` in `time`
+//! - **`chrono`** - Enables converting `ServerTime` to and from `chrono` types (implies `time`)
+//! - **`time-rs`** - Enables converting `ServerTime` to and from `time` crate types (implies `time`)
+//! - **`twitch`** - Enables typed messages for the Twitch IRC interface in `twitch`
 //!
 //! ## Using in `no_std` Environments
 //!
@@ -386,6 +453,11 @@
 extern crate alloc;
 
 pub(crate) mod compat {
+    #[cfg(not(feature = "std"))]
+    pub use alloc::borrow::Cow;
+    #[cfg(feature = "std")]
+    pub use std::borrow::Cow;
+
     pub use core::{
         fmt::{Debug, Display, Formatter, Result as FmtResult},
         iter::Map,
@@ -394,6 +466,7 @@ pub(crate) mod compat {
 
     #[cfg(not(feature = "std"))]
     pub use alloc::{
+        collections::{BTreeMap, BTreeSet},
         format,
         string::{String, ToString},
         vec::Vec,
@@ -401,6 +474,7 @@ pub(crate) mod compat {
 
     #[cfg(feature = "std")]
     pub use std::{
+        collections::{BTreeMap, BTreeSet},
         format,
         string::{String, ToString},
         vec::Vec,
@@ -410,10 +484,32 @@ pub(crate) mod compat {
 #[cfg(feature = "derive")]
 pub use ircv3_parse_derive::{FromMessage, ToMessage};
 
+pub mod batch;
 pub mod builder;
+pub mod cap;
+pub mod casemap;
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub mod codec;
 pub mod components;
+pub mod diagnostic;
 pub mod error;
+pub mod framer;
+pub mod isupport;
+pub mod label;
+pub mod limits;
 pub mod message;
+pub mod mode;
+pub mod parser;
+#[cfg(feature = "sasl")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sasl")))]
+pub mod sasl;
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+pub mod time;
+#[cfg(feature = "twitch")]
+#[cfg_attr(docsrs, doc(cfg(feature = "twitch")))]
+pub mod twitch;
 pub mod validators;
 
 mod rfc1123;
@@ -421,9 +517,18 @@ mod scanner;
 mod unescape;
 
 pub use components::Commands;
-pub use error::{DeError, IRCError};
-pub use message::{Message, MessageBuilder};
-pub use unescape::unescape;
+pub use diagnostic::Diagnostic;
+pub use error::{DeError, IRCError, PositionedError};
+pub use framer::LineFramer;
+pub use limits::{Limits, MessageSize};
+pub use message::{Message, MessageBuf, MessageBuilder, MessageBytes};
+pub use parser::{ParseOptions, Parser};
+pub use scanner::ByteSpan;
+
+#[cfg(feature = "encoding")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encoding")))]
+pub use encoding_rs;
+pub use unescape::{escape, unescape};
 
 use scanner::Scanner;
 
@@ -440,7 +545,9 @@ pub(crate) const EQ: u8 = b'=';
 
 /// Parse an IRC message from a string.
 ///
-/// Low-level parsing function.
+/// Low-level parsing function. Line length is not checked; use a [`Parser`] built with
+/// [`Limits`] to reject oversized lines.
+/// [`parse_positioned`] also returns the position of an error.
 ///
 /// # Examples
 ///
@@ -466,6 +573,50 @@ pub fn parse<'a>(input: &'a str) -> Result<Message<'a>, IRCError> {
     Ok(Message::new(input, scanner))
 }
 
+/// Parse an IRC message from a string, keeping the position of any error.
+///
+/// Behaves like [`parse`], but the error carries the bytes it refers to and can be
+/// rendered with [`PositionedError::diagnostic()`].
+///
+/// # Examples
+///
+/// ```rust
+/// let err = ircv3_parse::parse_positioned(":nick !CMD #channel").unwrap_err();
+/// assert_eq!(6, err.span.start);
+/// ```
+///
+/// # Errors
+///
+/// Returns [`PositionedError`]
+pub fn parse_positioned<'a>(input: &'a str) -> Result<Message<'a>, PositionedError> {
+    let scanner = Scanner::positioned(input.as_bytes())?;
+    Ok(Message::new(input, scanner))
+}
+
+/// Parse an IRC message from raw bytes.
+///
+/// Unlike [`parse`], the input does not need to be valid UTF-8. Components are returned as
+/// byte slices and can be converted individually. Like [`parse`], it does not check
+/// [`Limits`].
+///
+/// # Examples
+///
+/// ```rust
+/// let msg = ircv3_parse::parse_bytes(b"PRIVMSG #channel :caf\xe9")?;
+///
+/// assert!(msg.command().is_privmsg());
+/// assert_eq!(Some(&b"caf\xe9"[..]), msg.trailing());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`IRCError`]
+pub fn parse_bytes(input: &[u8]) -> Result<MessageBytes<'_>, IRCError> {
+    let scanner = Scanner::from_bytes(input)?;
+    Ok(MessageBytes::new(input, scanner))
+}
+
 /// Parse an IRC message into a type implementing [`message::de::FromMessage`].
 ///
 /// Convenience function for types using `[derive(FromMessage)]` or manually implementing the
@@ -0,0 +1,135 @@
+//! IRCv3 line length limits.
+//!
+//! A line is measured in two parts: the tag section, from the leading `@` up to and
+//! including the space after the tags, and the body, everything after it up to and including
+//! the CR LF. The body is always counted with a CR LF, whether or not the line has one.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ircv3_parse::{Limits, MessageSize};
+//!
+//! let msg = ircv3_parse::parse("@id=123 PRIVMSG #channel :hi")?;
+//! assert_eq!(MessageSize { tags: 8, body: 22 }, msg.size());
+//! assert!(Limits::SERVER.check(msg.size()).is_ok());
+//! # Ok::<(), ircv3_parse::IRCError>(())
+//! ```
+
+use crate::scanner::find_byte;
+use crate::{IRCError, AT, SPACE};
+
+/// Maximum tag section and body sizes, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum size of the tag section, including the leading `@` and the space after it.
+    pub tags: usize,
+    /// Maximum size of the body, including the CR LF.
+    pub body: usize,
+}
+
+impl Limits {
+    /// No limits. This is the default.
+    pub const NONE: Self = Self {
+        tags: usize::MAX,
+        body: usize::MAX,
+    };
+
+    /// 8191 bytes of tags and 512 bytes of body, the most a line may hold.
+    pub const SERVER: Self = Self {
+        tags: 8191,
+        body: 512,
+    };
+
+    /// 4094 bytes of tag data and 512 bytes of body, the most a client may send.
+    pub const CLIENT: Self = Self {
+        tags: 4094 + 2,
+        body: 512,
+    };
+
+    /// Checks `size` against these limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IRCError::TagsTooLong`] or [`IRCError::BodyTooLong`].
+    pub fn check(&self, size: MessageSize) -> Result<(), IRCError> {
+        if size.tags > self.tags {
+            return Err(IRCError::TagsTooLong {
+                len: size.tags,
+                max: self.tags,
+            });
+        }
+
+        if size.body > self.body {
+            return Err(IRCError::BodyTooLong {
+                len: size.body,
+                max: self.body,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Size of a line, split the same way as [`Limits`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MessageSize {
+    /// Size of the tag section, or 0 without tags.
+    pub tags: usize,
+    /// Size of the body, including the CR LF.
+    pub body: usize,
+}
+
+impl MessageSize {
+    /// Measures a line that has not been parsed, without its line ending.
+    pub(crate) fn of_line(line: &[u8]) -> Self {
+        let tags = match line.first() {
+            Some(&AT) => find_byte(SPACE, line).map_or(line.len(), |i| i + 1),
+            _ => 0,
+        };
+
+        Self {
+            tags,
+            body: line.len() - tags + 2,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.tags + self.body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Limits, MessageSize};
+    use crate::IRCError;
+
+    #[test]
+    fn check() {
+        let size = MessageSize {
+            tags: 4097,
+            body: 512,
+        };
+
+        assert!(Limits::NONE.check(size).is_ok());
+        assert!(Limits::SERVER.check(size).is_ok());
+        assert_eq!(
+            Err(IRCError::TagsTooLong {
+                len: 4097,
+                max: 4096
+            }),
+            Limits::CLIENT.check(size)
+        );
+
+        let size = MessageSize { tags: 0, body: 513 };
+        assert_eq!(
+            Err(IRCError::BodyTooLong { len: 513, max: 512 }),
+            Limits::SERVER.check(size)
+        );
+    }
+}
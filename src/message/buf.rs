@@ -0,0 +1,245 @@
+use bytes::Bytes;
+
+use crate::compat::{Debug, Display, FmtResult, Formatter};
+
+use crate::components::{Commands, Params, Source, Tags};
+use crate::scanner::Scanner;
+use crate::{IRCError, Message};
+
+/// An owned IRC message.
+///
+/// Holds the raw line in [`Bytes`] together with the component positions found while
+/// parsing, so it can be stored or sent across threads without re-parsing. Cloning is cheap.
+///
+/// Accessors borrow from the buffer and return the same types as [`Message`].
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::MessageBuf;
+///
+/// let owned = {
+///     let input = String::from(":nick!user@host PRIVMSG #channel :hi");
+///     let msg = ircv3_parse::parse(&input)?;
+///     msg.to_owned()
+/// };
+///
+/// assert_eq!("nick", owned.source().unwrap().name);
+/// assert_eq!("hi", owned.params().trailing.as_str());
+///
+/// let msg = owned.as_message();
+/// assert!(msg.command().is_privmsg());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
+pub struct MessageBuf {
+    input: Bytes,
+    scanner: Scanner,
+}
+
+impl MessageBuf {
+    /// Parses `input`, copying it into a new buffer.
+    ///
+    /// Like [`parse`](crate::parse), this does not check [`Limits`](crate::Limits).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IRCError`] if the input cannot be parsed.
+    pub fn parse(input: &str) -> Result<Self, IRCError> {
+        crate::parse(input).map(|msg| msg.to_owned())
+    }
+
+    /// Parses a buffer without copying it. [`Limits`](crate::Limits) are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IRCError::InvalidUtf8`] if the buffer is not valid UTF-8, or another
+    /// [`IRCError`] if it cannot be parsed.
+    pub fn from_bytes(input: impl Into<Bytes>) -> Result<Self, IRCError> {
+        let input = input.into();
+        let s = core::str::from_utf8(&input).map_err(|e| IRCError::InvalidUtf8 {
+            valid_up_to: e.valid_up_to(),
+        })?;
+
+        if s.is_empty() {
+            return Err(IRCError::EmptyInput);
+        }
+
+        let scanner = Scanner::new(s)?;
+        Ok(Self { input, scanner })
+    }
+
+    /// Returns a [`Message`] borrowing from this buffer.
+    #[inline]
+    pub fn as_message(&self) -> Message<'_> {
+        Message::new(self.input_raw(), self.scanner)
+    }
+
+    /// Returns [`Tags`] if present.
+    #[inline]
+    pub fn tags(&self) -> Option<Tags<'_>> {
+        self.as_message().tags()
+    }
+
+    /// Returns [`Source`] if present.
+    #[inline]
+    pub fn source(&self) -> Option<Source<'_>> {
+        self.as_message().source()
+    }
+
+    /// Returns [`Commands`].
+    #[inline]
+    pub fn command(&self) -> Commands<'_> {
+        self.as_message().command()
+    }
+
+    /// Returns [`Params`].
+    #[inline]
+    pub fn params(&self) -> Params<'_> {
+        self.as_message().params()
+    }
+
+    /// Returns the `time` tag of the `server-time` capability, or `None` if it is missing or
+    /// malformed.
+    #[cfg(feature = "time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+    #[inline]
+    pub fn server_time(&self) -> Option<crate::time::ServerTime> {
+        self.as_message().server_time()
+    }
+
+    /// Fetch the raw input `&str` backing this `MessageBuf`.
+    #[inline]
+    pub fn input_raw(&self) -> &str {
+        // SAFETY: `input` is only ever constructed from a `&str` or checked with
+        // `core::str::from_utf8` in `from_bytes`, and `Bytes` is immutable.
+        unsafe { core::str::from_utf8_unchecked(&self.input) }
+    }
+
+    /// Returns the underlying buffer.
+    #[inline]
+    pub fn into_bytes(self) -> Bytes {
+        self.input
+    }
+}
+
+impl<'a> Message<'a> {
+    /// Copies this message into an owned [`MessageBuf`].
+    pub fn to_owned(&self) -> MessageBuf {
+        MessageBuf {
+            input: Bytes::copy_from_slice(self.input.as_bytes()),
+            scanner: self.scanner,
+        }
+    }
+}
+
+impl From<Message<'_>> for MessageBuf {
+    fn from(msg: Message<'_>) -> Self {
+        msg.to_owned()
+    }
+}
+
+impl<'a> crate::message::de::FromMessage<'a> for MessageBuf {
+    fn from_message(msg: &Message<'a>) -> Result<Self, crate::DeError> {
+        Ok(msg.to_owned())
+    }
+}
+
+impl PartialEq for MessageBuf {
+    fn eq(&self, other: &Self) -> bool {
+        self.input == other.input
+    }
+}
+
+impl Eq for MessageBuf {}
+
+impl Display for MessageBuf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&self.as_message(), f)
+    }
+}
+
+impl Debug for MessageBuf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct(stringify!(MessageBuf))
+            .field("tags", &self.tags())
+            .field("source", &self.source())
+            .field("command", &self.command())
+            .field("params", &self.params())
+            .finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for MessageBuf {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.as_message().serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::MessageBuf;
+    use crate::{message::de::FromMessage, Commands, IRCError};
+
+    #[test]
+    fn outlives_input() {
+        let owned = {
+            let input = String::from("@id=1 :nick!user@host PRIVMSG #channel :Hello");
+            crate::parse(&input).unwrap().to_owned()
+        };
+
+        assert_eq!("1", owned.tags().unwrap().get("id").unwrap().as_str());
+        assert_eq!(Some("user"), owned.source().unwrap().user);
+        assert_eq!(Commands::PRIVMSG, owned.command());
+        assert_eq!(Some("#channel"), owned.params().middles.first());
+        assert_eq!("Hello", owned.params().trailing.as_str());
+        assert_eq!(
+            "@id=1 :nick!user@host PRIVMSG #channel :Hello",
+            owned.to_string()
+        );
+    }
+
+    #[test]
+    fn from_bytes_zero_copy() {
+        let input = Bytes::from_static(b"PING :token\r\n");
+        let msg = MessageBuf::from_bytes(input.clone()).unwrap();
+
+        assert!(msg.command().is_ping());
+        assert_eq!("token", msg.params().trailing.as_str());
+        assert_eq!(input.as_ptr(), msg.into_bytes().as_ptr());
+    }
+
+    #[test]
+    fn from_bytes_invalid() {
+        assert_eq!(
+            Err(IRCError::InvalidUtf8 { valid_up_to: 6 }),
+            MessageBuf::from_bytes(&b"PING :\xff"[..]).map(|_| ())
+        );
+        assert_eq!(
+            Err(IRCError::EmptyInput),
+            MessageBuf::from_bytes(Bytes::new()).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn clone_shares_buffer() {
+        let msg = MessageBuf::parse("JOIN #channel").unwrap();
+        let cloned = msg.clone();
+
+        assert_eq!(msg, cloned);
+        assert_eq!(msg.input_raw().as_ptr(), cloned.input_raw().as_ptr());
+    }
+
+    #[test]
+    fn from_message() {
+        let msg = MessageBuf::from_str(":nick NICK other").unwrap();
+        assert_eq!("nick", msg.source().unwrap().name);
+        assert_eq!(Commands::NICK, msg.as_message().command());
+    }
+}
@@ -18,6 +18,10 @@ enum TagTy<'a> {
         key: &'a str,
         value: Option<&'a str>,
     },
+    Escaped {
+        key: &'a str,
+        value: Option<&'a str>,
+    },
     Flag(&'a str),
 }
 
@@ -32,6 +36,14 @@ impl<'a> TagTy<'a> {
                     Ok(())
                 }
             }
+            Self::Escaped { key, value } => {
+                validators::tag_key(key)?;
+                if let Some(value) = value {
+                    validators::unescaped_tag_value(value)
+                } else {
+                    Ok(())
+                }
+            }
             Self::Flag(key) => validators::tag_key(key),
         }
     }
@@ -74,6 +86,9 @@ impl<'a> ToMessage for Tags<'a> {
                 TagTy::Value { key, value } => {
                     tags.tag(key, *value)?;
                 }
+                TagTy::Escaped { key, value } => {
+                    tags.tag_escaped(key, *value)?;
+                }
                 TagTy::Flag(key) => {
                     tags.flag(key)?;
                 }
@@ -264,6 +279,24 @@ impl<'a> MessageBuilder<'a> {
         Ok(self)
     }
 
+    /// Adds a tag whose value is escaped on serialization, so it may contain `;`, spaces,
+    /// `\`, CR and LF.
+    pub fn add_tag_escaped(
+        &mut self,
+        key: &'a str,
+        value: Option<&'a str>,
+    ) -> Result<&mut Self, IRCError> {
+        validators::tag_key(key)?;
+
+        if let Some(value) = value {
+            validators::unescaped_tag_value(value)?;
+        }
+
+        self.components.tags.push(TagTy::Escaped { key, value });
+
+        Ok(self)
+    }
+
     pub fn add_tags(&mut self, tags: &[(&'a str, Option<&'a str>)]) -> Result<&mut Self, IRCError> {
         for &(key, value) in tags {
             self.add_tag(key, value)?;
@@ -398,6 +431,21 @@ mod tests {
         assert_eq!(10, tags.serialized_size());
     }
 
+    #[test]
+    fn tags_escaped() {
+        let mut tags = Tags::new();
+        tags.push(TagTy::Escaped {
+            key: "key",
+            value: Some("a b;c\\"),
+        });
+
+        let mut buffer = IRCSerializer::new();
+        tags.to_message(&mut buffer).unwrap();
+
+        assert_eq!("@key=a\\sb\\:c\\\\", buffer.into_bytes());
+        assert_eq!(14, tags.serialized_size());
+    }
+
     #[test]
     fn tags_multiple() {
         let mut tags = Tags::new();
@@ -504,6 +552,28 @@ mod tests {
         assert_eq!(58, size);
     }
 
+    #[test]
+    fn add_tag_escaped_round_trip() {
+        let value = "multi word; with \\ and\r\n";
+
+        let mut msg = MessageBuilder::new(Commands::PRIVMSG);
+        msg.add_tag_escaped("note", Some(value)).unwrap();
+        msg.add_param("#channel").unwrap();
+
+        assert!(msg.add_tag("note", Some(value)).is_err());
+        assert!(msg.add_tag_escaped("note", Some("nul\0")).is_err());
+
+        let bytes = msg.to_bytes().unwrap();
+        let input = core::str::from_utf8(&bytes).unwrap();
+        let parsed = crate::parse(input).unwrap();
+
+        assert_eq!(
+            Some(value.to_string()),
+            parsed.tags().unwrap().get_unescaped("note")
+        );
+        assert_eq!(input.len(), msg.serialized_size());
+    }
+
     #[test]
     fn to_message() {
         struct PrivMsg {
@@ -0,0 +1,366 @@
+#[cfg(feature = "encoding")]
+use crate::compat::Cow;
+use crate::compat::{Debug, Display, FmtResult, Formatter};
+
+use crate::components::{Commands, Params, Source, Tags};
+use crate::scanner::{ByteSpan, Scanner};
+use crate::{IRCError, Message, SPACE};
+
+/// An IRC message parsed from raw bytes.
+///
+/// Created by [`parse_bytes`](crate::parse_bytes). Components are exposed as `&[u8]`, so
+/// lines in legacy encodings such as Latin-1 can be inspected without a lossy conversion.
+/// The `*_utf8` methods convert a single component, and [`to_message`](Self::to_message)
+/// converts the whole line.
+///
+/// # Examples
+///
+/// ```rust
+/// let msg = ircv3_parse::parse_bytes(b":nick PRIVMSG #caf\xe9 :d\xe9j\xe0 vu")?;
+///
+/// assert!(msg.command().is_privmsg());
+/// assert_eq!(Some(&b"nick"[..]), msg.source());
+/// assert_eq!(Some(&b"#caf\xe9"[..]), msg.middles().next());
+/// assert_eq!(Some(&b"d\xe9j\xe0 vu"[..]), msg.trailing());
+///
+/// assert_eq!("nick", msg.source_utf8()?.unwrap().name);
+/// assert!(msg.params_utf8().is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Copy)]
+pub struct MessageBytes<'a> {
+    input: &'a [u8],
+    scanner: Scanner,
+}
+
+impl<'a> MessageBytes<'a> {
+    #[inline]
+    pub(crate) fn new(input: &'a [u8], scanner: Scanner) -> Self {
+        Self { input, scanner }
+    }
+
+    /// Returns the raw tags, without the leading `@`, if present.
+    #[inline]
+    pub fn tags(&self) -> Option<&'a [u8]> {
+        if self.scanner.has_tags() {
+            Some(self.scanner.tags_span.extract_bytes(self.input))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the raw source, without the leading `:`, if present.
+    #[inline]
+    pub fn source(&self) -> Option<&'a [u8]> {
+        if self.scanner.has_source() {
+            Some(self.scanner.source_span.extract_bytes(self.input))
+        } else {
+            None
+        }
+    }
+
+    /// Returns [`Commands`].
+    ///
+    /// The scanner only accepts ASCII letters and digits in the command, so it is always
+    /// valid UTF-8.
+    #[inline]
+    pub fn command(&self) -> Commands<'a> {
+        let command = self.scanner.command_span.extract_bytes(self.input);
+        // SAFETY: `Scanner::scan_command` only accepts ASCII alphanumerics.
+        Commands::from(unsafe { core::str::from_utf8_unchecked(command) })
+    }
+
+    /// Returns an iterator over the raw middle parameters.
+    #[inline]
+    pub fn middles(&self) -> MiddlesBytes<'a> {
+        MiddlesBytes {
+            rest: self.scanner.params_span.extract_bytes(self.input),
+        }
+    }
+
+    /// Returns the raw trailing parameter, without the leading `:`, if present.
+    #[inline]
+    pub fn trailing(&self) -> Option<&'a [u8]> {
+        if self.scanner.has_trailing() {
+            Some(self.scanner.trailing_span.extract_bytes(self.input))
+        } else {
+            None
+        }
+    }
+
+    /// Converts the tags to [`Tags`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IRCError::InvalidUtf8`] if the tags are not valid UTF-8. `valid_up_to` is
+    /// an offset into the whole input.
+    pub fn tags_utf8(&self) -> Result<Option<Tags<'a>>, IRCError> {
+        if !self.scanner.has_tags() {
+            return Ok(None);
+        }
+
+        self.utf8(self.scanner.tags_span)
+            .map(|s| Some(Tags::new(s)))
+    }
+
+    /// Converts the source to [`Source`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IRCError::InvalidUtf8`] if the source is not valid UTF-8. `valid_up_to` is
+    /// an offset into the whole input.
+    pub fn source_utf8(&self) -> Result<Option<Source<'a>>, IRCError> {
+        if !self.scanner.has_source() {
+            return Ok(None);
+        }
+
+        self.utf8(self.scanner.source_span)
+            .map(|s| Some(Source::parse(s)))
+    }
+
+    /// Converts the parameters to [`Params`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IRCError::InvalidUtf8`] if the parameters are not valid UTF-8. `valid_up_to`
+    /// is an offset into the whole input.
+    pub fn params_utf8(&self) -> Result<Params<'a>, IRCError> {
+        if self.scanner.has_trailing() {
+            // Without middles, `params_span` is empty and does not point into the line, so
+            // start at the `:` of the trailing.
+            let start = if self.scanner.has_params() {
+                self.scanner.params_span.start
+            } else {
+                self.scanner.trailing_span.start - 1
+            };
+            let span = ByteSpan::new(start as usize, self.scanner.trailing_span.end as usize);
+            let input = self.utf8(span)?;
+
+            let middles = if self.scanner.has_params() {
+                self.scanner.params_span.len()
+            } else {
+                0
+            };
+            let trailing = self.scanner.trailing_span.start - start;
+
+            Ok(Params::new(
+                input,
+                &input[..middles],
+                Some(&input[trailing as usize..]),
+            ))
+        } else {
+            let input = self.utf8(self.scanner.params_span)?;
+            Ok(Params::new(input, input, None))
+        }
+    }
+
+    /// Converts the whole line to a [`Message`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IRCError::InvalidUtf8`] if the input is not valid UTF-8.
+    pub fn to_message(&self) -> Result<Message<'a>, IRCError> {
+        let input = core::str::from_utf8(self.input).map_err(|e| IRCError::InvalidUtf8 {
+            valid_up_to: e.valid_up_to(),
+        })?;
+
+        Ok(Message::new(input, self.scanner))
+    }
+
+    /// Decodes the whole line as UTF-8, falling back to `fallback` if it is not valid UTF-8.
+    ///
+    /// The result can be passed to [`parse`](crate::parse). Single-byte encodings keep the
+    /// ASCII delimiters in place, so the message structure is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ircv3_parse::encoding_rs::WINDOWS_1252;
+    ///
+    /// let bytes = ircv3_parse::parse_bytes(b"PRIVMSG #channel :caf\xe9")?;
+    /// let line = bytes.decode(WINDOWS_1252);
+    ///
+    /// let msg = ircv3_parse::parse(&line)?;
+    /// assert_eq!("café", msg.params().trailing.as_str());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "encoding")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "encoding")))]
+    pub fn decode(&self, fallback: &'static encoding_rs::Encoding) -> Cow<'a, str> {
+        decode(self.input, fallback)
+    }
+
+    /// Fetch the raw input `&[u8]` backing this `MessageBytes`.
+    #[inline]
+    pub fn input_raw(&self) -> &'a [u8] {
+        self.input
+    }
+
+    fn utf8(&self, span: ByteSpan) -> Result<&'a str, IRCError> {
+        core::str::from_utf8(span.extract_bytes(self.input)).map_err(|e| IRCError::InvalidUtf8 {
+            valid_up_to: span.start as usize + e.valid_up_to(),
+        })
+    }
+}
+
+impl Debug for MessageBytes<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct(stringify!(MessageBytes))
+            .field("tags", &self.tags().map(AsciiEscaped))
+            .field("source", &self.source().map(AsciiEscaped))
+            .field("command", &self.command())
+            .field(
+                "middles",
+                &AsciiEscaped(self.scanner.params_span.extract_bytes(self.input)),
+            )
+            .field("trailing", &self.trailing().map(AsciiEscaped))
+            .finish()
+    }
+}
+
+struct AsciiEscaped<'a>(&'a [u8]);
+
+impl Debug for AsciiEscaped<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("b\"")?;
+        for &b in self.0 {
+            Display::fmt(&core::ascii::escape_default(b), f)?;
+        }
+        f.write_str("\"")
+    }
+}
+
+/// Iterator over raw middle parameters, created by [`MessageBytes::middles`].
+#[derive(Debug, Clone)]
+pub struct MiddlesBytes<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for MiddlesBytes<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.rest.iter().position(|&b| b != SPACE)?;
+        let rest = &self.rest[start..];
+        let end = rest.iter().position(|&b| b == SPACE).unwrap_or(rest.len());
+
+        let (middle, rest) = rest.split_at(end);
+        self.rest = rest;
+        Some(middle)
+    }
+}
+
+/// Decodes `bytes` as UTF-8, falling back to `fallback` if they are not valid UTF-8.
+///
+/// Valid UTF-8 is borrowed as is.
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::{encoding_rs::WINDOWS_1252, message::bytes::decode};
+///
+/// assert_eq!("café", decode("café".as_bytes(), WINDOWS_1252));
+/// assert_eq!("café", decode(b"caf\xe9", WINDOWS_1252));
+/// ```
+#[cfg(feature = "encoding")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encoding")))]
+pub fn decode<'a>(bytes: &'a [u8], fallback: &'static encoding_rs::Encoding) -> Cow<'a, str> {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => Cow::Borrowed(s),
+        Err(_) => fallback.decode_without_bom_handling(bytes).0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_bytes, IRCError};
+
+    #[test]
+    fn components() {
+        let msg =
+            parse_bytes(b"@id=\xe9 :nick!user@host PRIVMSG  #a  #b\xff :hi there\r\n").unwrap();
+
+        assert_eq!(Some(&b"id=\xe9"[..]), msg.tags());
+        assert_eq!(Some(&b"nick!user@host"[..]), msg.source());
+        assert!(msg.command().is_privmsg());
+        assert_eq!(
+            vec![&b"#a"[..], &b"#b\xff"[..]],
+            msg.middles().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&b"hi there"[..]), msg.trailing());
+    }
+
+    #[test]
+    fn utf8_per_component() {
+        let msg = parse_bytes(b"@id=\xe9 :nick!user@host PRIVMSG #a :hi").unwrap();
+
+        assert_eq!(
+            Err(IRCError::InvalidUtf8 { valid_up_to: 4 }),
+            msg.tags_utf8().map(|_| ())
+        );
+        assert_eq!(Some("user"), msg.source_utf8().unwrap().unwrap().user);
+
+        let params = msg.params_utf8().unwrap();
+        assert_eq!(Some("#a"), params.middles.first());
+        assert_eq!("hi", params.trailing.as_str());
+
+        assert_eq!(
+            Err(IRCError::InvalidUtf8 { valid_up_to: 4 }),
+            msg.to_message().map(|_| ())
+        );
+    }
+
+    #[test]
+    fn trailing_only() {
+        let msg = parse_bytes(b"@id=\xe9 :n\xff PRIVMSG :hi there").unwrap();
+
+        let params = msg.params_utf8().unwrap();
+        assert!(params.middles.is_empty());
+        assert_eq!("hi there", params.trailing.as_str());
+        assert_eq!(":hi there", params.as_str());
+    }
+
+    #[test]
+    fn params_without_trailing() {
+        let msg = parse_bytes(b"MODE #channel +o nick").unwrap();
+
+        assert_eq!(None, msg.trailing());
+        assert_eq!(3, msg.middles().count());
+
+        let params = msg.params_utf8().unwrap();
+        assert_eq!("#channel +o nick", params.as_str());
+        assert!(params.trailing.is_none());
+    }
+
+    #[test]
+    fn to_message() {
+        let msg = parse_bytes(b":nick PING :token")
+            .unwrap()
+            .to_message()
+            .unwrap();
+
+        assert_eq!("nick", msg.source().unwrap().name);
+        assert_eq!("token", msg.params().trailing.as_str());
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Err(IRCError::EmptyInput), parse_bytes(b"").map(|_| ()));
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn decode_fallback() {
+        use super::decode;
+        use crate::compat::Cow;
+        use encoding_rs::WINDOWS_1252;
+
+        assert!(matches!(
+            decode(b"PING", WINDOWS_1252),
+            Cow::Borrowed("PING")
+        ));
+
+        let msg = parse_bytes(b"PRIVMSG #caf\xe9 :\x80").unwrap();
+        assert_eq!("PRIVMSG #café :€", msg.decode(WINDOWS_1252));
+    }
+}
@@ -58,6 +58,47 @@ pub trait FromMessage<'a>: Sized {
     fn from_message(msg: &crate::Message<'a>) -> Result<Self, crate::DeError>;
 }
 
+/// Convert a single component value, such as a tag value, into a typed field.
+///
+/// Used by `#[derive(FromMessage)]` for fields that are not `&str`, `String` or `bool`.
+/// Errors are reported as [`DeError::InvalidValue`](crate::DeError::InvalidValue).
+///
+/// Every [`FromStr`](core::str::FromStr) type implements it, so integers, `IpAddr` or
+/// user enums work as fields without extra code. Implement it directly for borrowing types.
+///
+/// ```rust
+/// use ircv3_parse::message::de::FromValue;
+///
+/// struct Color<'a>(&'a str);
+///
+/// impl<'a> FromValue<'a> for Color<'a> {
+///     type Error = &'static str;
+///
+///     fn from_value(value: &'a str) -> Result<Self, Self::Error> {
+///         value.strip_prefix('#').map(Color).ok_or("missing `#`")
+///     }
+/// }
+///
+/// assert_eq!("FF0000", Color::from_value("#FF0000").unwrap().0);
+/// ```
+pub trait FromValue<'a>: Sized {
+    type Error: core::fmt::Display;
+
+    fn from_value(value: &'a str) -> Result<Self, Self::Error>;
+}
+
+impl<T> FromValue<'_> for T
+where
+    T: core::str::FromStr,
+    T::Err: core::fmt::Display,
+{
+    type Error = T::Err;
+
+    fn from_value(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{message::de::FromMessage, DeError};
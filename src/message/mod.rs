@@ -1,14 +1,27 @@
+pub mod bytes;
 pub mod de;
 pub mod ser;
 
+mod buf;
 mod builder;
+mod multiline;
+mod split;
 
+pub use buf::MessageBuf;
 pub use builder::MessageBuilder;
+pub use bytes::MessageBytes;
+pub use multiline::{decode_multiline, MultilineBuilder, MultilineLimits};
+pub use split::{split_message, SplitOptions};
 
 use crate::compat::{Debug, Display, FmtResult, Formatter};
 
-use crate::components::{Commands, Params, Source, Tags};
-use crate::scanner::Scanner;
+use crate::components::{Commands, Params, Source, TagValue, Tags};
+use crate::error::{CommandError, ParamError, SourceError, TagError};
+use crate::limits::MessageSize;
+use crate::message::ser::{MessageSerializer, SerializeParams, SerializeTags, ToMessage};
+use crate::rfc1123::RFC1123;
+use crate::scanner::{char_span, find_line_ending, ByteSpan, Scanner};
+use crate::{validators, IRCError, PositionedError, EQ};
 
 /// A parsed IRC message.
 #[derive(Clone, Copy)]
@@ -68,10 +81,123 @@ impl<'a> Message<'a> {
         }
     }
 
+    /// Returns the `time` tag of the `server-time` capability, or `None` if it is missing or
+    /// malformed.
+    #[cfg(feature = "time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+    pub fn server_time(&self) -> Option<crate::time::ServerTime> {
+        let value = self.tags()?.get("time")?;
+        crate::time::ServerTime::parse(value.as_str()).ok()
+    }
+
     /// Fetch the raw input `&str` backing this `Message`.
     pub fn input_raw(&self) -> &str {
         self.input
     }
+
+    /// Returns the size of the tag section and body, as measured by [`Limits`].
+    ///
+    /// [`Limits`]: crate::Limits
+    pub fn size(&self) -> MessageSize {
+        let end = find_line_ending(self.input.as_bytes()).unwrap_or(self.input.len());
+        let tags = if self.scanner.has_tags() {
+            self.scanner.tags_span.end as usize + 1
+        } else {
+            0
+        };
+
+        MessageSize {
+            tags,
+            body: end - tags + 2,
+        }
+    }
+
+    /// Checks every component against [`validators`].
+    ///
+    /// Parsing only checks the structure of the line. The returned error carries the
+    /// position of the offending bytes, so it can be rendered with
+    /// [`PositionedError::diagnostic()`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let input = ":nick!user@host PRIVMSG #a:b :hi";
+    /// let msg = ircv3_parse::parse(input)?;
+    /// let err = msg.validate().unwrap_err();
+    ///
+    /// assert_eq!(26, err.span.start);
+    /// # Ok::<(), ircv3_parse::IRCError>(())
+    /// ```
+    pub fn validate(&self) -> Result<(), PositionedError> {
+        if let Some(tags) = self.tags() {
+            for tag in tags.split() {
+                let (key, value) = match tag.split_once(EQ as char) {
+                    Some((key, value)) => (key, Some(value)),
+                    None => (tag, None),
+                };
+
+                let key_part = key
+                    .rfind('/')
+                    .map_or(key.starts_with('+') as usize, |i| i + 1);
+                validators::tag_key(key).map_err(|e| self.locate(e, key, key_part))?;
+                if let Some(value) = value {
+                    validators::tag_value(value).map_err(|e| self.locate(e, value, 0))?;
+                }
+            }
+        }
+
+        if let Some(source) = self.source() {
+            let bare = source.user.is_none() && source.host.is_none();
+            if !(bare && RFC1123::new().validate(source.name).is_ok()) {
+                validators::nick(source.name).map_err(|e| self.locate(e, source.name, 0))?;
+            }
+            if let Some(user) = source.user {
+                validators::user(user).map_err(|e| self.locate(e, user, 0))?;
+            }
+            if let Some(host) = source.host {
+                validators::host(host).map_err(|e| self.locate(e, host, 0))?;
+            }
+        }
+
+        let command = self.scanner.command_span.extract(self.input);
+        validators::command(command).map_err(|e| self.locate(e, command, 0))?;
+
+        let params = self.params();
+        for middle in params.middles.iter() {
+            validators::param(middle).map_err(|e| self.locate(e, middle, 0))?;
+        }
+        if self.scanner.has_trailing() {
+            let trailing = self.scanner.trailing_span.extract(self.input);
+            validators::trailing(trailing).map_err(|e| self.locate(e, trailing, 0))?;
+        }
+
+        Ok(())
+    }
+
+    /// Positions a validator error for `piece`, a slice of the input. Errors that name a
+    /// character point at it, `offset` bytes into `piece`; the rest cover the whole piece.
+    fn locate(&self, error: impl Into<IRCError>, piece: &str, offset: usize) -> PositionedError {
+        let error = error.into();
+        let start = piece.as_ptr() as usize - self.input.as_ptr() as usize;
+
+        let position = match error {
+            IRCError::Tag(TagError::InvalidKeyChar { position, .. }) => Some(offset + position),
+            IRCError::Tag(TagError::InvalidValueChar { position, .. })
+            | IRCError::Source(SourceError::InvalidNickChar { position, .. })
+            | IRCError::Source(SourceError::InvalidUserChar { position, .. })
+            | IRCError::Command(CommandError::InvalidCommand { position, .. })
+            | IRCError::Param(ParamError::InvalidMiddleChar { position, .. }) => Some(position),
+            IRCError::Source(SourceError::InvalidNickFirstChar { .. })
+            | IRCError::Command(CommandError::InvalidFirstChar { .. }) => Some(0),
+            _ => None,
+        };
+
+        let span = match position {
+            Some(position) => char_span(self.input.as_bytes(), start + position),
+            None => ByteSpan::new(start, start + piece.len()),
+        };
+        error.at(span)
+    }
 }
 
 impl Display for Message<'_> {
@@ -91,6 +217,43 @@ impl Debug for Message<'_> {
     }
 }
 
+/// Re-serializes the message component by component. Tag values and the source are written
+/// as received, so escape sequences are kept and hosts such as `user/foo` or `2001:db8::1`
+/// are not checked against RFC 1123.
+impl ToMessage for Message<'_> {
+    fn to_message<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError> {
+        if let Some(tags) = self.tags() {
+            let mut serialize_tags = serialize.tags();
+            for (key, value) in tags.iter() {
+                match value {
+                    TagValue::Flag => serialize_tags.flag(key)?,
+                    value => serialize_tags.tag(key, Some(value.as_str()))?,
+                }
+            }
+            serialize_tags.end();
+        }
+
+        if self.scanner.has_source() {
+            serialize.raw_source(self.scanner.source_span.extract(self.input))?;
+        }
+
+        serialize.command(self.command());
+
+        let params = self.params();
+        if !params.middles.is_empty() {
+            let mut serialize_params = serialize.params();
+            serialize_params.extend(params.middles.iter())?;
+            serialize_params.end();
+        }
+
+        if let Some(trailing) = params.trailing.raw() {
+            serialize.trailing(trailing)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(feature = "serde")]
 impl serde::Serialize for Message<'_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -126,3 +289,23 @@ impl serde::Serialize for Message<'_> {
         state.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::message::ser::ToMessage;
+
+    #[test]
+    fn source_round_trip() {
+        for input in [
+            ":nick!user@user/foo PRIVMSG #c :hi",
+            ":nick!u@2001:db8::1 PRIVMSG #c :hi",
+            ":irc.example.com 001 nick :Welcome",
+            "@time=x :nick JOIN #c",
+        ] {
+            let msg = crate::parse(input).unwrap();
+
+            assert_eq!(input, msg.to_bytes().unwrap());
+            assert_eq!(input.len(), msg.serialized_size());
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Sending and receiving `draft/multiline` batches.
+
+use bytes::Bytes;
+
+use crate::compat::{format, String, Vec};
+
+use super::split::{
+    batch_close, batch_open, message, pieces, text_budget, SplitOptions, MULTILINE,
+    MULTILINE_CONCAT,
+};
+use crate::batch::Batch;
+use crate::IRCError;
+
+/// Limits from the value of the `draft/multiline` capability, e.g.
+/// `max-bytes=4096,max-lines=24`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MultilineLimits {
+    /// Total bytes of text in one batch, counting a byte for each line break.
+    pub max_bytes: usize,
+    /// Lines in one batch, or [`usize::MAX`] if the server sets no limit.
+    pub max_lines: usize,
+}
+
+impl MultilineLimits {
+    /// Reads the capability value. Returns `None` without the required `max-bytes`.
+    ///
+    /// ```rust
+    /// use ircv3_parse::message::MultilineLimits;
+    ///
+    /// let limits = MultilineLimits::parse("max-bytes=4096,max-lines=24").unwrap();
+    /// assert_eq!(4096, limits.max_bytes);
+    /// assert_eq!(24, limits.max_lines);
+    /// ```
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut max_bytes = None;
+        let mut max_lines = usize::MAX;
+
+        for pair in value.split(',') {
+            match pair.split_once('=') {
+                Some(("max-bytes", value)) => max_bytes = value.parse().ok(),
+                Some(("max-lines", value)) => max_lines = value.parse().ok()?,
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            max_bytes: max_bytes?,
+            max_lines,
+        })
+    }
+}
+
+/// Builds `draft/multiline` batches.
+///
+/// Long lines are split with `draft/multiline-concat` continuations. Text over the
+/// [`MultilineLimits`] goes into further batches, referenced as `<reference>-2`,
+/// `<reference>-3` and so on; a line carried over starts its batch without the concat tag.
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::message::{MultilineBuilder, MultilineLimits};
+///
+/// let limits = MultilineLimits { max_bytes: 4096, max_lines: 2 };
+/// let lines = MultilineBuilder::new("#channel", "ml").max(limits).build("one\ntwo\nthree")?;
+///
+/// assert_eq!(
+///     [
+///         "BATCH +ml draft/multiline #channel\r\n",
+///         "@batch=ml PRIVMSG #channel :one\r\n",
+///         "@batch=ml PRIVMSG #channel :two\r\n",
+///         "BATCH -ml\r\n",
+///         "BATCH +ml-2 draft/multiline #channel\r\n",
+///         "@batch=ml-2 PRIVMSG #channel :three\r\n",
+///         "BATCH -ml-2\r\n",
+///     ],
+///     &lines[..]
+/// );
+/// # Ok::<(), ircv3_parse::IRCError>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct MultilineBuilder<'a> {
+    target: &'a str,
+    reference: &'a str,
+    max: Option<MultilineLimits>,
+    options: SplitOptions<'a>,
+}
+
+impl<'a> MultilineBuilder<'a> {
+    pub fn new(target: &'a str, reference: &'a str) -> Self {
+        Self {
+            target,
+            reference,
+            max: None,
+            options: SplitOptions::default(),
+        }
+    }
+
+    /// Sets the limits advertised by the server. Without them, everything goes into one
+    /// batch.
+    pub fn max(mut self, max: MultilineLimits) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Sets how lines are split. `tags` go on the opening `BATCH` and `multiline` is
+    /// ignored.
+    pub fn options(mut self, options: SplitOptions<'a>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the lines of every batch needed for `text`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IRCError`] if the target, reference or a tag is invalid, or if not even
+    /// one character fits on a line.
+    pub fn build(&self, text: &str) -> Result<Vec<Bytes>, IRCError> {
+        let options = SplitOptions {
+            multiline: Some(self.reference),
+            ..self.options
+        };
+        let max = self.max.unwrap_or(MultilineLimits {
+            max_bytes: usize::MAX,
+            max_lines: usize::MAX,
+        });
+
+        let budget = text_budget(self.target, &options)?.min(max.max_bytes);
+        let pieces = pieces(text, budget, &options)?;
+
+        let mut batches: Vec<Vec<(&str, bool)>> = Vec::new();
+        let mut bytes = 0;
+        for (chunk, concat) in pieces {
+            let added = chunk.len() + usize::from(!concat);
+            let full = batches.last().map_or(true, |batch| {
+                batch.len() >= max.max_lines || bytes + added > max.max_bytes
+            });
+
+            if full {
+                batches.push(Vec::new());
+                bytes = chunk.len();
+                batches.last_mut().unwrap().push((chunk, false));
+            } else {
+                bytes += added;
+                batches.last_mut().unwrap().push((chunk, concat));
+            }
+        }
+
+        let mut lines = Vec::new();
+        for (i, batch) in batches.iter().enumerate() {
+            let reference = match i {
+                0 => String::from(self.reference),
+                i => format!("{}-{}", self.reference, i + 1),
+            };
+            let tags = [("batch", Some(reference.as_str()))];
+
+            lines.push(batch_open(&reference, self.target, &options)?);
+            for &(chunk, concat) in batch {
+                lines.push(message(self.target, chunk, &tags, concat, &options)?);
+            }
+            lines.push(batch_close(&reference, &options)?);
+        }
+
+        Ok(lines)
+    }
+}
+
+/// Joins the messages of a `draft/multiline` batch back into the original text.
+///
+/// Returns `None` if `batch` is of another type. The target is the first of the batch
+/// params.
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::batch::{BatchCollector, Collected};
+/// use ircv3_parse::message::decode_multiline;
+/// use ircv3_parse::MessageBuf;
+///
+/// let mut collector = BatchCollector::new();
+/// let mut collected = None;
+/// for line in [
+///     ":n!u@h BATCH +123 draft/multiline #channel",
+///     "@batch=123 :n!u@h PRIVMSG #channel :hello",
+///     "@batch=123;draft/multiline-concat :n!u@h PRIVMSG #channel : world",
+///     "@batch=123 :n!u@h PRIVMSG #channel :bye",
+///     ":n!u@h BATCH -123",
+/// ] {
+///     collected = collector.push(MessageBuf::parse(line)?)?;
+/// }
+///
+/// let Some(Collected::Batch(batch)) = collected else { panic!() };
+/// assert_eq!(Some("hello world\nbye".to_string()), decode_multiline(&batch));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn decode_multiline(batch: &Batch) -> Option<String> {
+    if batch.kind != MULTILINE {
+        return None;
+    }
+
+    let mut text = String::new();
+    for (i, msg) in batch.messages.iter().enumerate() {
+        let concat = msg
+            .tags()
+            .is_some_and(|tags| tags.get_flag(MULTILINE_CONCAT));
+        if i > 0 && !concat {
+            text.push('\n');
+        }
+        text.push_str(msg.params().trailing.as_str());
+    }
+
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_multiline, MultilineBuilder, MultilineLimits};
+    use crate::batch::{BatchCollector, Collected};
+    use crate::message::SplitOptions;
+    use crate::{Limits, MessageBuf};
+
+    fn round_trip(lines: &[bytes::Bytes]) -> Vec<String> {
+        let mut collector = BatchCollector::new();
+        let mut texts = Vec::new();
+        for line in lines {
+            let msg = MessageBuf::from_bytes(line.clone()).unwrap();
+            if let Some(Collected::Batch(batch)) = collector.push(msg).unwrap() {
+                texts.push(decode_multiline(&batch).unwrap());
+            }
+        }
+        texts
+    }
+
+    #[test]
+    fn parse_limits() {
+        assert_eq!(
+            Some(MultilineLimits {
+                max_bytes: 40000,
+                max_lines: usize::MAX
+            }),
+            MultilineLimits::parse("max-bytes=40000")
+        );
+        assert_eq!(None, MultilineLimits::parse("max-lines=10"));
+        assert_eq!(None, MultilineLimits::parse("max-bytes=4096,max-lines=x"));
+        assert_eq!(None, MultilineLimits::parse(""));
+    }
+
+    #[test]
+    fn long_lines() {
+        let options = SplitOptions {
+            limits: Limits {
+                body: 30,
+                ..Limits::CLIENT
+            },
+            tags: &[("label", Some("x"))],
+            ..Default::default()
+        };
+        let text = "0123456789abcdefghij\n\nend";
+        let lines = MultilineBuilder::new("#c", "b")
+            .options(options)
+            .build(text)
+            .unwrap();
+
+        assert_eq!("@label=x BATCH +b draft/multiline #c\r\n", lines[0]);
+        assert_eq!(
+            "@batch=b;draft/multiline-concat PRIVMSG #c :ghij\r\n",
+            lines[2]
+        );
+        assert_eq!(vec![text], round_trip(&lines));
+    }
+
+    #[test]
+    fn max_bytes() {
+        let limits = MultilineLimits {
+            max_bytes: 10,
+            max_lines: usize::MAX,
+        };
+        let lines = MultilineBuilder::new("#c", "b")
+            .max(limits)
+            .build("aaaa\nbbbb\ncccccccccccccccc")
+            .unwrap();
+
+        // "aaaa" and "bbbb" take 9 bytes with the line break; the last line is cut at 10.
+        assert_eq!(
+            vec!["aaaa\nbbbb", "cccccccccc", "cccccc"],
+            round_trip(&lines)
+        );
+        assert!(lines.iter().any(|line| line.starts_with(b"BATCH +b-3 ")));
+    }
+
+    #[test]
+    fn other_batches() {
+        let mut collector = BatchCollector::new();
+        collector
+            .push(MessageBuf::parse("BATCH +n netsplit a b").unwrap())
+            .unwrap();
+        let Some(Collected::Batch(batch)) = collector
+            .push(MessageBuf::parse("BATCH -n").unwrap())
+            .unwrap()
+        else {
+            panic!("batch not closed");
+        };
+
+        assert_eq!(None, decode_multiline(&batch));
+    }
+}
@@ -0,0 +1,60 @@
+//! Field dispatch for `#[derive(ToMessage)]`.
+//!
+//! A field whose type implements [`ToMessage`] is serialized as a nested message. Any other
+//! type implementing [`Display`] is written as a single tag value, parameter or trailing.
+//! The choice is made with autoref: `(&&Field(value)).param(serialize)` resolves to
+//! [`NestedField`] first and falls back to [`DisplayField`].
+
+use crate::compat::{Display, ToString};
+use crate::message::ser::{MessageSerializer, SerializeParams, SerializeTags, ToMessage};
+use crate::IRCError;
+
+pub struct Field<'a, T: ?Sized>(pub &'a T);
+
+pub trait NestedField {
+    fn tag<S: MessageSerializer>(&self, key: &str, serialize: &mut S) -> Result<(), IRCError>;
+    fn param<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError>;
+    fn trailing<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError>;
+}
+
+impl<T: ToMessage + ?Sized> NestedField for &Field<'_, T> {
+    fn tag<S: MessageSerializer>(&self, _key: &str, serialize: &mut S) -> Result<(), IRCError> {
+        self.0.to_message(serialize)
+    }
+
+    fn param<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError> {
+        self.0.to_message(serialize)
+    }
+
+    fn trailing<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError> {
+        self.0.to_message(serialize)
+    }
+}
+
+pub trait DisplayField {
+    fn tag<S: MessageSerializer>(&self, key: &str, serialize: &mut S) -> Result<(), IRCError>;
+    fn param<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError>;
+    fn trailing<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError>;
+}
+
+impl<T: Display + ?Sized> DisplayField for Field<'_, T> {
+    fn tag<S: MessageSerializer>(&self, key: &str, serialize: &mut S) -> Result<(), IRCError> {
+        let value = self.0.to_string();
+        let mut tags = serialize.tags();
+        tags.tag(key, Some(&value))?;
+        tags.end();
+        Ok(())
+    }
+
+    fn param<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError> {
+        let value = self.0.to_string();
+        let mut params = serialize.params();
+        params.push(&value)?;
+        params.end();
+        Ok(())
+    }
+
+    fn trailing<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError> {
+        serialize.trailing(&self.0.to_string())
+    }
+}
@@ -1,10 +1,14 @@
+mod field;
 mod size_tracker;
 
+#[doc(hidden)]
+pub use field::{DisplayField, Field, NestedField};
 pub use size_tracker::SizeTracker;
 
 use bytes::{BufMut, Bytes, BytesMut};
 
-use crate::{validators, Commands, IRCError};
+use crate::unescape::escape_chunks;
+use crate::{validators, Commands, IRCError, Limits, MessageSize};
 use crate::{AT, BANG, COLON, EQ, SEMICOLON, SPACE};
 
 /// Serialize the IRC message from custom data structure.
@@ -19,6 +23,16 @@ pub trait ToMessage {
         tracker.total()
     }
 
+    /// Returns the size of the tag section and body, to check against [`Limits`] before
+    /// sending.
+    fn message_size(&self) -> MessageSize {
+        let mut tracker = SizeTracker::new();
+        self.to_message(&mut tracker)
+            .expect("size calculation should not fail");
+
+        tracker.size()
+    }
+
     fn to_bytes(&self) -> Result<Bytes, IRCError> {
         let mut serializer = IRCSerializer::with_capacity(self.serialized_size());
         self.to_message(&mut serializer)?;
@@ -26,9 +40,26 @@ pub trait ToMessage {
     }
 }
 
+impl<T: ToMessage + ?Sized> ToMessage for &T {
+    fn to_message<S: MessageSerializer>(&self, serialize: &mut S) -> Result<(), IRCError> {
+        (**self).to_message(serialize)
+    }
+}
+
+pub(crate) use private::RawSource;
+
 mod private {
+    use crate::IRCError;
+
     pub trait Sealed {}
 
+    /// Writes parts of a received message as they are, without validating them again.
+    pub trait RawSource {
+        /// Writes `:<source>`, e.g. a services cloak or IPv6 host that the hostname
+        /// validators would reject.
+        fn raw_source(&mut self, source: &str) -> Result<(), IRCError>;
+    }
+
     impl Sealed for super::IRCSerializer {}
     impl<'a> Sealed for super::IRCTagsSerializer<'a> {}
     impl<'a> Sealed for super::IRCSourceSerializer<'a> {}
@@ -40,7 +71,7 @@ mod private {
     impl<'a> Sealed for super::size_tracker::SizeParamsTracker<'a> {}
 }
 
-pub trait MessageSerializer: private::Sealed + Sized {
+pub trait MessageSerializer: private::Sealed + private::RawSource + Sized {
     type Tags<'a>: SerializeTags
     where
         Self: 'a;
@@ -63,6 +94,9 @@ pub trait MessageSerializer: private::Sealed + Sized {
 
 pub trait SerializeTags: private::Sealed {
     fn tag(&mut self, key: &str, value: Option<&str>) -> Result<(), IRCError>;
+    /// Like [`tag`](Self::tag), but escapes the value instead of rejecting `;`, space, `\`,
+    /// CR and LF.
+    fn tag_escaped(&mut self, key: &str, value: Option<&str>) -> Result<(), IRCError>;
     fn flag(&mut self, key: &str) -> Result<(), IRCError>;
     fn end(self);
 }
@@ -87,31 +121,56 @@ pub struct IRCSerializer {
     has_command: bool,
     has_trailing: bool,
     needs_space: bool,
+    start: usize,
+    body_start: Option<usize>,
+    limits: Limits,
     buffer: BytesMut,
 }
 
 impl IRCSerializer {
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self {
-            has_tags: false,
-            has_command: false,
-            has_trailing: false,
-            needs_space: false,
-            buffer: BytesMut::new(),
-        }
+        Self::with_buffer(BytesMut::new())
     }
 
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_buffer(BytesMut::with_capacity(capacity))
+    }
+
+    /// Creates a serializer that appends to an existing buffer.
+    pub fn with_buffer(buffer: BytesMut) -> Self {
         Self {
             has_tags: false,
             has_command: false,
             has_trailing: false,
             needs_space: false,
-            buffer: BytesMut::with_capacity(capacity),
+            start: buffer.len(),
+            body_start: None,
+            limits: Limits::NONE,
+            buffer,
         }
     }
 
+    /// Fails with [`IRCError::TagsTooLong`] or [`IRCError::BodyTooLong`] as soon as the
+    /// message outgrows `limits`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ircv3_parse::message::ser::{IRCSerializer, MessageSerializer};
+    /// use ircv3_parse::{Commands, Limits};
+    ///
+    /// let mut serializer = IRCSerializer::new().with_limits(Limits::CLIENT);
+    /// serializer.command(Commands::PRIVMSG);
+    ///
+    /// let err = serializer.trailing(&"a".repeat(512)).unwrap_err();
+    /// assert!(err.is_length_error());
+    /// ```
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     fn add_space_if_needed(&mut self) {
         if self.needs_space {
             self.buffer.put_u8(SPACE);
@@ -119,9 +178,25 @@ impl IRCSerializer {
         }
     }
 
+    fn start_body(&mut self) {
+        self.add_space_if_needed();
+        self.body_start.get_or_insert(self.buffer.len());
+    }
+
+    fn body(&self) -> Section {
+        Section {
+            start: self.body_start.unwrap_or(self.buffer.len()),
+            max: self.limits.body,
+        }
+    }
+
     pub fn into_bytes(self) -> Bytes {
         self.buffer.freeze()
     }
+
+    pub fn into_buffer(self) -> BytesMut {
+        self.buffer
+    }
 }
 
 impl MessageSerializer for IRCSerializer {
@@ -142,6 +217,10 @@ impl MessageSerializer for IRCSerializer {
 
     fn tags(&mut self) -> Self::Tags<'_> {
         IRCTagsSerializer {
+            section: Section {
+                start: self.start,
+                max: self.limits.tags,
+            },
             buffer: &mut self.buffer,
             has_tags: &mut self.has_tags,
             needs_space: &mut self.needs_space,
@@ -153,24 +232,27 @@ impl MessageSerializer for IRCSerializer {
             validators::nick(name)?;
         }
 
-        self.add_space_if_needed();
+        self.start_body();
 
         self.buffer.put_u8(COLON);
         self.buffer.put_slice(name.as_bytes());
+        self.body().check_body(&self.buffer)?;
         Ok(IRCSourceSerializer {
+            section: self.body(),
             buffer: &mut self.buffer,
             needs_space: &mut self.needs_space,
         })
     }
 
     fn command(&mut self, command: Commands) {
-        self.add_space_if_needed();
+        self.start_body();
         self.has_command = true;
         self.buffer.put_slice(command.as_bytes());
     }
 
     fn params(&mut self) -> Self::Params<'_> {
         IRCParamsSerializer {
+            section: self.body(),
             buffer: &mut self.buffer,
         }
     }
@@ -184,7 +266,7 @@ impl MessageSerializer for IRCSerializer {
         }
 
         self.buffer.put_slice(value.as_bytes());
-        Ok(())
+        self.body().check_body(&self.buffer)
     }
 
     fn end(&mut self) -> Result<(), IRCError> {
@@ -192,12 +274,53 @@ impl MessageSerializer for IRCSerializer {
             return Err(IRCError::MissingCommand);
         }
 
+        self.body().check_body(&self.buffer)?;
+
         self.buffer.put_slice(b"\r\n");
         Ok(())
     }
 }
 
+impl RawSource for IRCSerializer {
+    fn raw_source(&mut self, source: &str) -> Result<(), IRCError> {
+        self.start_body();
+
+        self.buffer.put_u8(COLON);
+        self.buffer.put_slice(source.as_bytes());
+        self.needs_space = true;
+        self.body().check_body(&self.buffer)
+    }
+}
+
+/// Start and maximum size of a section of the buffer.
+#[derive(Clone, Copy)]
+struct Section {
+    start: usize,
+    max: usize,
+}
+
+impl Section {
+    /// Counts the space that follows the tags.
+    fn check_tags(&self, buffer: &BytesMut) -> Result<(), IRCError> {
+        let len = buffer.len() - self.start + 1;
+        if len > self.max {
+            return Err(IRCError::TagsTooLong { len, max: self.max });
+        }
+        Ok(())
+    }
+
+    /// Counts the CR LF that ends the body.
+    fn check_body(&self, buffer: &BytesMut) -> Result<(), IRCError> {
+        let len = buffer.len() - self.start + 2;
+        if len > self.max {
+            return Err(IRCError::BodyTooLong { len, max: self.max });
+        }
+        Ok(())
+    }
+}
+
 pub struct IRCTagsSerializer<'a> {
+    section: Section,
     buffer: &'a mut BytesMut,
     has_tags: &'a mut bool,
     needs_space: &'a mut bool,
@@ -222,7 +345,31 @@ impl<'a> SerializeTags for IRCTagsSerializer<'a> {
             self.buffer.put_slice(val.as_bytes());
         }
 
-        Ok(())
+        self.section.check_tags(self.buffer)
+    }
+
+    fn tag_escaped(&mut self, key: &str, value: Option<&str>) -> Result<(), IRCError> {
+        validators::tag_key(key)?;
+
+        if let Some(val) = value {
+            validators::unescaped_tag_value(val)?;
+        }
+
+        if !*self.has_tags {
+            self.buffer.put_u8(AT);
+            *self.has_tags = true;
+        } else {
+            self.buffer.put_u8(SEMICOLON);
+        }
+
+        self.buffer.put_slice(key.as_bytes());
+        self.buffer.put_u8(EQ);
+
+        if let Some(val) = value {
+            escape_chunks(val, |chunk| self.buffer.put_slice(chunk));
+        }
+
+        self.section.check_tags(self.buffer)
     }
 
     fn flag(&mut self, key: &str) -> Result<(), IRCError> {
@@ -236,7 +383,7 @@ impl<'a> SerializeTags for IRCTagsSerializer<'a> {
         }
 
         self.buffer.put_slice(key.as_bytes());
-        Ok(())
+        self.section.check_tags(self.buffer)
     }
 
     fn end(self) {
@@ -255,6 +402,7 @@ impl Drop for IRCTagsSerializer<'_> {
 }
 
 pub struct IRCSourceSerializer<'a> {
+    section: Section,
     buffer: &'a mut BytesMut,
     needs_space: &'a mut bool,
 }
@@ -264,14 +412,14 @@ impl<'a> SerializeSource for IRCSourceSerializer<'a> {
         validators::user(user)?;
         self.buffer.put_u8(BANG);
         self.buffer.put_slice(user.as_bytes());
-        Ok(())
+        self.section.check_body(self.buffer)
     }
 
     fn host(&mut self, host: &str) -> Result<(), IRCError> {
         validators::host(host)?;
         self.buffer.put_u8(AT);
         self.buffer.put_slice(host.as_bytes());
-        Ok(())
+        self.section.check_body(self.buffer)
     }
 
     fn end(self) {
@@ -286,6 +434,7 @@ impl Drop for IRCSourceSerializer<'_> {
 }
 
 pub struct IRCParamsSerializer<'a> {
+    section: Section,
     buffer: &'a mut BytesMut,
 }
 
@@ -294,7 +443,7 @@ impl<'a> SerializeParams for IRCParamsSerializer<'a> {
         validators::param(value)?;
         self.buffer.put_u8(SPACE);
         self.buffer.put_slice(value.as_bytes());
-        Ok(())
+        self.section.check_body(self.buffer)
     }
 
     fn extend<I, S>(&mut self, params: I) -> Result<(), IRCError>
@@ -307,7 +456,7 @@ impl<'a> SerializeParams for IRCParamsSerializer<'a> {
             self.buffer.put_u8(SPACE);
             self.buffer.put_slice(param.as_ref().as_bytes());
         }
-        Ok(())
+        self.section.check_body(self.buffer)
     }
 
     fn end(self) {}
@@ -693,4 +842,87 @@ mod tests {
         assert_eq!(" :Hello world", actual);
         assert_eq!(13, size);
     }
+
+    #[test]
+    fn length_limits() {
+        struct Line<'a> {
+            tag: &'a str,
+            channel: &'a str,
+            message: &'a str,
+        }
+
+        impl ToMessage for Line<'_> {
+            fn to_message<S: super::MessageSerializer>(
+                &self,
+                serialize: &mut S,
+            ) -> Result<(), crate::IRCError> {
+                let mut tags = serialize.tags();
+                tags.tag("a", Some(self.tag))?;
+                tags.end();
+
+                let mut source = serialize.source("nick")?;
+                source.user("user")?;
+                source.end();
+
+                serialize.command(Commands::PRIVMSG);
+
+                let mut params = serialize.params();
+                params.push(self.channel)?;
+                params.end();
+
+                serialize.trailing(self.message)?;
+                serialize.end()
+            }
+        }
+
+        fn write(line: &Line<'_>) -> Result<bytes::Bytes, crate::IRCError> {
+            let mut serializer = super::IRCSerializer::new().with_limits(crate::Limits::CLIENT);
+            line.to_message(&mut serializer)?;
+            Ok(serializer.into_bytes())
+        }
+
+        let tag = "b".repeat(4092);
+        let message = "c".repeat(481);
+        let line = Line {
+            tag: &tag,
+            channel: "#channel",
+            message: &message,
+        };
+
+        let size = line.message_size();
+        assert_eq!(4096, size.tags);
+        assert_eq!(512, size.body);
+        assert_eq!(line.serialized_size(), size.total());
+        assert_eq!(size.total(), write(&line).unwrap().len());
+
+        let long_tag = "b".repeat(4093);
+        let err = write(&Line {
+            tag: &long_tag,
+            ..line
+        })
+        .unwrap_err();
+        assert_eq!(
+            crate::IRCError::TagsTooLong {
+                len: 4097,
+                max: 4096
+            },
+            err
+        );
+
+        let long_message = "c".repeat(482);
+        let err = write(&Line {
+            message: &long_message,
+            ..line
+        })
+        .unwrap_err();
+        assert_eq!(crate::IRCError::BodyTooLong { len: 513, max: 512 }, err);
+
+        let long_channel = format!("#{}", "d".repeat(600));
+        let err = write(&Line {
+            channel: &long_channel,
+            ..line
+        })
+        .unwrap_err();
+        assert!(err.is_length_error());
+    }
 }
@@ -1,12 +1,18 @@
-use crate::message::ser::{MessageSerializer, SerializeParams, SerializeSource, SerializeTags};
-use crate::{Commands, IRCError};
+use crate::message::ser::{
+    MessageSerializer, RawSource, SerializeParams, SerializeSource, SerializeTags,
+};
+use crate::unescape::escaped_len;
+use crate::{Commands, IRCError, MessageSize};
 use crate::{AT, BANG, COLON, EQ, SEMICOLON, SPACE};
 
+/// Counts the bytes a message would serialize to, without writing them.
 pub struct SizeTracker {
     count: usize,
+    body_start: Option<usize>,
     has_tags: bool,
     has_command: bool,
     has_trailing: bool,
+    has_crlf: bool,
     needs_space: bool,
 }
 
@@ -15,9 +21,11 @@ impl SizeTracker {
     pub fn new() -> Self {
         Self {
             count: 0,
+            body_start: None,
             has_tags: false,
             has_command: false,
             has_trailing: false,
+            has_crlf: false,
             needs_space: false,
         }
     }
@@ -30,6 +38,25 @@ impl SizeTracker {
         self.count == 0
     }
 
+    /// Returns the size of the tag section and body, as measured by [`Limits`].
+    ///
+    /// The body includes a CR LF even if none was written.
+    ///
+    /// [`Limits`]: crate::Limits
+    pub fn size(&self) -> MessageSize {
+        let tags = match self.body_start {
+            Some(start) => start,
+            None if self.has_tags => self.count + 1,
+            None => 0,
+        };
+        let crlf = if self.has_crlf { 0 } else { 2 };
+
+        MessageSize {
+            tags,
+            body: self.count.saturating_sub(tags) + crlf,
+        }
+    }
+
     #[inline]
     pub fn put_u8(&mut self, _bytes: u8) {
         self.count += 1;
@@ -47,6 +74,12 @@ impl SizeTracker {
             self.needs_space = false;
         }
     }
+
+    #[inline]
+    fn start_body(&mut self) {
+        self.add_space_if_needed();
+        self.body_start.get_or_insert(self.count);
+    }
 }
 
 impl MessageSerializer for SizeTracker {
@@ -70,7 +103,7 @@ impl MessageSerializer for SizeTracker {
     }
 
     fn source(&mut self, name: &str) -> Result<Self::Source<'_>, IRCError> {
-        self.add_space_if_needed();
+        self.start_body();
         self.put_u8(COLON);
         self.put_slice(name.as_bytes());
         Ok(SizeSourceTracker {
@@ -80,7 +113,7 @@ impl MessageSerializer for SizeTracker {
     }
 
     fn command(&mut self, command: Commands) {
-        self.add_space_if_needed();
+        self.start_body();
         self.has_command = true;
         self.put_slice(command.as_bytes());
     }
@@ -102,6 +135,17 @@ impl MessageSerializer for SizeTracker {
 
     fn end(&mut self) -> Result<(), IRCError> {
         self.put_slice(b"\r\n");
+        self.has_crlf = true;
+        Ok(())
+    }
+}
+
+impl RawSource for SizeTracker {
+    fn raw_source(&mut self, source: &str) -> Result<(), IRCError> {
+        self.start_body();
+        self.put_u8(COLON);
+        self.put_slice(source.as_bytes());
+        self.needs_space = true;
         Ok(())
     }
 }
@@ -129,6 +173,24 @@ impl SerializeTags for SizeTagsTracker<'_> {
         Ok(())
     }
 
+    fn tag_escaped(&mut self, key: &str, value: Option<&str>) -> Result<(), IRCError> {
+        if !self.tracker.has_tags {
+            self.tracker.put_u8(AT);
+            self.tracker.has_tags = true;
+        } else {
+            self.tracker.put_u8(SEMICOLON);
+        }
+
+        self.tracker.put_slice(key.as_bytes());
+        self.tracker.put_u8(EQ);
+
+        if let Some(val) = value {
+            self.tracker.count += escaped_len(val);
+        }
+
+        Ok(())
+    }
+
     fn flag(&mut self, key: &str) -> Result<(), IRCError> {
         if !self.tracker.has_tags {
             self.tracker.put_u8(AT);
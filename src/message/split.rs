@@ -0,0 +1,441 @@
+//! Splitting long `PRIVMSG` and `NOTICE` text across several lines.
+
+use bytes::Bytes;
+
+use crate::compat::{format, Vec};
+
+use crate::message::ser::ToMessage;
+use crate::{Commands, IRCError, Limits, MessageBuilder};
+
+pub(crate) const MULTILINE: &str = "draft/multiline";
+pub(crate) const MULTILINE_CONCAT: &str = "draft/multiline-concat";
+
+/// Options for [`split_message`].
+#[derive(Debug, Clone, Copy)]
+pub struct SplitOptions<'a> {
+    /// `PRIVMSG` or `NOTICE`. Defaults to `PRIVMSG`.
+    pub command: Commands<'a>,
+    /// Length of the `nick!user@host` mask the server prepends when relaying the line.
+    pub source_len: usize,
+    /// Tags added to every line, or to the opening `BATCH` with `multiline`.
+    pub tags: &'a [(&'a str, Option<&'a str>)],
+    /// Defaults to [`Limits::CLIENT`].
+    pub limits: Limits,
+    /// Split at the last space that fits, falling back to a character boundary.
+    pub words: bool,
+    /// Never split inside an mIRC color code (`\x03` or `\x04` with its arguments).
+    pub formatting: bool,
+    /// Batch reference for `draft/multiline`. When set, the lines are wrapped in a `BATCH`
+    /// and continuations are tagged `draft/multiline-concat`.
+    pub multiline: Option<&'a str>,
+}
+
+impl Default for SplitOptions<'_> {
+    fn default() -> Self {
+        Self {
+            command: Commands::PRIVMSG,
+            source_len: 0,
+            tags: &[],
+            limits: Limits::CLIENT,
+            words: false,
+            formatting: false,
+            multiline: None,
+        }
+    }
+}
+
+/// Splits `text` into as many lines to `target` as needed to stay within
+/// [`SplitOptions::limits`] once the server prepends the source.
+///
+/// Lines never end inside a UTF-8 character. Line breaks in `text` always start a new
+/// message; blank lines are dropped unless `multiline` is set.
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::message::{split_message, SplitOptions};
+///
+/// let options = SplitOptions {
+///     source_len: "bot!bot@example.com".len(),
+///     words: true,
+///     ..Default::default()
+/// };
+/// let text = "lorem ipsum ".repeat(60);
+/// let lines = split_message("#channel", &text, &options)?;
+///
+/// assert_eq!(2, lines.len());
+/// for line in &lines {
+///     assert!(line.len() + options.source_len + 2 <= 512);
+/// }
+/// # Ok::<(), ircv3_parse::IRCError>(())
+/// ```
+///
+/// With `multiline`:
+///
+/// ```rust
+/// use ircv3_parse::message::{split_message, SplitOptions};
+///
+/// let options = SplitOptions {
+///     multiline: Some("ml1"),
+///     ..Default::default()
+/// };
+/// let lines = split_message("#channel", "hello\nworld", &options)?;
+///
+/// assert_eq!("BATCH +ml1 draft/multiline #channel\r\n", lines[0]);
+/// assert_eq!("@batch=ml1 PRIVMSG #channel :hello\r\n", lines[1]);
+/// assert_eq!("@batch=ml1 PRIVMSG #channel :world\r\n", lines[2]);
+/// assert_eq!("BATCH -ml1\r\n", lines[3]);
+/// # Ok::<(), ircv3_parse::IRCError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`IRCError`] if `target` or a tag is invalid, if the tags exceed the limit, or if
+/// not even one character fits on a line.
+pub fn split_message(
+    target: &str,
+    text: &str,
+    options: &SplitOptions<'_>,
+) -> Result<Vec<Bytes>, IRCError> {
+    let mut lines = Vec::new();
+
+    let batch_tag = options
+        .multiline
+        .map(|reference| [("batch", Some(reference))]);
+    let tags = match &batch_tag {
+        Some(batch_tag) => &batch_tag[..],
+        None => options.tags,
+    };
+
+    let max = text_budget(target, options)?;
+
+    if let Some(reference) = options.multiline {
+        lines.push(batch_open(reference, target, options)?);
+    }
+
+    for (chunk, concat) in pieces(text, max, options)? {
+        lines.push(message(target, chunk, tags, concat, options)?);
+    }
+
+    if let Some(reference) = options.multiline {
+        lines.push(batch_close(reference, options)?);
+    }
+
+    Ok(lines)
+}
+
+/// Splits `text` into pieces of at most `max` bytes, each paired with whether it continues
+/// the previous piece. Blank lines are kept only with `multiline`.
+pub(crate) fn pieces<'t>(
+    text: &'t str,
+    max: usize,
+    options: &SplitOptions<'_>,
+) -> Result<Vec<(&'t str, bool)>, IRCError> {
+    let mut pieces = Vec::new();
+
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+
+        if line.is_empty() {
+            if options.multiline.is_some() {
+                pieces.push((line, false));
+            }
+            continue;
+        }
+
+        let mut rest = line;
+        let mut concat = false;
+        while !rest.is_empty() {
+            let (chunk, next) = split_once(rest, max, options)?;
+            pieces.push((chunk, concat));
+
+            rest = next;
+            concat = options.multiline.is_some();
+        }
+    }
+
+    Ok(pieces)
+}
+
+/// `BATCH +<reference> draft/multiline <target>`, with the tags of `options`.
+pub(crate) fn batch_open(
+    reference: &str,
+    target: &str,
+    options: &SplitOptions<'_>,
+) -> Result<Bytes, IRCError> {
+    let open = format!("+{reference}");
+    let mut batch = MessageBuilder::new(Commands::BATCH);
+    batch.add_tags(options.tags)?;
+    batch.add_params([open.as_str(), MULTILINE, target])?;
+    checked(batch, options.limits)
+}
+
+pub(crate) fn batch_close(reference: &str, options: &SplitOptions<'_>) -> Result<Bytes, IRCError> {
+    let close = format!("-{reference}");
+    let mut batch = MessageBuilder::new(Commands::BATCH);
+    batch.add_param(&close)?;
+    checked(batch, options.limits)
+}
+
+/// Bytes of text that fit on one line, once the server prepends `:mask `.
+pub(crate) fn text_budget(target: &str, options: &SplitOptions<'_>) -> Result<usize, IRCError> {
+    let mut empty = MessageBuilder::new(options.command);
+    empty.add_param(target)?.set_trailing("")?;
+
+    let source = match options.source_len {
+        0 => 0,
+        len => len + 2,
+    };
+    let overhead = empty.message_size().body + source;
+
+    Ok(options.limits.body.saturating_sub(overhead))
+}
+
+/// Returns the longest allowed prefix of `rest` and what remains after it.
+fn split_once<'t>(
+    rest: &'t str,
+    max: usize,
+    options: &SplitOptions<'_>,
+) -> Result<(&'t str, &'t str), IRCError> {
+    if rest.len() <= max {
+        return Ok((rest, ""));
+    }
+
+    let mut cut = floor_char_boundary(rest, max);
+    if options.formatting {
+        cut = before_color_code(rest.as_bytes(), cut);
+    }
+
+    if options.words {
+        if let Some(space) = rest[..cut].rfind(' ').filter(|&space| space > 0) {
+            // Concatenated lines are joined as-is, so the space must stay.
+            return Ok(if options.multiline.is_some() {
+                rest.split_at(space + 1)
+            } else {
+                (&rest[..space], &rest[space + 1..])
+            });
+        }
+    }
+
+    if cut == 0 {
+        let width = rest.chars().next().map_or(1, char::len_utf8);
+        return Err(IRCError::BodyTooLong {
+            len: options.limits.body - max + width,
+            max: options.limits.body,
+        });
+    }
+
+    Ok(rest.split_at(cut))
+}
+
+pub(crate) fn message(
+    target: &str,
+    text: &str,
+    tags: &[(&str, Option<&str>)],
+    concat: bool,
+    options: &SplitOptions<'_>,
+) -> Result<Bytes, IRCError> {
+    let mut builder = MessageBuilder::new(options.command);
+    builder.add_tags(tags)?;
+    if concat {
+        builder.add_tag_flag(MULTILINE_CONCAT)?;
+    }
+    builder.add_param(target)?.set_trailing(text)?;
+
+    checked(builder, options.limits)
+}
+
+/// Builds the line, rejecting it if its tags are over the limit. Body sizes are handled by
+/// [`text_budget`], which also accounts for the source.
+fn checked(builder: MessageBuilder<'_>, limits: Limits) -> Result<Bytes, IRCError> {
+    let size = builder.message_size();
+    if size.tags > limits.tags {
+        return Err(IRCError::TagsTooLong {
+            len: size.tags,
+            max: limits.tags,
+        });
+    }
+
+    Ok(builder.build())
+}
+
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Moves `cut` back to the start of a color code it falls inside of.
+///
+/// `\x03` takes up to two digits, optionally followed by a comma and up to two more.
+/// `\x04` takes six hex digits, optionally followed by a comma and six more.
+fn before_color_code(bytes: &[u8], cut: usize) -> usize {
+    let from = cut.saturating_sub(13);
+
+    for start in (from..cut).rev() {
+        let end = match bytes[start] {
+            0x03 => color_code_end(bytes, start, 2, |b| b.is_ascii_digit()),
+            0x04 => color_code_end(bytes, start, 6, |b| b.is_ascii_hexdigit()),
+            _ => continue,
+        };
+
+        return if end > cut { start } else { cut };
+    }
+
+    cut
+}
+
+fn color_code_end(bytes: &[u8], start: usize, width: usize, digit: fn(&u8) -> bool) -> usize {
+    let digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take(width)
+            .take_while(|b| digit(b))
+            .count()
+    };
+
+    let mut end = start + 1;
+    let foreground = digits(end);
+    end += foreground;
+
+    if foreground > 0 && bytes.get(end) == Some(&b',') {
+        let background = digits(end + 1);
+        if background > 0 {
+            end += 1 + background;
+        }
+    }
+
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_message, SplitOptions};
+    use crate::{Commands, IRCError, Limits};
+
+    fn trailing(line: &[u8]) -> &str {
+        let line = core::str::from_utf8(line).unwrap();
+        crate::parse(line).unwrap().params().trailing.as_str()
+    }
+
+    fn small(body: usize) -> SplitOptions<'static> {
+        SplitOptions {
+            limits: Limits {
+                body,
+                ..Limits::CLIENT
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fits_on_one_line() {
+        let lines = split_message("#channel", "hello", &SplitOptions::default()).unwrap();
+        assert_eq!(vec!["PRIVMSG #channel :hello\r\n"], lines);
+    }
+
+    #[test]
+    fn accounts_for_source_and_tags() {
+        // "PRIVMSG #c :" + CRLF is 14 bytes, ":nick!u@h " another 10.
+        let options = SplitOptions {
+            source_len: "nick!u@h".len(),
+            tags: &[("+draft/reply", Some("abc"))],
+            ..small(30)
+        };
+
+        let lines = split_message("#c", "0123456789", &options).unwrap();
+        assert_eq!(2, lines.len());
+        assert_eq!("@+draft/reply=abc PRIVMSG #c :012345\r\n", lines[0]);
+        assert_eq!("6789", trailing(&lines[1]));
+    }
+
+    #[test]
+    fn utf8_boundaries() {
+        // 16 bytes of text per line; each character is 3 bytes.
+        let lines = split_message("#c", "가나다라마바사아자차", &small(30)).unwrap();
+        let chunks: Vec<_> = lines.iter().map(|l| trailing(l)).collect();
+
+        assert_eq!(vec!["가나다라마", "바사아자차"], chunks);
+    }
+
+    #[test]
+    fn words() {
+        let options = SplitOptions {
+            words: true,
+            ..small(30)
+        };
+
+        let lines = split_message("#c", "hello there world", &options).unwrap();
+        let chunks: Vec<_> = lines.iter().map(|l| trailing(l)).collect();
+        assert_eq!(vec!["hello there", "world"], chunks);
+
+        let lines = split_message("#c", "abcdefghijklmnopqrstuvwxyz", &options).unwrap();
+        let chunks: Vec<_> = lines.iter().map(|l| trailing(l)).collect();
+        assert_eq!(vec!["abcdefghijklmnop", "qrstuvwxyz"], chunks);
+    }
+
+    #[test]
+    fn formatting() {
+        let text = "abcdefghijklm\x0312,04red";
+        let lines = split_message("#c", text, &small(30)).unwrap();
+        assert_eq!("abcdefghijklm\x0312", trailing(&lines[0]));
+
+        let options = SplitOptions {
+            formatting: true,
+            ..small(30)
+        };
+        let lines = split_message("#c", text, &options).unwrap();
+        let chunks: Vec<_> = lines.iter().map(|l| trailing(l)).collect();
+        assert_eq!(vec!["abcdefghijklm", "\x0312,04red"], chunks);
+    }
+
+    #[test]
+    fn line_breaks() {
+        let options = SplitOptions {
+            command: Commands::NOTICE,
+            ..Default::default()
+        };
+
+        let lines = split_message("nick", "one\r\n\ntwo", &options).unwrap();
+        assert_eq!(vec!["NOTICE nick :one\r\n", "NOTICE nick :two\r\n"], lines);
+    }
+
+    #[test]
+    fn multiline() {
+        let options = SplitOptions {
+            tags: &[("+draft/reply", Some("abc"))],
+            words: true,
+            multiline: Some("b1"),
+            ..small(30)
+        };
+
+        let lines = split_message("#c", "hello there world\n\nbye", &options).unwrap();
+        assert_eq!(
+            vec![
+                "@+draft/reply=abc BATCH +b1 draft/multiline #c\r\n",
+                "@batch=b1 PRIVMSG #c :hello there \r\n",
+                "@batch=b1;draft/multiline-concat PRIVMSG #c :world\r\n",
+                "@batch=b1 PRIVMSG #c :\r\n",
+                "@batch=b1 PRIVMSG #c :bye\r\n",
+                "BATCH -b1\r\n",
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn nothing_fits() {
+        let err = split_message("#c", "hello", &small(14)).unwrap_err();
+        assert_eq!(IRCError::BodyTooLong { len: 15, max: 14 }, err);
+
+        let options = SplitOptions {
+            tags: &[("a", Some(&"b".repeat(5000)))],
+            ..Default::default()
+        };
+        assert!(split_message("#c", "hello", &options)
+            .unwrap_err()
+            .is_length_error());
+    }
+}
@@ -0,0 +1,532 @@
+//! Channel and user `MODE` strings.
+//!
+//! Whether a mode takes an argument depends on the server: [`ModeTable`] holds the
+//! `CHANMODES`, `PREFIX` and `CHANTYPES` tokens of `RPL_ISUPPORT`, and [`ModeChanges`]
+//! uses it to pair each mode with its argument. [`ModeBuilder`] packs changes back into
+//! `MODE` lines.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ircv3_parse::mode::{ModeChange, ModeChanges, ModeTable};
+//!
+//! let msg = ircv3_parse::parse(":op!op@example.com MODE #chan +ov-b alice bob *!*@spam")?;
+//! let changes = ModeChanges::from_message(&msg, ModeTable::RFC)?;
+//!
+//! assert_eq!("#chan", changes.target());
+//! assert_eq!(
+//!     vec![
+//!         ModeChange { set: true, mode: 'o', arg: Some("alice") },
+//!         ModeChange { set: true, mode: 'v', arg: Some("bob") },
+//!         ModeChange { set: false, mode: 'b', arg: Some("*!*@spam") },
+//!     ],
+//!     changes.collect::<Vec<_>>()
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use core::str::Chars;
+
+use bytes::Bytes;
+
+use crate::compat::{String, Vec};
+
+use crate::isupport::{ChanModes, ISupport};
+use crate::{Commands, DeError, IRCError, Limits, Message, MessageBuilder, MessageSize};
+
+/// How a channel mode takes its argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeKind {
+    /// `CHANMODES` type A, a list such as bans. Takes an argument when set or unset, and
+    /// none to query the list.
+    List,
+    /// `CHANMODES` type B. Always takes an argument.
+    Setting,
+    /// `CHANMODES` type C. Takes an argument only when set.
+    SetOnly,
+    /// `CHANMODES` type D. Never takes an argument.
+    Flag,
+    /// A `PREFIX` mode such as `o`. Always takes a nickname.
+    Prefix,
+}
+
+impl ModeKind {
+    /// Returns `true` if the mode takes an argument when `set`, or when unset.
+    pub fn takes_arg(&self, set: bool) -> bool {
+        match self {
+            Self::List | Self::Setting | Self::Prefix => true,
+            Self::SetOnly => set,
+            Self::Flag => false,
+        }
+    }
+}
+
+/// The modes a server supports, from `RPL_ISUPPORT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModeTable<'a> {
+    pub chanmodes: ChanModes<'a>,
+    /// Mode letters of `PREFIX`, e.g. `ov` for `(ov)@+`.
+    pub prefix: &'a str,
+    /// Channel prefixes from `CHANTYPES`. Other targets are users.
+    pub chantypes: &'a str,
+}
+
+impl ModeTable<'static> {
+    /// The RFC 2811 channel modes with `(ov)@+` prefixes.
+    pub const RFC: Self = Self {
+        chanmodes: ChanModes::RFC,
+        prefix: "ov",
+        chantypes: "#&",
+    };
+}
+
+impl<'a> ModeTable<'a> {
+    /// Returns the kind of channel mode `mode`. Unknown modes are treated as flags.
+    pub fn kind(&self, mode: char) -> ModeKind {
+        if self.prefix.contains(mode) {
+            ModeKind::Prefix
+        } else if self.chanmodes.a.contains(mode) {
+            ModeKind::List
+        } else if self.chanmodes.b.contains(mode) {
+            ModeKind::Setting
+        } else if self.chanmodes.c.contains(mode) {
+            ModeKind::SetOnly
+        } else {
+            ModeKind::Flag
+        }
+    }
+
+    /// Returns `true` if `target` names a channel.
+    pub fn is_channel(&self, target: &str) -> bool {
+        target
+            .chars()
+            .next()
+            .is_some_and(|c| self.chantypes.contains(c))
+    }
+}
+
+impl Default for ModeTable<'_> {
+    fn default() -> Self {
+        ModeTable::RFC
+    }
+}
+
+impl<'a> From<&'a ISupport> for ModeTable<'a> {
+    fn from(isupport: &'a ISupport) -> Self {
+        let prefix = isupport
+            .get("PREFIX")
+            .map(|value| {
+                value
+                    .strip_prefix('(')
+                    .and_then(|value| value.split_once(')'))
+                    .map_or("", |(modes, _)| modes)
+            })
+            .unwrap_or(ModeTable::RFC.prefix);
+
+        Self {
+            chanmodes: isupport.chanmodes(),
+            prefix,
+            chantypes: isupport.chantypes(),
+        }
+    }
+}
+
+/// A single `+x` or `-x`, with its argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModeChange<'a> {
+    /// `true` for `+`, `false` for `-`.
+    pub set: bool,
+    pub mode: char,
+    pub arg: Option<&'a str>,
+}
+
+/// Iterator over the changes of a mode string such as `+ov-b alice bob *!*@spam`.
+///
+/// A mode whose argument is missing gets `None`.
+#[derive(Debug, Clone)]
+pub struct ModeChanges<'a> {
+    target: &'a str,
+    modes: Chars<'a>,
+    args: Vec<&'a str>,
+    next_arg: usize,
+    set: bool,
+    table: Option<ModeTable<'a>>,
+}
+
+impl<'a> ModeChanges<'a> {
+    /// Reads channel mode changes, taking arguments from `args` according to `table`.
+    pub fn channel<I>(modes: &'a str, args: I, table: ModeTable<'a>) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        Self {
+            target: "",
+            modes: modes.chars(),
+            args: args.into_iter().collect(),
+            next_arg: 0,
+            set: true,
+            table: Some(table),
+        }
+    }
+
+    /// Reads user mode changes, none of which take an argument.
+    pub fn user(modes: &'a str) -> Self {
+        Self {
+            target: "",
+            modes: modes.chars(),
+            args: Vec::new(),
+            next_arg: 0,
+            set: true,
+            table: None,
+        }
+    }
+
+    /// Reads a `MODE` message, as channel modes if the target is a channel according to
+    /// `table` and as user modes otherwise. A `MODE` without a mode string has no changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeError`] if the message is not a `MODE` or has no target.
+    pub fn from_message(msg: &Message<'a>, table: ModeTable<'a>) -> Result<Self, DeError> {
+        if !matches!(msg.command(), Commands::MODE) {
+            return Err(DeError::invalid_command("MODE", msg.command().as_str()));
+        }
+
+        let params = msg.params();
+        let mut params = params.middles.iter().chain(params.trailing.raw());
+
+        let target = params.next().ok_or_else(DeError::missing_param)?;
+        let modes = params.next().unwrap_or_default();
+
+        let changes = if table.is_channel(target) {
+            Self::channel(modes, params, table)
+        } else {
+            Self::user(modes)
+        };
+
+        Ok(Self { target, ..changes })
+    }
+
+    /// Returns the target of the `MODE` message, or `""` if not read from a message.
+    pub fn target(&self) -> &'a str {
+        self.target
+    }
+}
+
+impl<'a> Iterator for ModeChanges<'a> {
+    type Item = ModeChange<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mode = self.modes.next()?;
+            match mode {
+                '+' => self.set = true,
+                '-' => self.set = false,
+                _ => {
+                    let takes_arg = self
+                        .table
+                        .is_some_and(|table| table.kind(mode).takes_arg(self.set));
+
+                    let arg = if takes_arg {
+                        let arg = self.args.get(self.next_arg).copied();
+                        self.next_arg += 1;
+                        arg
+                    } else {
+                        None
+                    };
+
+                    return Some(ModeChange {
+                        set: self.set,
+                        mode,
+                        arg,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Packs mode changes into as few `MODE` lines as the server allows.
+///
+/// Each line carries at most [`max_args`](Self::max_args) changes with an argument, the
+/// `MODES` token of `RPL_ISUPPORT`, and fits in [`Limits::CLIENT`].
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::mode::ModeBuilder;
+///
+/// let mut builder = ModeBuilder::new("#chan").max_args(2);
+/// builder.set('o', "alice").set('o', "bob").unset('b', "*!*@spam").flag(true, 'm');
+///
+/// let lines = builder.build()?;
+/// assert_eq!("MODE #chan +oo alice bob\r\n", lines[0]);
+/// assert_eq!("MODE #chan -b+m *!*@spam\r\n", lines[1]);
+/// # Ok::<(), ircv3_parse::IRCError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModeBuilder<'a> {
+    target: &'a str,
+    changes: Vec<ModeChange<'a>>,
+    max_args: usize,
+    limits: Limits,
+}
+
+impl<'a> ModeBuilder<'a> {
+    pub fn new(target: &'a str) -> Self {
+        Self {
+            target,
+            changes: Vec::new(),
+            max_args: 3,
+            limits: Limits::CLIENT,
+        }
+    }
+
+    /// Sets the number of changes with an argument per line. Defaults to 3, the RFC value.
+    pub fn max_args(mut self, max: usize) -> Self {
+        self.max_args = max.max(1);
+        self
+    }
+
+    /// Defaults to [`Limits::CLIENT`].
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn push(&mut self, change: ModeChange<'a>) -> &mut Self {
+        self.changes.push(change);
+        self
+    }
+
+    /// Adds `+mode arg`.
+    pub fn set(&mut self, mode: char, arg: &'a str) -> &mut Self {
+        self.push(ModeChange {
+            set: true,
+            mode,
+            arg: Some(arg),
+        })
+    }
+
+    /// Adds `-mode arg`.
+    pub fn unset(&mut self, mode: char, arg: &'a str) -> &mut Self {
+        self.push(ModeChange {
+            set: false,
+            mode,
+            arg: Some(arg),
+        })
+    }
+
+    /// Adds a change without an argument.
+    pub fn flag(&mut self, set: bool, mode: char) -> &mut Self {
+        self.push(ModeChange {
+            set,
+            mode,
+            arg: None,
+        })
+    }
+
+    /// Returns the `MODE` lines, or none without changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IRCError`] if the target or an argument is not a valid parameter, or a
+    /// single change does not fit in a line.
+    pub fn build(&self) -> Result<Vec<Bytes>, IRCError> {
+        // `MODE <target> ` and the CR LF.
+        let base = "MODE ".len() + self.target.len() + 1 + 2;
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+        while start < self.changes.len() {
+            let mut len = base;
+            let mut args = 0;
+            let mut sign = None;
+            let mut end = start;
+
+            for change in &self.changes[start..] {
+                let mut added = change.mode.len_utf8();
+                if sign != Some(change.set) {
+                    added += 1;
+                }
+                if let Some(arg) = change.arg {
+                    added += 1 + arg.len();
+                }
+
+                let too_long = len + added > self.limits.body;
+                let too_many = change.arg.is_some() && args == self.max_args;
+                if end > start && (too_long || too_many) {
+                    break;
+                }
+
+                len += added;
+                sign = Some(change.set);
+                args += usize::from(change.arg.is_some());
+                end += 1;
+            }
+
+            let line = self.line(&self.changes[start..end])?;
+            self.limits.check(MessageSize {
+                tags: 0,
+                body: line.len(),
+            })?;
+            lines.push(line);
+            start = end;
+        }
+
+        Ok(lines)
+    }
+
+    fn line(&self, changes: &[ModeChange<'a>]) -> Result<Bytes, IRCError> {
+        let mut modes = String::new();
+        let mut sign = None;
+        for change in changes {
+            if sign != Some(change.set) {
+                modes.push(if change.set { '+' } else { '-' });
+                sign = Some(change.set);
+            }
+            modes.push(change.mode);
+        }
+
+        let mut builder = MessageBuilder::new(Commands::MODE);
+        builder.add_param(self.target)?.add_param(&modes)?;
+        for arg in changes.iter().filter_map(|change| change.arg) {
+            builder.add_param(arg)?;
+        }
+        Ok(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ModeBuilder, ModeChange, ModeChanges, ModeKind, ModeTable};
+    use crate::isupport::ISupport;
+
+    fn change(set: bool, mode: char, arg: Option<&str>) -> ModeChange<'_> {
+        ModeChange { set, mode, arg }
+    }
+
+    #[test]
+    fn channel() {
+        let changes: Vec<_> =
+            ModeChanges::channel("+kl-lk+b-b", ["key", "10", "*!*@x"], ModeTable::RFC).collect();
+
+        assert_eq!(
+            vec![
+                change(true, 'k', Some("key")),
+                change(true, 'l', Some("10")),
+                change(false, 'l', None),
+                change(false, 'k', Some("*!*@x")),
+                change(true, 'b', None),
+                change(false, 'b', None),
+            ],
+            changes
+        );
+    }
+
+    #[test]
+    fn table_from_isupport() {
+        let mut isupport = ISupport::new();
+        isupport.handle(
+            &crate::parse(
+                ":irc.example.com 005 nick CHANMODES=beI,kf,lj,imnpst PREFIX=(qaohv)~&@%+ \
+                CHANTYPES=#! :are supported by this server",
+            )
+            .unwrap(),
+        );
+        let table = ModeTable::from(&isupport);
+
+        assert_eq!(ModeKind::Prefix, table.kind('q'));
+        assert_eq!(ModeKind::List, table.kind('I'));
+        assert_eq!(ModeKind::Setting, table.kind('f'));
+        assert_eq!(ModeKind::SetOnly, table.kind('j'));
+        assert_eq!(ModeKind::Flag, table.kind('s'));
+        assert_eq!(ModeKind::Flag, table.kind('Z'));
+        assert!(table.is_channel("!chan"));
+        assert!(!table.is_channel("&chan"));
+
+        let msg = crate::parse("MODE !chan +qj-h alice 5:10 :bob").unwrap();
+        let changes: Vec<_> = ModeChanges::from_message(&msg, table).unwrap().collect();
+        assert_eq!(
+            vec![
+                change(true, 'q', Some("alice")),
+                change(true, 'j', Some("5:10")),
+                change(false, 'h', Some("bob")),
+            ],
+            changes
+        );
+
+        assert_eq!(ModeTable::RFC, ModeTable::from(&ISupport::new()));
+    }
+
+    #[test]
+    fn user() {
+        let msg = crate::parse(":nick MODE nick :+iw-o").unwrap();
+        let changes = ModeChanges::from_message(&msg, ModeTable::RFC).unwrap();
+
+        assert_eq!("nick", changes.target());
+        assert_eq!(
+            vec![
+                change(true, 'i', None),
+                change(true, 'w', None),
+                change(false, 'o', None),
+            ],
+            changes.collect::<Vec<_>>()
+        );
+
+        let msg = crate::parse("MODE #chan").unwrap();
+        assert_eq!(
+            0,
+            ModeChanges::from_message(&msg, ModeTable::RFC)
+                .unwrap()
+                .count()
+        );
+        assert!(
+            ModeChanges::from_message(&crate::parse("MODE").unwrap(), ModeTable::RFC)
+                .unwrap_err()
+                .is_missing_param()
+        );
+        assert!(
+            ModeChanges::from_message(&crate::parse("JOIN #chan").unwrap(), ModeTable::RFC)
+                .unwrap_err()
+                .is_invalid_command()
+        );
+    }
+
+    #[test]
+    fn build() {
+        let mut builder = ModeBuilder::new("#chan");
+        for nick in ["a", "b", "c", "d"] {
+            builder.set('v', nick);
+        }
+        builder.flag(false, 'm').flag(true, 'n');
+
+        assert_eq!(
+            vec!["MODE #chan +vvv a b c\r\n", "MODE #chan +v-m+n d\r\n"],
+            builder.build().unwrap()
+        );
+        assert!(ModeBuilder::new("#chan").build().unwrap().is_empty());
+    }
+
+    #[test]
+    fn build_length() {
+        let mask = "x".repeat(200);
+        let mut builder = ModeBuilder::new("#chan").max_args(10);
+        for _ in 0..3 {
+            builder.set('b', &mask);
+        }
+
+        let lines = builder.build().unwrap();
+        assert_eq!(2, lines.len());
+        assert!(lines.iter().all(|line| line.len() <= 512));
+
+        let mask = "x".repeat(600);
+        let mut builder = ModeBuilder::new("#chan");
+        builder.set('b', &mask);
+        assert!(builder.build().unwrap_err().is_length_error());
+
+        let mut builder = ModeBuilder::new("#chan");
+        builder.set('b', "bad mask");
+        assert!(builder.build().is_err());
+    }
+}
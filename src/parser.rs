@@ -0,0 +1,508 @@
+//! Configurable parsing.
+//!
+//! [`parse`](crate::parse) rejects any line that breaks the message grammar. Real servers
+//! are not always that careful, so a [`Parser`] built with
+//! [`lenient`](ParserBuilder::lenient) repairs the common quirks instead and reports each
+//! repair as a [`Warning`]:
+//!
+//! - Repeated spaces between components are collapsed
+//! - Whitespace at the end of the line is trimmed
+//! - Empty tag entries (`@;a=b`) are skipped
+//! - A line that ends after its tags or source is accepted with an empty command
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ircv3_parse::parser::{Parser, Warning};
+//! use ircv3_parse::ByteSpan;
+//!
+//! let parser = Parser::builder().lenient(true).build();
+//!
+//! let parsed = parser.parse("@;id=1 :nick PRIVMSG  #channel :hi")?;
+//! let msg = parsed.message();
+//!
+//! assert_eq!("1", msg.tags().unwrap().get("id").unwrap().as_str());
+//! assert_eq!("#channel", msg.params().middles.first().unwrap());
+//! assert_eq!(
+//!     &[
+//!         Warning::EmptyTag { span: ByteSpan::new(1, 2) },
+//!         Warning::ExtraSpaces { span: ByteSpan::new(21, 22) },
+//!     ],
+//!     parsed.warnings()
+//! );
+//! # Ok::<(), ircv3_parse::IRCError>(())
+//! ```
+
+use crate::compat::{Cow, Display, FmtResult, Formatter, String, Vec};
+
+use crate::scanner::{find_byte, find_line_ending, ByteSpan, Scanner};
+use crate::{IRCError, Limits, Message, PositionedError, AT, COLON, SEMICOLON, SPACE};
+
+/// Options for [`Parser`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Repair common server quirks instead of rejecting the line.
+    pub lenient: bool,
+    /// Reject lines whose tag section or body is too long.
+    pub limits: Limits,
+}
+
+/// An IRC message parser.
+///
+/// [`Parser::default()`] is strict and behaves like [`parse`](crate::parse). Of the parsing
+/// functions, only a parser built with [`ParserBuilder::limits`] checks [`Limits`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Parser {
+    options: ParseOptions,
+}
+
+impl Parser {
+    pub fn new(options: ParseOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn builder() -> ParserBuilder {
+        ParserBuilder::default()
+    }
+
+    pub fn options(&self) -> ParseOptions {
+        self.options
+    }
+
+    /// Parses a line, repairing it first in lenient mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PositionedError`] if the line cannot be parsed. In lenient mode, error
+    /// positions refer to the repaired line, available through [`Message::input_raw()`].
+    pub fn parse<'a>(&self, input: &'a str) -> Result<Parsed<'a>, PositionedError> {
+        let parsed = self.scan(input)?;
+        self.check_limits(&parsed.message())?;
+        Ok(parsed)
+    }
+
+    fn scan<'a>(&self, input: &'a str) -> Result<Parsed<'a>, PositionedError> {
+        if input.is_empty() {
+            return Err(IRCError::EmptyInput.at(ByteSpan::new(0, 0)));
+        }
+
+        if !self.options.lenient {
+            return Ok(Parsed {
+                scanner: Scanner::positioned(input.as_bytes())?,
+                line: Cow::Borrowed(input),
+                warnings: Vec::new(),
+            });
+        }
+
+        let mut warnings = Vec::new();
+        let line = repair(input, &mut warnings);
+        let line = if warnings.is_empty() {
+            Cow::Borrowed(input)
+        } else {
+            Cow::Owned(line)
+        };
+
+        if line.is_empty() {
+            return Err(IRCError::EmptyInput.at(ByteSpan::new(0, 0)));
+        }
+
+        Ok(Parsed {
+            scanner: Scanner::without_command(line.as_bytes())?,
+            line,
+            warnings,
+        })
+    }
+
+    /// Checks the configured limits, pointing the error at the first byte past the limit.
+    fn check_limits(&self, msg: &Message<'_>) -> Result<(), PositionedError> {
+        let size = msg.size();
+        let line_end = size.total() - 2;
+
+        self.options.limits.check(size).map_err(|error| {
+            let span = match error {
+                IRCError::TagsTooLong { max, .. } => ByteSpan::new(max, size.tags),
+                IRCError::BodyTooLong { max, .. } => {
+                    let start = (size.tags + max.saturating_sub(2)).min(line_end);
+                    ByteSpan::new(start, line_end)
+                }
+                _ => ByteSpan::new(0, line_end),
+            };
+            error.at(span)
+        })
+    }
+}
+
+/// Builder for [`Parser`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParserBuilder {
+    options: ParseOptions,
+}
+
+impl ParserBuilder {
+    /// Repairs common server quirks instead of rejecting the line. Defaults to `false`.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.options.lenient = lenient;
+        self
+    }
+
+    /// Rejects lines over `limits`. Defaults to [`Limits::NONE`].
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.options.limits = limits;
+        self
+    }
+
+    pub fn build(self) -> Parser {
+        Parser::new(self.options)
+    }
+}
+
+/// The result of [`Parser::parse()`].
+#[derive(Debug, Clone)]
+pub struct Parsed<'a> {
+    line: Cow<'a, str>,
+    scanner: Scanner,
+    warnings: Vec<Warning>,
+}
+
+impl Parsed<'_> {
+    /// Returns the parsed message.
+    ///
+    /// Borrows from the input, or from the repaired line if anything was repaired.
+    #[inline]
+    pub fn message(&self) -> Message<'_> {
+        Message::new(&self.line, self.scanner)
+    }
+
+    /// Returns the repairs made to the line, in order. Always empty in strict mode.
+    #[inline]
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+}
+
+/// A repair made by a lenient [`Parser`]. Spans refer to the original input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    /// Spaces beyond the single separator between two components.
+    ExtraSpaces { span: ByteSpan },
+    /// Spaces at the end of the line, outside the trailing parameter.
+    TrailingWhitespace { span: ByteSpan },
+    /// A `;` that separates nothing, or an `@` with no tags.
+    EmptyTag { span: ByteSpan },
+    /// The line ends before the command.
+    MissingCommand { span: ByteSpan },
+}
+
+impl Warning {
+    /// Returns the bytes of the original input this warning refers to.
+    pub fn span(&self) -> ByteSpan {
+        match self {
+            Self::ExtraSpaces { span }
+            | Self::TrailingWhitespace { span }
+            | Self::EmptyTag { span }
+            | Self::MissingCommand { span } => *span,
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let span = self.span();
+        match self {
+            Self::ExtraSpaces { .. } => write!(f, "repeated spaces at byte {}", span.start),
+            Self::TrailingWhitespace { .. } => {
+                write!(f, "trailing whitespace at byte {}", span.start)
+            }
+            Self::EmptyTag { .. } => write!(f, "empty tag at byte {}", span.start),
+            Self::MissingCommand { .. } => write!(f, "missing command at byte {}", span.start),
+        }
+    }
+}
+
+/// Rewrites `input` in canonical form, recording every change in `warnings`.
+fn repair(input: &str, warnings: &mut Vec<Warning>) -> String {
+    let bytes = input.as_bytes();
+    let end = find_line_ending(bytes).unwrap_or(bytes.len());
+
+    let mut line = String::with_capacity(input.len());
+    let mut pos = 0;
+    let has_tags = bytes[0] == AT;
+
+    if has_tags {
+        let tags_end = find_byte(SPACE, &bytes[..end]).unwrap_or(end);
+
+        let mut start = 1;
+        let mut previous_empty = false;
+        for tag in input[1..tags_end].split(SEMICOLON as char) {
+            if tag.is_empty() {
+                // Point at the `;` after an empty entry. The last entry has none, so point at
+                // the `;` or `@` before it, unless an empty entry before it already did.
+                if start < tags_end {
+                    warnings.push(Warning::EmptyTag {
+                        span: ByteSpan::new(start, start + 1),
+                    });
+                } else if !previous_empty {
+                    warnings.push(Warning::EmptyTag {
+                        span: ByteSpan::new(start - 1, start),
+                    });
+                }
+            } else {
+                line.push(if line.is_empty() { AT } else { SEMICOLON } as char);
+                line.push_str(tag);
+            }
+            previous_empty = tag.is_empty();
+            start += tag.len() + 1;
+        }
+
+        pos = tags_end;
+    }
+
+    let mut has_command = false;
+    let mut first = true;
+    loop {
+        let spaces = pos;
+        while pos < end && bytes[pos] == SPACE {
+            pos += 1;
+        }
+
+        if pos == end {
+            if pos > spaces {
+                warnings.push(Warning::TrailingWhitespace {
+                    span: ByteSpan::new(spaces, end),
+                });
+            }
+            break;
+        }
+
+        // The space after a tag section counts as a separator even if every tag was dropped.
+        let separator = usize::from(!line.is_empty() || (first && has_tags));
+        if pos - spaces > separator {
+            warnings.push(Warning::ExtraSpaces {
+                span: ByteSpan::new(spaces + separator, pos),
+            });
+        }
+        if !line.is_empty() {
+            line.push(SPACE as char);
+        }
+
+        let is_source = first && bytes[pos] == COLON;
+        if !is_source && bytes[pos] == COLON {
+            line.push_str(&input[pos..end]);
+            break;
+        }
+
+        let token_end = find_byte(SPACE, &bytes[pos..end]).map_or(end, |i| pos + i);
+        line.push_str(&input[pos..token_end]);
+        pos = token_end;
+
+        has_command |= !is_source;
+        first = false;
+    }
+
+    if line.is_empty() {
+        return line;
+    }
+
+    if !has_command {
+        warnings.push(Warning::MissingCommand {
+            span: ByteSpan::new(end, end),
+        });
+        // Tags and source are only recognised when followed by a space.
+        line.push(SPACE as char);
+    }
+
+    line.push_str(&input[end..]);
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Parser, Warning};
+    use crate::{ByteSpan, IRCError, Limits};
+
+    fn lenient() -> Parser {
+        Parser::builder().lenient(true).build()
+    }
+
+    #[test]
+    fn strict_rejects_quirks() {
+        let parser = Parser::default();
+
+        assert!(parser.parse(":nick  PRIVMSG #channel :hi").is_err());
+        assert!(parser.parse("PRIVMSG \r\n").is_err());
+        assert!(parser.parse(":nick.example.com\r\n").is_err());
+
+        let parsed = parser.parse("@;a=b PING").unwrap();
+        assert!(parsed.warnings().is_empty());
+    }
+
+    #[test]
+    fn conforming_line_is_borrowed() {
+        let input = "@a=b :nick PRIVMSG #channel :hi  there \r\n";
+        let parsed = lenient().parse(input).unwrap();
+
+        assert!(parsed.warnings().is_empty());
+        assert_eq!(input, parsed.message().input_raw());
+        assert_eq!("hi  there ", parsed.message().params().trailing.as_str());
+    }
+
+    #[test]
+    fn collapses_spaces() {
+        let parsed = lenient()
+            .parse(":nick   MODE #channel  +o   nick\r\n")
+            .unwrap();
+        let msg = parsed.message();
+
+        assert_eq!(":nick MODE #channel +o nick\r\n", msg.input_raw());
+        assert_eq!(
+            vec!["#channel", "+o", "nick"],
+            msg.params().middles.to_vec()
+        );
+        assert_eq!(
+            &[
+                Warning::ExtraSpaces {
+                    span: ByteSpan::new(6, 8)
+                },
+                Warning::ExtraSpaces {
+                    span: ByteSpan::new(22, 23)
+                },
+                Warning::ExtraSpaces {
+                    span: ByteSpan::new(26, 28)
+                },
+            ],
+            parsed.warnings()
+        );
+    }
+
+    #[test]
+    fn trims_trailing_whitespace() {
+        let parsed = lenient().parse("JOIN #channel  \r\n").unwrap();
+
+        assert_eq!("JOIN #channel\r\n", parsed.message().input_raw());
+        assert_eq!(
+            &[Warning::TrailingWhitespace {
+                span: ByteSpan::new(13, 15)
+            }],
+            parsed.warnings()
+        );
+    }
+
+    #[test]
+    fn skips_empty_tags() {
+        let parsed = lenient().parse("@a=1;;b;  PING").unwrap();
+        let msg = parsed.message();
+
+        assert_eq!("@a=1;b PING", msg.input_raw());
+        assert!(msg.tags().unwrap().get_flag("b"));
+        assert_eq!(
+            &[
+                Warning::EmptyTag {
+                    span: ByteSpan::new(5, 6)
+                },
+                Warning::EmptyTag {
+                    span: ByteSpan::new(7, 8)
+                },
+                Warning::ExtraSpaces {
+                    span: ByteSpan::new(9, 10)
+                },
+            ],
+            parsed.warnings()
+        );
+
+        let parsed = lenient().parse("@;; PING").unwrap();
+        assert_eq!("PING", parsed.message().input_raw());
+        assert_eq!(
+            &[
+                Warning::EmptyTag {
+                    span: ByteSpan::new(1, 2)
+                },
+                Warning::EmptyTag {
+                    span: ByteSpan::new(2, 3)
+                },
+            ],
+            parsed.warnings()
+        );
+
+        let parsed = lenient().parse("@ PING").unwrap();
+        assert_eq!("PING", parsed.message().input_raw());
+        assert!(parsed.message().tags().is_none());
+        assert_eq!(
+            &[Warning::EmptyTag {
+                span: ByteSpan::new(0, 1)
+            }],
+            parsed.warnings()
+        );
+
+        let parsed = lenient().parse("@;  PING").unwrap();
+        assert_eq!(
+            &[
+                Warning::EmptyTag {
+                    span: ByteSpan::new(1, 2)
+                },
+                Warning::ExtraSpaces {
+                    span: ByteSpan::new(3, 4)
+                },
+            ],
+            parsed.warnings()
+        );
+    }
+
+    #[test]
+    fn missing_command() {
+        let parsed = lenient().parse(":irc.example.com\r\n").unwrap();
+        let msg = parsed.message();
+
+        assert_eq!("irc.example.com", msg.source().unwrap().name);
+        assert_eq!("", msg.command().as_str());
+        assert!(msg.params().middles.is_empty());
+        assert_eq!(
+            &[Warning::MissingCommand {
+                span: ByteSpan::new(16, 16)
+            }],
+            parsed.warnings()
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let parser = Parser::builder().limits(Limits::SERVER).build();
+
+        let body = format!("PRIVMSG #channel :{}\r\n", "a".repeat(492));
+        assert_eq!(512, parser.parse(&body).unwrap().message().size().body);
+
+        let body = format!("PRIVMSG #channel :{}", "a".repeat(493));
+        let err = parser.parse(&body).unwrap_err();
+        assert_eq!(IRCError::BodyTooLong { len: 513, max: 512 }, err.error);
+        assert_eq!(ByteSpan::new(510, 511), err.span);
+
+        let tags = format!("@a={} PING", "b".repeat(8188));
+        let err = parser.parse(&tags).unwrap_err();
+        assert_eq!(
+            IRCError::TagsTooLong {
+                len: 8192,
+                max: 8191
+            },
+            err.error
+        );
+        assert_eq!(ByteSpan::new(8191, 8192), err.span);
+
+        let tags = format!("@a={} PING", "b".repeat(8187));
+        assert!(parser.parse(&tags).is_ok());
+
+        // Limits smaller than a line ending still point inside the line.
+        let parser = Parser::builder()
+            .limits(Limits { tags: 10, body: 1 })
+            .build();
+        let err = parser.parse("@a=b PING").unwrap_err();
+        assert_eq!(IRCError::BodyTooLong { len: 6, max: 1 }, err.error);
+        assert_eq!(ByteSpan::new(5, 9), err.span);
+        assert!(Parser::default().parse(&body).is_ok());
+    }
+
+    #[test]
+    fn unrepairable() {
+        assert!(lenient().parse("").is_err());
+        assert!(lenient().parse("@;\r\n").is_err());
+        assert!(lenient().parse(":nick !CMD").is_err());
+    }
+}
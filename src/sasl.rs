@@ -0,0 +1,756 @@
+//! SASL authentication over `AUTHENTICATE`.
+//!
+//! [`Sasl`] is a sans-IO state machine for the `PLAIN`, `EXTERNAL` and `SCRAM-SHA-256`
+//! mechanisms: it is fed parsed [`Message`]s and returns the lines to send. Payloads are
+//! base64 encoded and split into 400-byte `AUTHENTICATE` lines, and the `900`-`908` numerics
+//! update its [`SaslState`].
+//!
+//! Run it between `CAP ACK sasl` and `CAP END`, holding the end of negotiation with
+//! [`CapNegotiator::hold_end`](crate::cap::CapNegotiator::hold_end).
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ircv3_parse::sasl::{Sasl, SaslState};
+//!
+//! let mut sasl = Sasl::plain("jilles", "sesame");
+//! assert_eq!("AUTHENTICATE PLAIN\r\n", sasl.start());
+//!
+//! let out = sasl.handle(&ircv3_parse::parse("AUTHENTICATE +")?)?;
+//! assert_eq!(["AUTHENTICATE AGppbGxlcwBzZXNhbWU=\r\n"], &out[..]);
+//!
+//! let msg = ircv3_parse::parse(
+//!     ":irc.example.com 900 jilles jilles!jilles@example.com jilles :You are now logged in",
+//! )?;
+//! sasl.handle(&msg)?;
+//! sasl.handle(&ircv3_parse::parse(":irc.example.com 903 jilles :SASL successful")?)?;
+//!
+//! assert_eq!(SaslState::Success, sasl.state());
+//! assert_eq!(Some("jilles"), sasl.account());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::compat::{format, Debug, Display, FmtResult, Formatter, String, ToString, Vec};
+
+use crate::components::Numeric;
+use crate::error::SaslError;
+use crate::{Commands, DeError, Message, MessageBuilder};
+
+/// Largest `AUTHENTICATE` payload chunk, in base64 bytes.
+const CHUNK: usize = 400;
+
+/// Largest challenge buffered from `AUTHENTICATE` lines, in base64 bytes.
+pub const MAX_CHALLENGE_LENGTH: usize = 8192;
+
+/// Default limit on the SCRAM iteration count a server may ask for.
+pub const DEFAULT_MAX_ITERATIONS: u32 = 100_000;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mechanism {
+    Plain,
+    External,
+    ScramSha256,
+}
+
+impl Mechanism {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Plain => "PLAIN",
+            Self::External => "EXTERNAL",
+            Self::ScramSha256 => "SCRAM-SHA-256",
+        }
+    }
+}
+
+impl Display for Mechanism {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Progress of a [`Sasl`] exchange.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaslState {
+    /// `AUTHENTICATE` has not been sent.
+    Idle,
+    /// Waiting for the server.
+    Authenticating,
+    /// The server sent `903 RPL_SASLSUCCESS`.
+    Success,
+    /// The server sent `904`, `905`, `906` or `907`.
+    Failed(Numeric),
+}
+
+/// Where a mechanism is in its own exchange.
+#[derive(Debug, Clone)]
+enum Step {
+    Start,
+    /// `client-first-message-bare`, kept for the SCRAM auth message.
+    ClientFirst(String),
+    /// Server signature expected in the SCRAM `server-final-message`.
+    ClientFinal([u8; 32]),
+    Sent,
+}
+
+/// Authenticates with one SASL mechanism.
+///
+/// The SCRAM password is used as given, without SASLprep. [`Debug`] output leaves out the
+/// password, the nonce and the exchange in progress.
+#[derive(Clone)]
+pub struct Sasl {
+    mechanism: Mechanism,
+    authzid: String,
+    authcid: String,
+    password: String,
+    nonce: String,
+    step: Step,
+    state: SaslState,
+    incoming: String,
+    account: Option<String>,
+    mechanisms: Vec<String>,
+    max_iterations: u32,
+}
+
+impl Sasl {
+    fn new(mechanism: Mechanism, authcid: String, password: String, nonce: String) -> Self {
+        Self {
+            mechanism,
+            authzid: String::new(),
+            authcid,
+            password,
+            nonce,
+            step: Step::Start,
+            state: SaslState::Idle,
+            incoming: String::new(),
+            account: None,
+            mechanisms: Vec::new(),
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        }
+    }
+
+    /// Authenticates with `PLAIN`, sending the account name and password.
+    pub fn plain(authcid: impl Into<String>, password: impl Into<String>) -> Self {
+        Self::new(
+            Mechanism::Plain,
+            authcid.into(),
+            password.into(),
+            String::new(),
+        )
+    }
+
+    /// Authenticates with `EXTERNAL`, e.g. with a TLS client certificate.
+    pub fn external() -> Self {
+        Self::new(
+            Mechanism::External,
+            String::new(),
+            String::new(),
+            String::new(),
+        )
+    }
+
+    /// Authenticates with `SCRAM-SHA-256`.
+    ///
+    /// `nonce` must be a fresh random printable ASCII string without `,`, generated by the
+    /// caller for every attempt.
+    pub fn scram_sha256(
+        authcid: impl Into<String>,
+        password: impl Into<String>,
+        nonce: impl Into<String>,
+    ) -> Self {
+        Self::new(
+            Mechanism::ScramSha256,
+            authcid.into(),
+            password.into(),
+            nonce.into(),
+        )
+    }
+
+    /// Sets the identity to act as, if different from the authenticated one.
+    pub fn authzid(mut self, authzid: impl Into<String>) -> Self {
+        self.authzid = authzid.into();
+        self
+    }
+
+    /// Rejects SCRAM challenges asking for more than `max` iterations, each of which costs
+    /// an HMAC round. Defaults to [`DEFAULT_MAX_ITERATIONS`].
+    pub fn max_iterations(mut self, max: u32) -> Self {
+        self.max_iterations = max;
+        self
+    }
+
+    pub fn mechanism(&self) -> Mechanism {
+        self.mechanism
+    }
+
+    pub fn state(&self) -> SaslState {
+        self.state
+    }
+
+    /// Returns the account name from `900 RPL_LOGGEDIN`.
+    pub fn account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    /// Returns the mechanisms the server listed in `908 RPL_SASLMECHS`.
+    pub fn mechanisms(&self) -> impl Iterator<Item = &str> {
+        self.mechanisms.iter().map(String::as_str)
+    }
+
+    /// Returns `AUTHENTICATE <mechanism>`, the first line to send.
+    pub fn start(&mut self) -> Bytes {
+        self.step = Step::Start;
+        self.state = SaslState::Authenticating;
+        self.incoming.clear();
+
+        let mut builder = MessageBuilder::new(Commands::AUTHENTICATE);
+        builder.add_param(self.mechanism.as_str()).unwrap();
+        builder.build()
+    }
+
+    /// Returns `AUTHENTICATE *`, which asks the server to abort the exchange.
+    ///
+    /// Later `AUTHENTICATE` messages are ignored until [`start`](Self::start) is called
+    /// again.
+    pub fn abort(&mut self) -> Bytes {
+        self.step = Step::Start;
+        self.incoming.clear();
+        if self.state == SaslState::Authenticating {
+            self.state = SaslState::Idle;
+        }
+
+        let mut builder = MessageBuilder::new(Commands::AUTHENTICATE);
+        builder.add_param("*").unwrap();
+        builder.build()
+    }
+
+    /// Feeds a message from the server and returns the lines to send in response.
+    ///
+    /// Messages other than `AUTHENTICATE` and the SASL numerics are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SaslError`] if a challenge is malformed or longer than
+    /// [`MAX_CHALLENGE_LENGTH`], or the SCRAM server signature does not match. The exchange should then be aborted with [`abort`](Self::abort).
+    pub fn handle(&mut self, msg: &Message<'_>) -> Result<Vec<Bytes>, SaslError> {
+        let command = msg.command();
+        let params = msg.params();
+
+        if matches!(command, Commands::AUTHENTICATE) {
+            if self.state != SaslState::Authenticating {
+                return Ok(Vec::new());
+            }
+
+            let chunk = params
+                .middles
+                .first()
+                .or(params.trailing.raw())
+                .ok_or_else(DeError::missing_param)?;
+
+            if chunk != "+" {
+                if self.incoming.len() + chunk.len() > MAX_CHALLENGE_LENGTH {
+                    self.incoming.clear();
+                    return Err(SaslError::ChallengeTooLong {
+                        max: MAX_CHALLENGE_LENGTH,
+                    });
+                }
+                self.incoming.push_str(chunk);
+            }
+            if chunk.len() == CHUNK {
+                return Ok(Vec::new());
+            }
+
+            let challenge = STANDARD
+                .decode(core::mem::take(&mut self.incoming))
+                .map_err(|_| SaslError::InvalidBase64)?;
+
+            return Ok(self
+                .respond(&challenge)?
+                .map(|payload| authenticate(&payload))
+                .unwrap_or_default());
+        }
+
+        match command.numeric() {
+            Some(Numeric::RPL_LOGGEDIN) => {
+                self.account = params.middles.iter().nth(2).map(ToString::to_string);
+            }
+            Some(Numeric::RPL_LOGGEDOUT) => self.account = None,
+            Some(Numeric::RPL_SASLSUCCESS) => self.state = SaslState::Success,
+            Some(
+                numeric @ (Numeric::ERR_SASLFAIL
+                | Numeric::ERR_SASLTOOLONG
+                | Numeric::ERR_SASLABORTED
+                | Numeric::ERR_SASLALREADY),
+            ) => self.state = SaslState::Failed(numeric),
+            Some(Numeric::RPL_SASLMECHS) => {
+                let list = params.middles.second().unwrap_or_default();
+                self.mechanisms = list.split(',').map(ToString::to_string).collect();
+            }
+            _ => {}
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// Answers a decoded challenge, or returns `None` once there is nothing left to send.
+    fn respond(&mut self, challenge: &[u8]) -> Result<Option<Vec<u8>>, SaslError> {
+        match (self.mechanism, &self.step) {
+            (_, Step::Sent) => Ok(None),
+            (Mechanism::Plain, _) => {
+                self.step = Step::Sent;
+                Ok(Some(
+                    format!("{}\0{}\0{}", self.authzid, self.authcid, self.password).into_bytes(),
+                ))
+            }
+            (Mechanism::External, _) => {
+                self.step = Step::Sent;
+                Ok(Some(self.authzid.clone().into_bytes()))
+            }
+            (Mechanism::ScramSha256, Step::Start) => {
+                let bare = format!("n={},r={}", saslname(&self.authcid), self.nonce);
+                let first = format!("{}{}", self.gs2_header(), bare);
+                self.step = Step::ClientFirst(bare);
+                Ok(Some(first.into_bytes()))
+            }
+            (Mechanism::ScramSha256, Step::ClientFirst(bare)) => {
+                let server_first =
+                    core::str::from_utf8(challenge).map_err(|_| SaslError::InvalidScram)?;
+                let (nonce, salt, iterations) =
+                    server_first_message(server_first, self.max_iterations)?;
+                if nonce.len() <= self.nonce.len() || !nonce.starts_with(self.nonce.as_str()) {
+                    return Err(SaslError::InvalidScram);
+                }
+
+                let without_proof = format!("c={},r={}", STANDARD.encode(self.gs2_header()), nonce);
+                let auth_message = format!("{bare},{server_first},{without_proof}");
+
+                let salted = hi(self.password.as_bytes(), &salt, iterations);
+                let client_key = hmac(&salted, b"Client Key");
+                let stored_key: [u8; 32] = Sha256::digest(client_key).into();
+                let mut proof = hmac(&stored_key, auth_message.as_bytes());
+                for (p, k) in proof.iter_mut().zip(client_key) {
+                    *p ^= k;
+                }
+
+                let server_key = hmac(&salted, b"Server Key");
+                self.step = Step::ClientFinal(hmac(&server_key, auth_message.as_bytes()));
+
+                Ok(Some(
+                    format!("{},p={}", without_proof, STANDARD.encode(proof)).into_bytes(),
+                ))
+            }
+            (Mechanism::ScramSha256, Step::ClientFinal(signature)) => {
+                let server_final =
+                    core::str::from_utf8(challenge).map_err(|_| SaslError::InvalidScram)?;
+
+                if let Some(error) = server_final.strip_prefix("e=") {
+                    return Err(SaslError::Server(error.to_string()));
+                }
+
+                let verifier = server_final
+                    .split(',')
+                    .find_map(|attr| attr.strip_prefix("v="))
+                    .ok_or(SaslError::InvalidScram)?;
+                let verifier = STANDARD
+                    .decode(verifier)
+                    .map_err(|_| SaslError::InvalidBase64)?;
+                if verifier != signature {
+                    return Err(SaslError::ServerSignature);
+                }
+
+                self.step = Step::Sent;
+                Ok(Some(Vec::new()))
+            }
+        }
+    }
+
+    /// Returns the GS2 header: no channel binding, with the `authzid` if set.
+    fn gs2_header(&self) -> String {
+        if self.authzid.is_empty() {
+            String::from("n,,")
+        } else {
+            format!("n,a={},", saslname(&self.authzid))
+        }
+    }
+}
+
+impl Debug for Sasl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct(stringify!(Sasl))
+            .field("mechanism", &self.mechanism)
+            .field("authzid", &self.authzid)
+            .field("authcid", &self.authcid)
+            .field("password", &format_args!("<redacted>"))
+            .field("nonce", &format_args!("<redacted>"))
+            .field("state", &self.state)
+            .field("account", &self.account)
+            .field("mechanisms", &self.mechanisms)
+            .field("max_iterations", &self.max_iterations)
+            .finish()
+    }
+}
+
+/// Encodes `payload` into `AUTHENTICATE` lines of at most 400 bytes each.
+///
+/// An empty payload is sent as `+`, as is the end of a payload that fills its last line.
+///
+/// # Examples
+///
+/// ```rust
+/// let lines = ircv3_parse::sasl::authenticate(&[0; 300]);
+///
+/// assert_eq!(2, lines.len());
+/// assert_eq!(b"AUTHENTICATE +\r\n", lines[1].as_ref());
+/// ```
+pub fn authenticate(payload: &[u8]) -> Vec<Bytes> {
+    let encoded = STANDARD.encode(payload);
+    let mut lines: Vec<Bytes> = encoded
+        .as_bytes()
+        .chunks(CHUNK)
+        .map(|chunk| line(core::str::from_utf8(chunk).unwrap()))
+        .collect();
+
+    if encoded.len() % CHUNK == 0 {
+        lines.push(line("+"));
+    }
+
+    lines
+}
+
+fn line(chunk: &str) -> Bytes {
+    let mut builder = MessageBuilder::new(Commands::AUTHENTICATE);
+    builder.add_param(chunk).unwrap();
+    builder.build()
+}
+
+/// Escapes `=` and `,` in a SCRAM user name.
+fn saslname(name: &str) -> String {
+    name.replace('=', "=3D").replace(',', "=2C")
+}
+
+/// Reads the nonce, salt and iteration count of a SCRAM `server-first-message`.
+fn server_first_message(
+    message: &str,
+    max_iterations: u32,
+) -> Result<(&str, Vec<u8>, u32), SaslError> {
+    let mut nonce = None;
+    let mut salt = None;
+    let mut iterations = None;
+
+    for attr in message.split(',') {
+        match attr.split_once('=') {
+            Some(("r", value)) => nonce = Some(value),
+            Some(("s", value)) => {
+                salt = Some(
+                    STANDARD
+                        .decode(value)
+                        .map_err(|_| SaslError::InvalidBase64)?,
+                );
+            }
+            Some(("i", value)) => {
+                iterations = value.parse().ok().filter(|&i| i > 0 && i <= max_iterations);
+            }
+            Some(("m", _)) => return Err(SaslError::InvalidScram),
+            _ => {}
+        }
+    }
+
+    match (nonce, salt, iterations) {
+        (Some(nonce), Some(salt), Some(iterations)) => Ok((nonce, salt, iterations)),
+        _ => Err(SaslError::InvalidScram),
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// `Hi()` from RFC 5802, which is PBKDF2 with HMAC-SHA-256 and a single block.
+fn hi(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(password).expect("HMAC takes keys of any length");
+    mac.update(salt);
+    mac.update(&1u32.to_be_bytes());
+
+    let mut u: [u8; 32] = mac.finalize().into_bytes().into();
+    let mut result = u;
+    for _ in 1..iterations {
+        u = hmac(password, &u);
+        for (r, u) in result.iter_mut().zip(u) {
+            *r ^= u;
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    use super::{authenticate, Sasl, SaslState, MAX_CHALLENGE_LENGTH};
+    use crate::components::Numeric;
+    use crate::error::SaslError;
+
+    fn handle(sasl: &mut Sasl, line: &str) -> Result<Vec<String>, SaslError> {
+        let msg = crate::parse(line).unwrap();
+        Ok(sasl
+            .handle(&msg)?
+            .iter()
+            .map(|line| String::from_utf8(line.to_vec()).unwrap())
+            .collect())
+    }
+
+    fn challenge(payload: &str) -> String {
+        format!("AUTHENTICATE {}", STANDARD.encode(payload))
+    }
+
+    fn response(payload: &str) -> Vec<String> {
+        vec![format!("AUTHENTICATE {}\r\n", STANDARD.encode(payload))]
+    }
+
+    // RFC 7677, section 3.
+    const CLIENT_NONCE: &str = "rOprNGfwEbeRWgbNEkqO";
+    const SERVER_FIRST: &str = "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,\
+        s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096";
+    const CLIENT_FINAL: &str = "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,\
+        p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ=";
+    const SERVER_FINAL: &str = "v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=";
+
+    #[test]
+    fn scram_sha256() {
+        let mut sasl = Sasl::scram_sha256("user", "pencil", CLIENT_NONCE);
+        assert_eq!("AUTHENTICATE SCRAM-SHA-256\r\n", sasl.start());
+
+        assert_eq!(
+            response("n,,n=user,r=rOprNGfwEbeRWgbNEkqO"),
+            handle(&mut sasl, "AUTHENTICATE +").unwrap()
+        );
+        assert_eq!(
+            response(CLIENT_FINAL),
+            handle(&mut sasl, &challenge(SERVER_FIRST)).unwrap()
+        );
+        assert_eq!(
+            vec!["AUTHENTICATE +\r\n"],
+            handle(&mut sasl, &challenge(SERVER_FINAL)).unwrap()
+        );
+
+        handle(&mut sasl, ":irc.example.com 903 user :SASL successful").unwrap();
+        assert_eq!(SaslState::Success, sasl.state());
+    }
+
+    #[test]
+    fn scram_bad_server() {
+        let mut sasl = Sasl::scram_sha256("user", "pencil", CLIENT_NONCE);
+        sasl.start();
+        handle(&mut sasl, "AUTHENTICATE +").unwrap();
+        handle(&mut sasl, &challenge(SERVER_FIRST)).unwrap();
+
+        assert_eq!(
+            Err(SaslError::ServerSignature),
+            handle(
+                &mut sasl,
+                &challenge("v=AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=")
+            )
+        );
+
+        let mut sasl = Sasl::scram_sha256("user", "pencil", CLIENT_NONCE);
+        sasl.start();
+        handle(&mut sasl, "AUTHENTICATE +").unwrap();
+        assert_eq!(
+            Err(SaslError::InvalidScram),
+            handle(
+                &mut sasl,
+                &challenge("r=someoneelse,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=1")
+            )
+        );
+        assert_eq!(
+            Err(SaslError::InvalidBase64),
+            handle(&mut sasl, "AUTHENTICATE !!!!")
+        );
+    }
+
+    #[test]
+    fn scram_max_iterations() {
+        let mut sasl = Sasl::scram_sha256("user", "pencil", CLIENT_NONCE).max_iterations(4095);
+        sasl.start();
+        handle(&mut sasl, "AUTHENTICATE +").unwrap();
+        assert_eq!(
+            Err(SaslError::InvalidScram),
+            handle(&mut sasl, &challenge(SERVER_FIRST))
+        );
+
+        let mut sasl = Sasl::scram_sha256("user", "pencil", CLIENT_NONCE);
+        sasl.start();
+        handle(&mut sasl, "AUTHENTICATE +").unwrap();
+        let server_first = format!("r={CLIENT_NONCE}x,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4294967295");
+        assert_eq!(
+            Err(SaslError::InvalidScram),
+            handle(&mut sasl, &challenge(&server_first))
+        );
+    }
+
+    #[test]
+    fn challenge_too_long() {
+        let mut sasl = Sasl::plain("user", "pass");
+        sasl.start();
+
+        let chunk = format!("AUTHENTICATE {}", "A".repeat(400));
+        for _ in 0..MAX_CHALLENGE_LENGTH / 400 {
+            assert!(handle(&mut sasl, &chunk).unwrap().is_empty());
+        }
+        assert_eq!(
+            Err(SaslError::ChallengeTooLong {
+                max: MAX_CHALLENGE_LENGTH
+            }),
+            handle(&mut sasl, &chunk)
+        );
+    }
+
+    #[test]
+    fn scram_authzid() {
+        let mut sasl = Sasl::scram_sha256("us,er", "pencil", CLIENT_NONCE).authzid("ad=min");
+        sasl.start();
+
+        assert_eq!(
+            response("n,a=ad=3Dmin,n=us=2Cer,r=rOprNGfwEbeRWgbNEkqO"),
+            handle(&mut sasl, "AUTHENTICATE +").unwrap()
+        );
+    }
+
+    #[test]
+    fn external() {
+        let mut sasl = Sasl::external();
+        assert_eq!("AUTHENTICATE EXTERNAL\r\n", sasl.start());
+        assert_eq!(
+            vec!["AUTHENTICATE +\r\n"],
+            handle(&mut sasl, "AUTHENTICATE +").unwrap()
+        );
+
+        let mut sasl = Sasl::external().authzid("admin");
+        sasl.start();
+        assert_eq!(
+            response("admin"),
+            handle(&mut sasl, "AUTHENTICATE +").unwrap()
+        );
+    }
+
+    #[test]
+    fn abort() {
+        let mut sasl = Sasl::scram_sha256("user", "pencil", CLIENT_NONCE);
+        sasl.start();
+        handle(&mut sasl, "AUTHENTICATE +").unwrap();
+
+        assert_eq!("AUTHENTICATE *\r\n", sasl.abort());
+        assert_eq!(SaslState::Idle, sasl.state());
+        assert!(handle(&mut sasl, &challenge(SERVER_FIRST))
+            .unwrap()
+            .is_empty());
+
+        handle(
+            &mut sasl,
+            ":irc.example.com 906 user :SASL authentication aborted",
+        )
+        .unwrap();
+        assert_eq!(SaslState::Failed(Numeric::ERR_SASLABORTED), sasl.state());
+
+        // A new attempt starts over.
+        sasl.start();
+        assert_eq!(
+            response("n,,n=user,r=rOprNGfwEbeRWgbNEkqO"),
+            handle(&mut sasl, "AUTHENTICATE +").unwrap()
+        );
+    }
+
+    #[test]
+    fn debug_redacts_secrets() {
+        let sasl = Sasl::scram_sha256("user", "pencil", CLIENT_NONCE);
+        let debug = format!("{sasl:?}");
+
+        assert!(debug.contains("\"user\""));
+        assert!(!debug.contains("pencil"));
+        assert!(!debug.contains(CLIENT_NONCE));
+    }
+
+    #[test]
+    fn chunks() {
+        let lines = authenticate(&[b'a'; 450]);
+        assert_eq!(2, lines.len());
+        assert_eq!("AUTHENTICATE \r\n".len() + 400, lines[0].len());
+        assert_eq!("AUTHENTICATE \r\n".len() + 200, lines[1].len());
+
+        let lines = authenticate(&[b'a'; 300]);
+        assert_eq!(2, lines.len());
+        assert_eq!(b"AUTHENTICATE +\r\n", lines[1].as_ref());
+
+        assert_eq!(vec!["AUTHENTICATE +\r\n"], authenticate(b""));
+    }
+
+    #[test]
+    fn chunked_challenge() {
+        let payload = "a".repeat(300);
+        let encoded = STANDARD.encode(&payload);
+        let mut sasl = Sasl::plain("user", "pass");
+        sasl.start();
+
+        // PLAIN ignores the challenge, but only answers once all of it has arrived.
+        assert!(handle(&mut sasl, &format!("AUTHENTICATE {encoded}"))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            response("\0user\0pass"),
+            handle(&mut sasl, "AUTHENTICATE +").unwrap()
+        );
+        assert!(handle(&mut sasl, "AUTHENTICATE +").unwrap().is_empty());
+    }
+
+    #[test]
+    fn numerics() {
+        let mut sasl = Sasl::plain("user", "pass").authzid("admin");
+        sasl.start();
+        assert_eq!(
+            response("admin\0user\0pass"),
+            handle(&mut sasl, "AUTHENTICATE +").unwrap()
+        );
+
+        handle(
+            &mut sasl,
+            ":irc.example.com 908 user PLAIN,EXTERNAL :are available SASL mechanisms",
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["PLAIN", "EXTERNAL"],
+            sasl.mechanisms().collect::<Vec<_>>()
+        );
+
+        handle(
+            &mut sasl,
+            ":irc.example.com 904 user :SASL authentication failed",
+        )
+        .unwrap();
+        assert_eq!(SaslState::Failed(Numeric::ERR_SASLFAIL), sasl.state());
+        assert_eq!(None, sasl.account());
+
+        // Ignored outside an exchange.
+        assert!(handle(&mut sasl, "AUTHENTICATE +").unwrap().is_empty());
+        assert_eq!("AUTHENTICATE *\r\n", sasl.abort());
+
+        handle(
+            &mut sasl,
+            ":irc.example.com 900 user user!u@h admin :You are now logged in as admin",
+        )
+        .unwrap();
+        assert_eq!(Some("admin"), sasl.account());
+        handle(&mut sasl, ":irc.example.com 901 user user!u@h :Logged out").unwrap();
+        assert_eq!(None, sasl.account());
+    }
+}
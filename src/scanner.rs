@@ -1,5 +1,7 @@
+use core::ops::Range;
+
 use crate::{
-    error::{CommandError, IRCError, ParamError},
+    error::{CommandError, IRCError, ParamError, PositionedError},
     AT, COLON, CR, LF, SPACE,
 };
 
@@ -20,10 +22,30 @@ pub struct Scanner {
 
 impl Scanner {
     pub fn new(input: &str) -> Result<Self, IRCError> {
-        let bytes = input.as_bytes();
+        Self::from_bytes(input.as_bytes())
+    }
 
+    /// Scans raw bytes.
+    ///
+    /// Every delimiter is ASCII, so the resulting spans fall on UTF-8 boundaries whenever
+    /// the input is valid UTF-8.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IRCError> {
+        Ok(Self::scan(bytes, false)?)
+    }
+
+    /// Like [`from_bytes`](Self::from_bytes), keeping the position of the error.
+    pub(crate) fn positioned(bytes: &[u8]) -> Result<Self, PositionedError> {
+        Self::scan(bytes, false)
+    }
+
+    /// Scans a line that may end right after its tags or source, leaving the command empty.
+    pub(crate) fn without_command(bytes: &[u8]) -> Result<Self, PositionedError> {
+        Self::scan(bytes, true)
+    }
+
+    fn scan(bytes: &[u8], allow_missing_command: bool) -> Result<Self, PositionedError> {
         if bytes.is_empty() {
-            return Err(IRCError::EmptyInput);
+            return Err(IRCError::EmptyInput.at(ByteSpan::new(0, 0)));
         }
 
         let mut scanner = Self {
@@ -42,6 +64,10 @@ impl Scanner {
 
         scanner.scan_tags(bytes, &mut pos)?;
         scanner.scan_source(bytes, &mut pos)?;
+        if allow_missing_command && Self::is_message_end(bytes, pos) {
+            scanner.command_span = ByteSpan::new(pos, pos);
+            return Ok(scanner);
+        }
         scanner.scan_command(bytes, &mut pos)?;
 
         let line_end = Self::is_message_end(bytes, pos);
@@ -55,7 +81,7 @@ impl Scanner {
     }
 
     #[inline]
-    fn scan_tags(&mut self, bytes: &[u8], pos: &mut usize) -> Result<(), IRCError> {
+    fn scan_tags(&mut self, bytes: &[u8], pos: &mut usize) -> Result<(), PositionedError> {
         if bytes[*pos] != AT {
             return Ok(());
         }
@@ -63,8 +89,9 @@ impl Scanner {
         *pos += 1; // skip '@'
         let start = *pos;
 
-        *pos +=
-            find_byte(SPACE, &bytes[*pos..]).ok_or(IRCError::MissingSpace { component: "TAG" })?;
+        *pos += find_byte(SPACE, &bytes[*pos..]).ok_or_else(|| {
+            IRCError::MissingSpace { component: "TAG" }.at(unterminated(bytes, start - 1))
+        })?;
 
         self.tags_span = ByteSpan::new(start, *pos);
         self.has_tags = true;
@@ -73,7 +100,7 @@ impl Scanner {
     }
 
     #[inline]
-    fn scan_source(&mut self, bytes: &[u8], pos: &mut usize) -> Result<(), IRCError> {
+    fn scan_source(&mut self, bytes: &[u8], pos: &mut usize) -> Result<(), PositionedError> {
         if *pos >= bytes.len() || bytes[*pos] != COLON {
             return Ok(());
         }
@@ -81,8 +108,11 @@ impl Scanner {
         *pos += 1; // skip ':'
         let start = *pos;
 
-        *pos += find_byte(SPACE, &bytes[*pos..]).ok_or(IRCError::MissingSpace {
-            component: "SOURCE",
+        *pos += find_byte(SPACE, &bytes[*pos..]).ok_or_else(|| {
+            IRCError::MissingSpace {
+                component: "SOURCE",
+            }
+            .at(unterminated(bytes, start - 1))
         })?;
 
         self.source_span = ByteSpan::new(start, *pos);
@@ -93,9 +123,9 @@ impl Scanner {
     }
 
     #[inline]
-    fn scan_command(&mut self, bytes: &[u8], pos: &mut usize) -> Result<(), IRCError> {
+    fn scan_command(&mut self, bytes: &[u8], pos: &mut usize) -> Result<(), PositionedError> {
         if *pos >= bytes.len() {
-            return Err(IRCError::Command(CommandError::Empty));
+            return Err(IRCError::Command(CommandError::Empty).at(ByteSpan::new(*pos, *pos)));
         }
 
         let start = *pos;
@@ -104,7 +134,8 @@ impl Scanner {
         if !first_byte.is_ascii_alphanumeric() {
             return Err(IRCError::Command(CommandError::InvalidFirstChar {
                 char: first_byte as char,
-            }));
+            })
+            .at(char_span(bytes, start)));
         }
 
         if first_byte.is_ascii_digit() {
@@ -118,7 +149,8 @@ impl Scanner {
             if digit_count != 3 {
                 return Err(IRCError::Command(CommandError::WrongDigitCount {
                     actual: digit_count,
-                }));
+                })
+                .at(ByteSpan::new(start, *pos)));
             }
         } else {
             while *pos < bytes.len() && bytes[*pos].is_ascii_alphabetic() {
@@ -132,9 +164,9 @@ impl Scanner {
     }
 
     #[inline]
-    fn scan_parameters(&mut self, bytes: &[u8], mut pos: usize) -> Result<(), IRCError> {
+    fn scan_parameters(&mut self, bytes: &[u8], mut pos: usize) -> Result<(), PositionedError> {
         if bytes[pos] != SPACE {
-            return Err(IRCError::MissingSpace { component: "PARAM" });
+            return Err(IRCError::MissingSpace { component: "PARAM" }.at(char_span(bytes, pos)));
         }
 
         pos += 1; // skip space
@@ -147,7 +179,9 @@ impl Scanner {
             let start = pos + space_colon_pos;
 
             if start == pos {
-                return Err(IRCError::Param(ParamError::EmptyMiddle));
+                return Err(
+                    IRCError::Param(ParamError::EmptyMiddle).at(ByteSpan::new(pos, pos + 1))
+                );
             }
 
             self.params_span = ByteSpan::new(pos, start);
@@ -159,7 +193,7 @@ impl Scanner {
             let end_pos = pos + find_line_ending(&bytes[pos..]).unwrap_or(bytes[pos..].len());
 
             if end_pos == pos {
-                return Err(IRCError::Param(ParamError::EmptyMiddle));
+                return Err(IRCError::Param(ParamError::EmptyMiddle).at(ByteSpan::new(pos, pos)));
             }
 
             self.params_span = ByteSpan::new(pos, end_pos);
@@ -169,7 +203,7 @@ impl Scanner {
     }
 
     #[inline]
-    fn scan_trailing(&mut self, bytes: &[u8], pos: usize) -> Result<(), IRCError> {
+    fn scan_trailing(&mut self, bytes: &[u8], pos: usize) -> Result<(), PositionedError> {
         let end = pos + find_line_ending(&bytes[pos..]).unwrap_or(bytes[pos..].len());
 
         self.trailing_span = ByteSpan::new(pos, end);
@@ -200,8 +234,26 @@ impl Scanner {
     }
 }
 
+/// Span of a component starting at `start` that runs to the end of the line.
+fn unterminated(bytes: &[u8], start: usize) -> ByteSpan {
+    let end = start + find_line_ending(&bytes[start..]).unwrap_or(bytes.len() - start);
+    ByteSpan::new(start, end)
+}
+
+/// Span of the (possibly multi-byte) character at `pos`.
+pub(crate) fn char_span(bytes: &[u8], pos: usize) -> ByteSpan {
+    let width = match bytes[pos] {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => 1,
+    };
+
+    ByteSpan::new(pos, (pos + width).min(bytes.len()))
+}
+
 #[inline]
-fn find_byte(needle: u8, haystack: &[u8]) -> Option<usize> {
+pub(crate) fn find_byte(needle: u8, haystack: &[u8]) -> Option<usize> {
     if haystack.len() < MEMCHR_THRESHOLD {
         haystack.iter().position(|&b| b == needle)
     } else {
@@ -210,7 +262,7 @@ fn find_byte(needle: u8, haystack: &[u8]) -> Option<usize> {
 }
 
 #[inline]
-fn find_line_ending(bytes: &[u8]) -> Option<usize> {
+pub(crate) fn find_line_ending(bytes: &[u8]) -> Option<usize> {
     if bytes.len() < MEMCHR_THRESHOLD {
         bytes.iter().position(|&b| matches!(b, CR | LF))
     } else {
@@ -232,17 +284,14 @@ fn find_space_colon_pattern(bytes: &[u8]) -> Option<usize> {
         }
         None
     } else {
-        memchr::memchr(COLON, bytes).and_then(|colon_pos| {
-            if colon_pos > 0 && bytes[colon_pos - 1] == SPACE {
-                Some(colon_pos - 1)
-            } else {
-                None
-            }
-        })
+        memchr::memchr_iter(COLON, bytes)
+            .find(|&colon_pos| colon_pos > 0 && bytes[colon_pos - 1] == SPACE)
+            .map(|colon_pos| colon_pos - 1)
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Byte range `start..end` within a message line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ByteSpan {
     pub start: u32,
     pub end: u32,
@@ -256,7 +305,7 @@ impl Default for ByteSpan {
 
 impl ByteSpan {
     #[inline]
-    pub(crate) fn new(start: usize, end: usize) -> Self {
+    pub fn new(start: usize, end: usize) -> Self {
         Self {
             start: start as u32,
             end: end as u32,
@@ -277,6 +326,11 @@ impl ByteSpan {
         }
     }
 
+    #[inline]
+    pub(crate) fn extract_bytes<'a>(&self, input: &'a [u8]) -> &'a [u8] {
+        &input[self.start as usize..self.end as usize]
+    }
+
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.start == self.end
@@ -286,4 +340,33 @@ impl ByteSpan {
     pub fn len(&self) -> usize {
         (self.end - self.start) as usize
     }
+
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
+
+    /// Moves the span forward by `offset` bytes.
+    #[inline]
+    pub fn offset(self, offset: usize) -> Self {
+        Self::new(self.start as usize + offset, self.end as usize + offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_space_colon_pattern, Scanner};
+
+    #[test]
+    fn space_colon_after_inner_colon() {
+        // Long enough to take the memchr path.
+        let input = "MODE #chan +j-h 5:10 :bob";
+        let scanner = Scanner::new(input).unwrap();
+
+        assert_eq!("#chan +j-h 5:10", scanner.params_span.extract(input));
+        assert_eq!("bob", scanner.trailing_span.extract(input));
+
+        assert_eq!(Some(4), find_space_colon_pattern(b"5:10 :bob"));
+        assert_eq!(Some(15), find_space_colon_pattern(b"#chan +j-h 5:10 :bob"));
+    }
 }
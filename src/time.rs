@@ -0,0 +1,459 @@
+//! Timestamps from the IRCv3 `server-time` tag.
+//!
+//! Servers with the `server-time` capability tag messages with the time they were
+//! received, as `@time=2024-01-01T12:34:56.789Z`. [`ServerTime`] holds that instant with
+//! millisecond precision and writes it back in the same form. With `std` it converts to
+//! and from [`SystemTime`](std::time::SystemTime), with the `chrono` feature to
+//! `chrono::DateTime<Utc>`, and with the `time-rs` feature to `time::OffsetDateTime`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ircv3_parse::time::ServerTime;
+//!
+//! let msg = ircv3_parse::parse("@time=2024-01-01T12:34:56.789Z :nick PRIVMSG #c :hi")?;
+//! let time = msg.server_time().unwrap();
+//!
+//! assert_eq!((2024, 1, 1), time.date());
+//! assert_eq!((12, 34, 56, 789), time.time());
+//! assert_eq!(1_704_112_496_789, time.unix_millis());
+//! assert_eq!("2024-01-01T12:34:56.789Z", time.to_string());
+//! # Ok::<(), ircv3_parse::IRCError>(())
+//! ```
+
+use core::str::FromStr;
+
+use crate::compat::{Display, FmtResult, Formatter};
+
+use crate::error::ServerTimeError;
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+/// A UTC timestamp between years 0 and 9999, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServerTime {
+    /// Milliseconds since 1970-01-01T00:00:00.000Z.
+    millis: i64,
+}
+
+impl ServerTime {
+    /// 1970-01-01T00:00:00.000Z.
+    pub const UNIX_EPOCH: Self = Self { millis: 0 };
+    /// 0000-01-01T00:00:00.000Z.
+    pub const MIN: Self = Self {
+        millis: -62_167_219_200_000,
+    };
+    /// 9999-12-31T23:59:59.999Z.
+    pub const MAX: Self = Self {
+        millis: 253_402_300_799_999,
+    };
+
+    /// Parses `YYYY-MM-DDThh:mm:ss.sssZ`.
+    ///
+    /// The fraction may have one to nine digits, or be left out; digits after the
+    /// milliseconds are dropped. Only UTC, written `Z`, is accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ServerTimeError::InvalidFormat`] if `value` is not in this form, and
+    /// [`ServerTimeError::OutOfRange`] for a date or time that does not exist, such as
+    /// February 30th or a leap second.
+    pub fn parse(value: &str) -> Result<Self, ServerTimeError> {
+        let bytes = value.as_bytes();
+        if bytes.len() < 20
+            || bytes[4] != b'-'
+            || bytes[7] != b'-'
+            || bytes[10] != b'T'
+            || bytes[13] != b':'
+            || bytes[16] != b':'
+            || bytes[bytes.len() - 1] != b'Z'
+        {
+            return Err(ServerTimeError::InvalidFormat);
+        }
+
+        let year = number(&bytes[0..4])?;
+        let month = number(&bytes[5..7])?;
+        let day = number(&bytes[8..10])?;
+        let hour = number(&bytes[11..13])?;
+        let minute = number(&bytes[14..16])?;
+        let second = number(&bytes[17..19])?;
+
+        let millis = match &bytes[19..bytes.len() - 1] {
+            [] => 0,
+            [b'.', digits @ ..] if (1..=9).contains(&digits.len()) => {
+                number(digits)?;
+                let mut millis = 0;
+                for i in 0..3 {
+                    millis = millis * 10 + digits.get(i).map_or(0, |digit| digit - b'0') as u32;
+                }
+                millis
+            }
+            _ => return Err(ServerTimeError::InvalidFormat),
+        };
+
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(ServerTimeError::OutOfRange);
+        }
+
+        let days = days_from_civil(year as i64, month, day);
+        let seconds = (hour * 3600 + minute * 60 + second) as i64;
+
+        Ok(Self {
+            millis: days * MILLIS_PER_DAY + seconds * 1000 + millis as i64,
+        })
+    }
+
+    /// Creates a timestamp from milliseconds since the Unix epoch. Returns `None` outside
+    /// [`MIN`](Self::MIN) and [`MAX`](Self::MAX).
+    pub fn from_unix_millis(millis: i64) -> Option<Self> {
+        (Self::MIN.millis..=Self::MAX.millis)
+            .contains(&millis)
+            .then_some(Self { millis })
+    }
+
+    /// Returns the milliseconds since the Unix epoch, negative before 1970.
+    pub fn unix_millis(&self) -> i64 {
+        self.millis
+    }
+
+    /// Returns the year, month and day.
+    pub fn date(&self) -> (u16, u8, u8) {
+        let (year, month, day) = civil_from_days(self.millis.div_euclid(MILLIS_PER_DAY));
+        (year as u16, month as u8, day as u8)
+    }
+
+    /// Returns the hour, minute, second and millisecond.
+    pub fn time(&self) -> (u8, u8, u8, u16) {
+        let millis = self.millis.rem_euclid(MILLIS_PER_DAY);
+        let seconds = millis / 1000;
+
+        (
+            (seconds / 3600) as u8,
+            (seconds / 60 % 60) as u8,
+            (seconds % 60) as u8,
+            (millis % 1000) as u16,
+        )
+    }
+}
+
+impl FromStr for ServerTime {
+    type Err = ServerTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Writes `YYYY-MM-DDThh:mm:ss.sssZ`, the form required by the specification.
+impl Display for ServerTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let (year, month, day) = self.date();
+        let (hour, minute, second, millis) = self.time();
+
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}.{millis:03}Z"
+        )
+    }
+}
+
+/// Fails where the platform cannot represent the time, e.g. before 1601 on Windows.
+#[cfg(feature = "std")]
+impl TryFrom<ServerTime> for std::time::SystemTime {
+    type Error = ServerTimeError;
+
+    fn try_from(time: ServerTime) -> Result<Self, Self::Error> {
+        let offset = core::time::Duration::from_millis(time.millis.unsigned_abs());
+
+        if time.millis < 0 {
+            std::time::UNIX_EPOCH.checked_sub(offset)
+        } else {
+            std::time::UNIX_EPOCH.checked_add(offset)
+        }
+        .ok_or(ServerTimeError::OutOfRange)
+    }
+}
+
+/// Rounds down to the millisecond.
+#[cfg(feature = "std")]
+impl TryFrom<std::time::SystemTime> for ServerTime {
+    type Error = ServerTimeError;
+
+    fn try_from(time: std::time::SystemTime) -> Result<Self, Self::Error> {
+        let millis = match time.duration_since(std::time::UNIX_EPOCH) {
+            Ok(after) => i128::try_from(after.as_millis()).ok(),
+            Err(before) => {
+                let before = before.duration();
+                let partial = before.subsec_nanos() % 1_000_000 != 0;
+                i128::try_from(before.as_millis() + u128::from(partial))
+                    .ok()
+                    .map(|millis| -millis)
+            }
+        };
+
+        millis
+            .and_then(|millis| i64::try_from(millis).ok())
+            .and_then(Self::from_unix_millis)
+            .ok_or(ServerTimeError::OutOfRange)
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+impl From<ServerTime> for chrono::DateTime<chrono::Utc> {
+    fn from(time: ServerTime) -> Self {
+        chrono::DateTime::from_timestamp_millis(time.millis)
+            .expect("years 0 to 9999 are in chrono's range")
+    }
+}
+
+/// Rounds down to the millisecond.
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+impl TryFrom<chrono::DateTime<chrono::Utc>> for ServerTime {
+    type Error = ServerTimeError;
+
+    fn try_from(time: chrono::DateTime<chrono::Utc>) -> Result<Self, Self::Error> {
+        Self::from_unix_millis(time.timestamp_millis()).ok_or(ServerTimeError::OutOfRange)
+    }
+}
+
+#[cfg(feature = "time-rs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time-rs")))]
+impl From<ServerTime> for time_rs::OffsetDateTime {
+    fn from(time: ServerTime) -> Self {
+        time_rs::OffsetDateTime::from_unix_timestamp_nanos(i128::from(time.millis) * 1_000_000)
+            .expect("years 0 to 9999 are in time's range")
+    }
+}
+
+/// Rounds down to the millisecond.
+#[cfg(feature = "time-rs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time-rs")))]
+impl TryFrom<time_rs::OffsetDateTime> for ServerTime {
+    type Error = ServerTimeError;
+
+    fn try_from(time: time_rs::OffsetDateTime) -> Result<Self, Self::Error> {
+        i64::try_from(time.unix_timestamp_nanos().div_euclid(1_000_000))
+            .ok()
+            .and_then(Self::from_unix_millis)
+            .ok_or(ServerTimeError::OutOfRange)
+    }
+}
+
+fn number(digits: &[u8]) -> Result<u32, ServerTimeError> {
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return Err(ServerTimeError::InvalidFormat);
+    }
+
+    Ok(digits
+        .iter()
+        .fold(0, |n, digit| n * 10 + (digit - b'0') as u32))
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days to and from 1970-01-01 in the proleptic Gregorian calendar, after Howard Hinnant's
+// `days_from_civil` and `civil_from_days`.
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = ((153 * ((month + 9) % 12) + 2) / 5 + day - 1) as i64;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ServerTime;
+    use crate::error::ServerTimeError;
+
+    #[test]
+    fn parse() {
+        let time = ServerTime::parse("2024-02-29T23:59:59.999Z").unwrap();
+        assert_eq!((2024, 2, 29), time.date());
+        assert_eq!((23, 59, 59, 999), time.time());
+
+        assert_eq!(
+            ServerTime::UNIX_EPOCH,
+            "1970-01-01T00:00:00Z".parse().unwrap()
+        );
+        assert_eq!(
+            Some(ServerTime::from_unix_millis(1_500).unwrap()),
+            "1970-01-01T00:00:01.5Z".parse().ok()
+        );
+        assert_eq!(
+            ServerTime::parse("1970-01-01T00:00:01.500Z"),
+            ServerTime::parse("1970-01-01T00:00:01.500999999Z")
+        );
+
+        for value in [
+            "",
+            "2024-01-01 12:34:56.789Z",
+            "2024-01-01T12:34:56.789",
+            "2024-01-01T12:34:56.789+00:00",
+            "2024-01-01T12:34:56.Z",
+            "2024-01-01T12:34:56.1234567890Z",
+            "2024-1-01T12:34:56.789Z",
+            "+024-01-01T12:34:56.789Z",
+            "2024-01-01T12:34:5x.789Z",
+        ] {
+            assert_eq!(
+                Err(ServerTimeError::InvalidFormat),
+                ServerTime::parse(value),
+                "{value}"
+            );
+        }
+
+        for value in [
+            "2023-02-29T00:00:00.000Z",
+            "1900-02-29T00:00:00.000Z",
+            "2024-13-01T00:00:00.000Z",
+            "2024-04-31T00:00:00.000Z",
+            "2024-01-00T00:00:00.000Z",
+            "2024-01-01T24:00:00.000Z",
+            "2016-12-31T23:59:60.000Z",
+        ] {
+            assert_eq!(
+                Err(ServerTimeError::OutOfRange),
+                ServerTime::parse(value),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn display() {
+        for value in [
+            "0000-01-01T00:00:00.000Z",
+            "1969-12-31T23:59:59.999Z",
+            "2000-02-29T01:02:03.004Z",
+            "9999-12-31T23:59:59.999Z",
+        ] {
+            assert_eq!(value, ServerTime::parse(value).unwrap().to_string());
+        }
+
+        assert_eq!("0000-01-01T00:00:00.000Z", ServerTime::MIN.to_string());
+        assert_eq!("9999-12-31T23:59:59.999Z", ServerTime::MAX.to_string());
+    }
+
+    #[test]
+    fn unix_millis() {
+        assert_eq!(
+            -1,
+            ServerTime::parse("1969-12-31T23:59:59.999Z")
+                .unwrap()
+                .unix_millis()
+        );
+        assert_eq!(
+            None,
+            ServerTime::from_unix_millis(ServerTime::MAX.unix_millis() + 1)
+        );
+        assert_eq!(
+            None,
+            ServerTime::from_unix_millis(ServerTime::MIN.unix_millis() - 1)
+        );
+    }
+
+    #[test]
+    fn message() {
+        let msg = crate::parse("@time=2011-10-19T16:40:51.620Z :a!a@a PRIVMSG #c :hi").unwrap();
+        assert_eq!(
+            Some(1_319_042_451_620),
+            msg.server_time().map(|time| time.unix_millis())
+        );
+
+        assert_eq!(
+            None,
+            crate::parse("@time=yesterday PING").unwrap().server_time()
+        );
+        assert_eq!(None, crate::parse("PING").unwrap().server_time());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn system_time() {
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        let time = ServerTime::parse("2024-01-01T12:34:56.789Z").unwrap();
+        let system = SystemTime::try_from(time).unwrap();
+        assert_eq!(Ok(time), ServerTime::try_from(system));
+        assert_eq!(
+            Ok(time),
+            ServerTime::try_from(system + Duration::from_micros(999))
+        );
+
+        let before = UNIX_EPOCH - Duration::from_micros(1500);
+        assert_eq!(-2, ServerTime::try_from(before).unwrap().unix_millis());
+        assert_eq!(
+            Err(ServerTimeError::OutOfRange),
+            ServerTime::try_from(
+                SystemTime::try_from(ServerTime::MAX).unwrap() + Duration::from_millis(1)
+            )
+        );
+
+        // Year 0 is out of range on some platforms, but must not panic.
+        let min = SystemTime::try_from(ServerTime::MIN).and_then(ServerTime::try_from);
+        assert!(min == Ok(ServerTime::MIN) || min == Err(ServerTimeError::OutOfRange));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        use chrono::{DateTime, Utc};
+
+        let time = ServerTime::parse("2024-01-01T12:34:56.789Z").unwrap();
+        let chrono = DateTime::<Utc>::from(time);
+        assert_eq!(1_704_112_496_789, chrono.timestamp_millis());
+        assert_eq!(Ok(time), ServerTime::try_from(chrono));
+    }
+
+    #[cfg(feature = "time-rs")]
+    #[test]
+    fn time_rs() {
+        use time_rs::{OffsetDateTime, UtcOffset};
+
+        let time = ServerTime::parse("1960-01-01T12:34:56.789Z").unwrap();
+        let offset = OffsetDateTime::from(time);
+        assert_eq!(1960, offset.year());
+        assert_eq!(789, offset.millisecond());
+
+        let shifted = offset.to_offset(UtcOffset::from_hms(9, 0, 0).unwrap());
+        assert_eq!(Ok(time), ServerTime::try_from(shifted));
+    }
+}
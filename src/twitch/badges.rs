@@ -0,0 +1,121 @@
+use core::convert::Infallible;
+
+use crate::message::de::FromValue;
+
+/// A badge from the `badges` or `badge-info` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Badge<'a> {
+    pub name: &'a str,
+    /// Badge version. In `badge-info` this holds extra data, such as the number of
+    /// subscribed months.
+    pub version: &'a str,
+}
+
+/// Iterator over the `badges` or `badge-info` tag, e.g. `subscriber/12,premium/1`.
+///
+/// Entries without a `/` are skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::twitch::{Badge, Badges};
+///
+/// let badges = Badges::new("subscriber/12,premium/1");
+///
+/// assert_eq!(Some("12"), badges.version("subscriber"));
+/// assert!(badges.contains("premium"));
+///
+/// let all: Vec<Badge> = badges.collect();
+/// assert_eq!(Badge { name: "premium", version: "1" }, all[1]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Badges<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Badges<'a> {
+    pub fn new(value: &'a str) -> Self {
+        Self { rest: value }
+    }
+
+    /// Returns the version of the badge called `name`.
+    pub fn version(&self, name: &str) -> Option<&'a str> {
+        let mut badges = *self;
+        badges
+            .find(|badge| badge.name == name)
+            .map(|badge| badge.version)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.version(name).is_some()
+    }
+
+    /// Returns `true` if there are no badges left.
+    pub fn is_empty(&self) -> bool {
+        let mut badges = *self;
+        badges.next().is_none()
+    }
+}
+
+impl<'a> Iterator for Badges<'a> {
+    type Item = Badge<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.rest.is_empty() {
+            let (badge, rest) = self.rest.split_once(',').unwrap_or((self.rest, ""));
+            self.rest = rest;
+
+            if let Some((name, version)) = badge.split_once('/') {
+                return Some(Badge { name, version });
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> FromValue<'a> for Badges<'a> {
+    type Error = Infallible;
+
+    fn from_value(value: &'a str) -> Result<Self, Self::Error> {
+        Ok(Self::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Badge, Badges};
+
+    #[test]
+    fn iterate() {
+        let badges: Vec<_> = Badges::new("broadcaster/1,subscriber/0,invalid,bits/100").collect();
+
+        assert_eq!(
+            vec![
+                Badge {
+                    name: "broadcaster",
+                    version: "1"
+                },
+                Badge {
+                    name: "subscriber",
+                    version: "0"
+                },
+                Badge {
+                    name: "bits",
+                    version: "100"
+                },
+            ],
+            badges
+        );
+    }
+
+    #[test]
+    fn lookup() {
+        let badges = Badges::new("subscriber/24,bits/1000");
+
+        assert_eq!(Some("1000"), badges.version("bits"));
+        assert_eq!(None, badges.version("moderator"));
+        assert!(Badges::new("").is_empty());
+        assert!(!badges.is_empty());
+    }
+}
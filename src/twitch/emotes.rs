@@ -0,0 +1,217 @@
+use core::{convert::Infallible, ops::Range};
+
+use crate::message::de::FromValue;
+
+/// A single emote occurrence from the `emotes` tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Emote<'a> {
+    pub id: &'a str,
+    /// Position in the message text, counted in chars. The end is exclusive.
+    pub range: Range<usize>,
+}
+
+impl Emote<'_> {
+    /// Returns the emote text from `message`.
+    ///
+    /// Returns `None` if the range is outside of `message`.
+    pub fn text<'m>(&self, message: &'m str) -> Option<&'m str> {
+        slice_chars(message, self.range.clone())
+    }
+}
+
+/// Iterator over the `emotes` tag, e.g. `25:0-4,12-16/1902:6-10`.
+///
+/// Yields one [`Emote`] per occurrence. Malformed entries are skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::twitch::EmoteRanges;
+///
+/// let message = "Kappa ¡Hola! Kappa";
+/// let mut emotes = EmoteRanges::new("25:0-4,13-17");
+///
+/// let kappa = emotes.next().unwrap();
+/// assert_eq!("25", kappa.id);
+/// assert_eq!(0..5, kappa.range);
+/// assert_eq!(Some("Kappa"), kappa.text(message));
+///
+/// // Ranges count chars, so `¡` does not shift the second occurrence.
+/// assert_eq!(Some("Kappa"), emotes.next().unwrap().text(message));
+/// assert!(emotes.next().is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmoteRanges<'a> {
+    rest: &'a str,
+    id: &'a str,
+    ranges: &'a str,
+}
+
+impl<'a> EmoteRanges<'a> {
+    pub fn new(value: &'a str) -> Self {
+        Self {
+            rest: value,
+            id: "",
+            ranges: "",
+        }
+    }
+
+    /// Returns `true` if there are no emotes left.
+    pub fn is_empty(&self) -> bool {
+        self.clone().next().is_none()
+    }
+}
+
+impl<'a> Iterator for EmoteRanges<'a> {
+    type Item = Emote<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if !self.ranges.is_empty() {
+                let (range, rest) = self.ranges.split_once(',').unwrap_or((self.ranges, ""));
+                self.ranges = rest;
+
+                if let Some(range) = parse_range(range) {
+                    return Some(Emote { id: self.id, range });
+                }
+                continue;
+            }
+
+            if self.rest.is_empty() {
+                return None;
+            }
+
+            let (emote, rest) = self.rest.split_once('/').unwrap_or((self.rest, ""));
+            self.rest = rest;
+
+            if let Some((id, ranges)) = emote.split_once(':') {
+                self.id = id;
+                self.ranges = ranges;
+            }
+        }
+    }
+}
+
+impl<'a> FromValue<'a> for EmoteRanges<'a> {
+    type Error = Infallible;
+
+    fn from_value(value: &'a str) -> Result<Self, Self::Error> {
+        Ok(Self::new(value))
+    }
+}
+
+/// Parses an inclusive `start-end` range into an exclusive one.
+fn parse_range(value: &str) -> Option<Range<usize>> {
+    let (start, end) = value.split_once('-')?;
+    let start: usize = start.parse().ok()?;
+    let end: usize = end.parse().ok()?;
+
+    if end < start {
+        return None;
+    }
+
+    end.checked_add(1).map(|end| start..end)
+}
+
+/// Slices `text` by a range counted in chars instead of bytes.
+///
+/// Returns `None` if the range is outside of `text`.
+///
+/// # Examples
+///
+/// ```rust
+/// use ircv3_parse::twitch::slice_chars;
+///
+/// assert_eq!(Some("ño"), slice_chars("señor", 2..4));
+/// assert_eq!(None, slice_chars("señor", 3..9));
+/// ```
+pub fn slice_chars(text: &str, range: Range<usize>) -> Option<&str> {
+    if range.start > range.end {
+        return None;
+    }
+
+    let mut indices = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(core::iter::once(text.len()));
+
+    let start = indices.nth(range.start)?;
+    let end = if range.end == range.start {
+        start
+    } else {
+        indices.nth(range.end - range.start - 1)?
+    };
+
+    Some(&text[start..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{slice_chars, Emote, EmoteRanges};
+
+    #[test]
+    fn multiple_emotes() {
+        let emotes: Vec<_> = EmoteRanges::new("25:0-4,12-16/1902:6-10").collect();
+
+        assert_eq!(
+            vec![
+                Emote {
+                    id: "25",
+                    range: 0..5
+                },
+                Emote {
+                    id: "25",
+                    range: 12..17
+                },
+                Emote {
+                    id: "1902",
+                    range: 6..11
+                },
+            ],
+            emotes
+        );
+    }
+
+    #[test]
+    fn multibyte_text() {
+        let message = "😀 Kappa Keepo";
+        let emotes: Vec<_> = EmoteRanges::new("25:2-6/1902:8-12")
+            .map(|e| e.text(message).unwrap())
+            .collect();
+
+        assert_eq!(vec!["Kappa", "Keepo"], emotes);
+    }
+
+    #[test]
+    fn malformed_entries_skipped() {
+        let emotes: Vec<_> = EmoteRanges::new("bad/25:x-1,0-4,5-2/:/1902:6-10").collect();
+
+        assert_eq!(2, emotes.len());
+        assert_eq!("25", emotes[0].id);
+        assert_eq!("1902", emotes[1].id);
+    }
+
+    #[test]
+    fn overflowing_range_skipped() {
+        let max = usize::MAX;
+        let value = format!("25:0-{max}/1902:{max}-{max},6-10");
+        let emotes: Vec<_> = EmoteRanges::new(&value).collect();
+
+        assert_eq!(1, emotes.len());
+        assert_eq!(6..11, emotes[0].range);
+    }
+
+    #[test]
+    fn empty() {
+        assert!(EmoteRanges::new("").is_empty());
+        assert!(!EmoteRanges::new("25:0-4").is_empty());
+    }
+
+    #[test]
+    fn slice_chars_bounds() {
+        assert_eq!(Some("abc"), slice_chars("abc", 0..3));
+        assert_eq!(Some(""), slice_chars("abc", 3..3));
+        assert_eq!(None, slice_chars("abc", 2..4));
+        assert_eq!(None, slice_chars("abc", 4..4));
+    }
+}
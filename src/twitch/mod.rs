@@ -0,0 +1,522 @@
+//! Typed messages for the Twitch IRC interface.
+//!
+//! Each type implements [`FromMessage`] and reads the tags documented at
+//! <https://dev.twitch.tv/docs/chat/irc/>. Tag values are borrowed from the input as is,
+//! except for `system-msg`, which is unescaped.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ircv3_parse::twitch::PrivMsg;
+//!
+//! let input = "@badges=moderator/1;color=#1E90FF;display-name=Ronni;id=b34ccfc7;mod=1;\
+//!     room-id=1337;subscriber=0;tmi-sent-ts=1507246572675;user-id=1337 \
+//!     :ronni!ronni@ronni.tmi.twitch.tv PRIVMSG #ronni :Kappa Keepo";
+//!
+//! let msg: PrivMsg = ircv3_parse::from_str(input)?;
+//!
+//! assert_eq!("#ronni", msg.channel);
+//! assert_eq!("ronni", msg.login);
+//! assert_eq!(Some("Ronni"), msg.display_name);
+//! assert!(msg.moderator);
+//! assert!(!msg.subscriber);
+//! assert_eq!(Some(1507246572675), msg.tmi_sent_ts);
+//! assert_eq!("Kappa Keepo", msg.message);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+mod badges;
+mod emotes;
+
+pub use badges::{Badge, Badges};
+pub use emotes::{slice_chars, Emote, EmoteRanges};
+
+use core::str::FromStr;
+
+use crate::compat::{format, String};
+
+use crate::{components::Tags, message::de::FromMessage, Commands, DeError, Message};
+
+/// A chat message sent to a channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivMsg<'a> {
+    pub channel: &'a str,
+    pub message: &'a str,
+    /// Login name of the sender, taken from the source.
+    pub login: &'a str,
+    pub id: Option<&'a str>,
+    pub user_id: Option<&'a str>,
+    pub display_name: Option<&'a str>,
+    pub color: Option<&'a str>,
+    pub badges: Badges<'a>,
+    pub badge_info: Badges<'a>,
+    pub emotes: EmoteRanges<'a>,
+    pub room_id: Option<&'a str>,
+    /// Number of bits cheered with this message.
+    pub bits: Option<u32>,
+    pub moderator: bool,
+    pub subscriber: bool,
+    pub vip: bool,
+    pub first_msg: bool,
+    pub reply_parent_msg_id: Option<&'a str>,
+    /// Server timestamp in Unix milliseconds.
+    pub tmi_sent_ts: Option<u64>,
+}
+
+impl<'a> FromMessage<'a> for PrivMsg<'a> {
+    fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+        expect_command(msg, Commands::PRIVMSG)?;
+        let tags = msg.tags();
+        let params = msg.params();
+
+        Ok(Self {
+            channel: channel(msg)?,
+            message: params.trailing.as_str(),
+            login: msg.source().ok_or(DeError::missing_source())?.name,
+            id: tag(&tags, "id"),
+            user_id: tag(&tags, "user-id"),
+            display_name: tag(&tags, "display-name"),
+            color: tag(&tags, "color"),
+            badges: Badges::new(tag(&tags, "badges").unwrap_or_default()),
+            badge_info: Badges::new(tag(&tags, "badge-info").unwrap_or_default()),
+            emotes: EmoteRanges::new(tag(&tags, "emotes").unwrap_or_default()),
+            room_id: tag(&tags, "room-id"),
+            bits: parse_tag(&tags, "bits", "bits")?,
+            moderator: bool_tag(&tags, "mod"),
+            subscriber: bool_tag(&tags, "subscriber"),
+            vip: tags.is_some_and(|t| t.contains("vip")),
+            first_msg: bool_tag(&tags, "first-msg"),
+            reply_parent_msg_id: tag(&tags, "reply-parent-msg-id"),
+            tmi_sent_ts: parse_tag(&tags, "tmi_sent_ts", "tmi-sent-ts")?,
+        })
+    }
+}
+
+/// A subscription, raid, announcement or other channel event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserNotice<'a> {
+    pub channel: &'a str,
+    /// Optional message attached by the user.
+    pub message: Option<&'a str>,
+    /// Event type, e.g. `sub`, `resub`, `raid` or `announcement`.
+    pub msg_id: &'a str,
+    pub login: Option<&'a str>,
+    pub id: Option<&'a str>,
+    pub user_id: Option<&'a str>,
+    pub display_name: Option<&'a str>,
+    pub color: Option<&'a str>,
+    pub badges: Badges<'a>,
+    pub badge_info: Badges<'a>,
+    pub emotes: EmoteRanges<'a>,
+    pub room_id: Option<&'a str>,
+    /// Unescaped message shown by Twitch for the event.
+    pub system_msg: Option<String>,
+    pub moderator: bool,
+    pub subscriber: bool,
+    /// Server timestamp in Unix milliseconds.
+    pub tmi_sent_ts: Option<u64>,
+}
+
+impl<'a> FromMessage<'a> for UserNotice<'a> {
+    fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+        expect_command(msg, Commands::USERNOTICE)?;
+        let tags = msg.tags();
+
+        Ok(Self {
+            channel: channel(msg)?,
+            message: msg.params().trailing.raw(),
+            msg_id: tag(&tags, "msg-id").ok_or(DeError::missing_tag("msg_id", "msg-id"))?,
+            login: tag(&tags, "login"),
+            id: tag(&tags, "id"),
+            user_id: tag(&tags, "user-id"),
+            display_name: tag(&tags, "display-name"),
+            color: tag(&tags, "color"),
+            badges: Badges::new(tag(&tags, "badges").unwrap_or_default()),
+            badge_info: Badges::new(tag(&tags, "badge-info").unwrap_or_default()),
+            emotes: EmoteRanges::new(tag(&tags, "emotes").unwrap_or_default()),
+            room_id: tag(&tags, "room-id"),
+            system_msg: tags.and_then(|t| t.get_unescaped("system-msg")),
+            moderator: bool_tag(&tags, "mod"),
+            subscriber: bool_tag(&tags, "subscriber"),
+            tmi_sent_ts: parse_tag(&tags, "tmi_sent_ts", "tmi-sent-ts")?,
+        })
+    }
+}
+
+/// Channel chat settings.
+///
+/// Twitch sends every setting after joining and only the changed ones afterwards, so each
+/// setting is `None` when absent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoomState<'a> {
+    pub channel: &'a str,
+    pub room_id: Option<&'a str>,
+    pub emote_only: Option<bool>,
+    /// Minutes a user must follow before chatting, `-1` when disabled.
+    pub followers_only: Option<i32>,
+    pub r9k: Option<bool>,
+    /// Seconds a user must wait between messages.
+    pub slow: Option<u32>,
+    pub subs_only: Option<bool>,
+}
+
+impl<'a> FromMessage<'a> for RoomState<'a> {
+    fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+        expect_command(msg, Commands::ROOMSTATE)?;
+        let tags = msg.tags();
+
+        Ok(Self {
+            channel: channel(msg)?,
+            room_id: tag(&tags, "room-id"),
+            emote_only: tag(&tags, "emote-only").map(|v| v == "1"),
+            followers_only: parse_tag(&tags, "followers_only", "followers-only")?,
+            r9k: tag(&tags, "r9k").map(|v| v == "1"),
+            slow: parse_tag(&tags, "slow", "slow")?,
+            subs_only: tag(&tags, "subs-only").map(|v| v == "1"),
+        })
+    }
+}
+
+/// The bot's own state in a channel, sent after joining and after each message it sends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserState<'a> {
+    pub channel: &'a str,
+    pub id: Option<&'a str>,
+    pub display_name: Option<&'a str>,
+    pub color: Option<&'a str>,
+    pub badges: Badges<'a>,
+    pub badge_info: Badges<'a>,
+    pub emote_sets: Option<&'a str>,
+    pub moderator: bool,
+    pub subscriber: bool,
+}
+
+impl<'a> FromMessage<'a> for UserState<'a> {
+    fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+        expect_command(msg, Commands::USERSTATE)?;
+        let tags = msg.tags();
+
+        Ok(Self {
+            channel: channel(msg)?,
+            id: tag(&tags, "id"),
+            display_name: tag(&tags, "display-name"),
+            color: tag(&tags, "color"),
+            badges: Badges::new(tag(&tags, "badges").unwrap_or_default()),
+            badge_info: Badges::new(tag(&tags, "badge-info").unwrap_or_default()),
+            emote_sets: tag(&tags, "emote-sets"),
+            moderator: bool_tag(&tags, "mod"),
+            subscriber: bool_tag(&tags, "subscriber"),
+        })
+    }
+}
+
+/// The bot's global state, sent after authenticating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalUserState<'a> {
+    pub user_id: Option<&'a str>,
+    pub display_name: Option<&'a str>,
+    pub color: Option<&'a str>,
+    pub badges: Badges<'a>,
+    pub badge_info: Badges<'a>,
+    pub emote_sets: Option<&'a str>,
+}
+
+impl<'a> FromMessage<'a> for GlobalUserState<'a> {
+    fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+        expect_command(msg, Commands::GLOBALUSERSTATE)?;
+        let tags = msg.tags();
+
+        Ok(Self {
+            user_id: tag(&tags, "user-id"),
+            display_name: tag(&tags, "display-name"),
+            color: tag(&tags, "color"),
+            badges: Badges::new(tag(&tags, "badges").unwrap_or_default()),
+            badge_info: Badges::new(tag(&tags, "badge-info").unwrap_or_default()),
+            emote_sets: tag(&tags, "emote-sets"),
+        })
+    }
+}
+
+/// All messages in a channel, or all messages from one user, were removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearChat<'a> {
+    pub channel: &'a str,
+    /// Login of the user whose messages were removed, or `None` if the chat was cleared.
+    pub user: Option<&'a str>,
+    /// Timeout in seconds, or `None` for a permanent ban.
+    pub ban_duration: Option<u32>,
+    pub room_id: Option<&'a str>,
+    pub target_user_id: Option<&'a str>,
+    /// Server timestamp in Unix milliseconds.
+    pub tmi_sent_ts: Option<u64>,
+}
+
+impl<'a> FromMessage<'a> for ClearChat<'a> {
+    fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+        expect_command(msg, Commands::CLEARCHAT)?;
+        let tags = msg.tags();
+
+        Ok(Self {
+            channel: channel(msg)?,
+            user: msg.params().trailing.raw(),
+            ban_duration: parse_tag(&tags, "ban_duration", "ban-duration")?,
+            room_id: tag(&tags, "room-id"),
+            target_user_id: tag(&tags, "target-user-id"),
+            tmi_sent_ts: parse_tag(&tags, "tmi_sent_ts", "tmi-sent-ts")?,
+        })
+    }
+}
+
+/// A single message was removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearMsg<'a> {
+    pub channel: &'a str,
+    pub message: &'a str,
+    pub login: Option<&'a str>,
+    pub target_msg_id: &'a str,
+    /// Server timestamp in Unix milliseconds.
+    pub tmi_sent_ts: Option<u64>,
+}
+
+impl<'a> FromMessage<'a> for ClearMsg<'a> {
+    fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+        expect_command(msg, Commands::CLEARMSG)?;
+        let tags = msg.tags();
+
+        Ok(Self {
+            channel: channel(msg)?,
+            message: msg.params().trailing.as_str(),
+            login: tag(&tags, "login"),
+            target_msg_id: tag(&tags, "target-msg-id")
+                .ok_or(DeError::missing_tag("target_msg_id", "target-msg-id"))?,
+            tmi_sent_ts: parse_tag(&tags, "tmi_sent_ts", "tmi-sent-ts")?,
+        })
+    }
+}
+
+/// A private message between two users.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Whisper<'a> {
+    /// Login name of the sender, taken from the source.
+    pub from: &'a str,
+    /// Login name of the recipient.
+    pub to: &'a str,
+    pub message: &'a str,
+    pub message_id: Option<&'a str>,
+    pub thread_id: Option<&'a str>,
+    pub user_id: Option<&'a str>,
+    pub display_name: Option<&'a str>,
+    pub color: Option<&'a str>,
+    pub badges: Badges<'a>,
+    pub emotes: EmoteRanges<'a>,
+}
+
+impl<'a> FromMessage<'a> for Whisper<'a> {
+    fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+        expect_command(msg, Commands::WHISPER)?;
+        let tags = msg.tags();
+        let params = msg.params();
+
+        Ok(Self {
+            from: msg.source().ok_or(DeError::missing_source())?.name,
+            to: params
+                .middles
+                .first()
+                .ok_or(DeError::missing_param_field("to", 0))?,
+            message: params.trailing.as_str(),
+            message_id: tag(&tags, "message-id"),
+            thread_id: tag(&tags, "thread-id"),
+            user_id: tag(&tags, "user-id"),
+            display_name: tag(&tags, "display-name"),
+            color: tag(&tags, "color"),
+            badges: Badges::new(tag(&tags, "badges").unwrap_or_default()),
+            emotes: EmoteRanges::new(tag(&tags, "emotes").unwrap_or_default()),
+        })
+    }
+}
+
+fn expect_command(msg: &Message<'_>, expected: Commands<'_>) -> Result<(), DeError> {
+    let command = msg.command();
+    if command != expected {
+        return Err(DeError::invalid_command(
+            expected.as_str(),
+            command.as_str(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn channel<'a>(msg: &Message<'a>) -> Result<&'a str, DeError> {
+    msg.params()
+        .middles
+        .first()
+        .ok_or(DeError::missing_param_field("channel", 0))
+}
+
+/// Returns a tag value, treating an empty value as absent.
+fn tag<'a>(tags: &Option<Tags<'a>>, key: &str) -> Option<&'a str> {
+    tags.and_then(|t| t.get(key))
+        .map(|v| v.as_str())
+        .filter(|v| !v.is_empty())
+}
+
+fn bool_tag(tags: &Option<Tags<'_>>, key: &str) -> bool {
+    tag(tags, key) == Some("1")
+}
+
+fn parse_tag<T>(tags: &Option<Tags<'_>>, field: &str, key: &str) -> Result<Option<T>, DeError>
+where
+    T: FromStr,
+    T::Err: core::fmt::Display,
+{
+    tag(tags, key)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|e| DeError::invalid_value(field, format!("cannot parse `{value}`: {e}")))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClearChat, ClearMsg, GlobalUserState, PrivMsg, RoomState, UserNotice, UserState, Whisper,
+    };
+    use crate::message::de::FromMessage;
+
+    #[test]
+    fn privmsg() {
+        let msg = PrivMsg::from_str(
+            "@badge-info=;badges=vip/1,bits/100;bits=100;color=;display-name=Cheer;\
+             emotes=;first-msg=1;id=abc;mod=0;subscriber=1;user-id=42;vip=1 \
+             :cheer!cheer@cheer.tmi.twitch.tv PRIVMSG #channel :cheer100 hi",
+        )
+        .unwrap();
+
+        assert_eq!("#channel", msg.channel);
+        assert_eq!("cheer100 hi", msg.message);
+        assert_eq!(Some(100), msg.bits);
+        assert_eq!(None, msg.color);
+        assert!(msg.badge_info.is_empty());
+        assert!(msg.emotes.is_empty());
+        assert_eq!(Some("100"), msg.badges.version("bits"));
+        assert!(msg.vip);
+        assert!(msg.first_msg);
+        assert!(msg.subscriber);
+        assert!(!msg.moderator);
+    }
+
+    #[test]
+    fn privmsg_invalid_bits() {
+        let err = PrivMsg::from_str("@bits=lots :a!a@a PRIVMSG #channel :hi").unwrap_err();
+        assert_eq!("EXTRACT[INVALID_VALUE]: invalid field value for field 'bits': cannot parse `lots`: invalid digit found in string", format!("{err:?}"));
+    }
+
+    #[test]
+    fn wrong_command() {
+        assert!(PrivMsg::from_str(":a!a@a NOTICE #channel :hi")
+            .unwrap_err()
+            .is_invalid_command());
+    }
+
+    #[test]
+    fn usernotice() {
+        let msg = UserNotice::from_str(
+            "@badges=;login=ronni;msg-id=resub;mod=0;subscriber=1;\
+             system-msg=ronni\\shas\\ssubscribed\\sfor\\s6\\smonths!;tmi-sent-ts=1507246572675 \
+             :tmi.twitch.tv USERNOTICE #dallas :Great stream",
+        )
+        .unwrap();
+
+        assert_eq!("resub", msg.msg_id);
+        assert_eq!(Some("ronni"), msg.login);
+        assert_eq!(Some("Great stream"), msg.message);
+        assert_eq!(
+            Some("ronni has subscribed for 6 months!"),
+            msg.system_msg.as_deref()
+        );
+
+        let msg = UserNotice::from_str("@msg-id=raid :tmi.twitch.tv USERNOTICE #dallas").unwrap();
+        assert_eq!(None, msg.message);
+
+        assert!(UserNotice::from_str(":tmi.twitch.tv USERNOTICE #dallas")
+            .unwrap_err()
+            .is_missing_tag());
+    }
+
+    #[test]
+    fn roomstate() {
+        let msg = RoomState::from_str(
+            "@emote-only=0;followers-only=-1;r9k=0;room-id=12345678;slow=0;subs-only=0 \
+             :tmi.twitch.tv ROOMSTATE #bar",
+        )
+        .unwrap();
+
+        assert_eq!("#bar", msg.channel);
+        assert_eq!(Some(false), msg.emote_only);
+        assert_eq!(Some(-1), msg.followers_only);
+        assert_eq!(Some(0), msg.slow);
+
+        let msg = RoomState::from_str("@room-id=1;slow=10 :tmi.twitch.tv ROOMSTATE #bar").unwrap();
+        assert_eq!(Some(10), msg.slow);
+        assert_eq!(None, msg.subs_only);
+    }
+
+    #[test]
+    fn userstate() {
+        let msg = UserState::from_str(
+            "@badges=moderator/1;color=#0D4200;display-name=Bot;emote-sets=0,33,50;mod=1;subscriber=0 \
+             :tmi.twitch.tv USERSTATE #dallas",
+        )
+        .unwrap();
+
+        assert_eq!("#dallas", msg.channel);
+        assert_eq!(Some("0,33,50"), msg.emote_sets);
+        assert!(msg.moderator);
+
+        let msg = GlobalUserState::from_str(
+            "@display-name=Bot;user-id=12345 :tmi.twitch.tv GLOBALUSERSTATE",
+        )
+        .unwrap();
+        assert_eq!(Some("12345"), msg.user_id);
+    }
+
+    #[test]
+    fn clearchat() {
+        let msg = ClearChat::from_str(
+            "@ban-duration=350;room-id=1;target-user-id=2;tmi-sent-ts=1642719320727 \
+             :tmi.twitch.tv CLEARCHAT #dallas :ronni",
+        )
+        .unwrap();
+        assert_eq!(Some("ronni"), msg.user);
+        assert_eq!(Some(350), msg.ban_duration);
+
+        let msg = ClearChat::from_str(":tmi.twitch.tv CLEARCHAT #dallas").unwrap();
+        assert_eq!(None, msg.user);
+        assert_eq!(None, msg.ban_duration);
+    }
+
+    #[test]
+    fn clearmsg() {
+        let msg = ClearMsg::from_str(
+            "@login=foo;target-msg-id=94e6c7ff :tmi.twitch.tv CLEARMSG #bar :what a great day",
+        )
+        .unwrap();
+        assert_eq!("94e6c7ff", msg.target_msg_id);
+        assert_eq!("what a great day", msg.message);
+    }
+
+    #[test]
+    fn whisper() {
+        let msg = Whisper::from_str(
+            "@display-name=PetsgomOO;message-id=306;thread-id=12345678_87654321 \
+             :petsgomoo!petsgomoo@petsgomoo.tmi.twitch.tv WHISPER foo :hello",
+        )
+        .unwrap();
+        assert_eq!("petsgomoo", msg.from);
+        assert_eq!("foo", msg.to);
+        assert_eq!("hello", msg.message);
+        assert_eq!(Some("306"), msg.message_id);
+    }
+}
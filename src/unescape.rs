@@ -2,6 +2,8 @@ use crate::compat::{String, Vec};
 
 use crate::{COLON, CR, LF, SEMICOLON, SPACE};
 
+const BACKSLASH: u8 = b'\\';
+
 /// Unescapes an IRCv3 tag value according to the specification.
 ///
 /// The following sequences are unescaped:
@@ -21,8 +23,6 @@ use crate::{COLON, CR, LF, SEMICOLON, SPACE};
 /// assert_eq!(unescape("back\\\\slash"), "back\\slash");
 /// ```
 pub fn unescape(value: &str) -> String {
-    const BACKSLASH: u8 = b'\\';
-
     let bytes = value.as_bytes();
     let mut result = Vec::with_capacity(value.len());
     let mut i = 0;
@@ -67,3 +67,65 @@ pub fn unescape(value: &str) -> String {
 
     String::from_utf8(result).expect("Invalid UTF-8 in result")
 }
+
+/// Escapes an IRCv3 tag value according to the specification.
+///
+/// This is the inverse of [`unescape`]:
+/// - `;` → `\:`
+/// - ` ` → `\s`
+/// - `\` → `\\`
+/// - CR → `\r`
+/// - LF → `\n`
+///
+/// # Examples
+///
+/// ```
+/// use ircv3_parse::{escape, unescape};
+///
+/// assert_eq!(escape("hello world"), "hello\\sworld");
+/// assert_eq!(escape("semi;colon"), "semi\\:colon");
+/// assert_eq!(unescape(&escape("a;b c\\d\r\n")), "a;b c\\d\r\n");
+/// ```
+pub fn escape(value: &str) -> String {
+    let mut result = Vec::with_capacity(escaped_len(value));
+    escape_chunks(value, |chunk| result.extend_from_slice(chunk));
+
+    String::from_utf8(result).expect("Invalid UTF-8 in result")
+}
+
+/// Returns the length of `value` after [`escape`].
+#[inline]
+pub(crate) fn escaped_len(value: &str) -> usize {
+    value.len()
+        + value
+            .bytes()
+            .filter(|b| matches!(*b, SEMICOLON | SPACE | BACKSLASH | CR | LF))
+            .count()
+}
+
+/// Calls `f` with consecutive pieces of the escaped value, without allocating.
+pub(crate) fn escape_chunks(value: &str, mut f: impl FnMut(&[u8])) {
+    let bytes = value.as_bytes();
+    let mut start = 0;
+
+    for (i, &b) in bytes.iter().enumerate() {
+        let escaped: &[u8] = match b {
+            SEMICOLON => b"\\:",
+            SPACE => b"\\s",
+            BACKSLASH => b"\\\\",
+            CR => b"\\r",
+            LF => b"\\n",
+            _ => continue,
+        };
+
+        if start < i {
+            f(&bytes[start..i]);
+        }
+        f(escaped);
+        start = i + 1;
+    }
+
+    if start < bytes.len() {
+        f(&bytes[start..]);
+    }
+}
@@ -268,6 +268,23 @@ pub fn tag_value(input: &str) -> Result<(), TagError> {
     Ok(())
 }
 
+/// Validates an IRC tag value that will be escaped before serialization.
+///
+/// # Rules
+///
+/// - May be empty (for tags like `key=`)
+/// - Must not contain NUL, which has no escape sequence
+#[inline]
+pub fn unescaped_tag_value(input: &str) -> Result<(), TagError> {
+    match input.bytes().position(|c| c == NUL) {
+        Some(position) => Err(TagError::InvalidValueChar {
+            char: NUL as char,
+            position,
+        }),
+        None => Ok(()),
+    }
+}
+
 /// Validates a single IRC parameter (middle parameter, not trailing).
 ///
 /// # Rules
@@ -0,0 +1,81 @@
+#[cfg(feature = "tokio")]
+use ircv3_parse::{codec::IrcCodec, message::de::FromMessage, DeError, Message};
+
+#[cfg(feature = "tokio")]
+#[derive(Debug, PartialEq)]
+struct Line {
+    nick: Option<String>,
+    command: String,
+    middles: Vec<String>,
+    trailing: Option<String>,
+}
+
+#[cfg(feature = "tokio")]
+impl<'a> FromMessage<'a> for Line {
+    fn from_message(msg: &Message<'a>) -> Result<Self, DeError> {
+        let params = msg.params();
+        Ok(Self {
+            nick: msg.source().map(|s| s.name.to_string()),
+            command: msg.command().to_string(),
+            middles: params.middles.iter().map(|s| s.to_string()).collect(),
+            trailing: params.trailing.raw().map(|s| s.to_string()),
+        })
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn framed_read() {
+    use futures_util::StreamExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio_util::codec::FramedRead;
+
+    let (mut server, client) = tokio::io::duplex(64);
+    let mut reader = FramedRead::new(client, IrcCodec::<Line>::new());
+
+    tokio::spawn(async move {
+        server.write_all(b":irc.example.com PING ").await.unwrap();
+        server
+            .write_all(b":token\r\n:nick!user@host PRIV")
+            .await
+            .unwrap();
+        server.write_all(b"MSG #channel :hi\n").await.unwrap();
+    });
+
+    let ping = reader.next().await.unwrap().unwrap();
+    assert_eq!(Some("irc.example.com".to_string()), ping.nick);
+    assert_eq!("PING", ping.command);
+    assert_eq!(Some("token".to_string()), ping.trailing);
+
+    let privmsg = reader.next().await.unwrap().unwrap();
+    assert_eq!(Some("nick".to_string()), privmsg.nick);
+    assert_eq!(vec!["#channel".to_string()], privmsg.middles);
+    assert_eq!(Some("hi".to_string()), privmsg.trailing);
+
+    assert!(reader.next().await.is_none());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn framed_write() {
+    use futures_util::SinkExt;
+    use ircv3_parse::{Commands, MessageBuilder};
+    use tokio::io::AsyncReadExt;
+    use tokio_util::codec::FramedWrite;
+
+    let (server, mut client) = tokio::io::duplex(256);
+    let mut writer = FramedWrite::new(server, IrcCodec::<Line>::new());
+
+    let mut nick = MessageBuilder::new(Commands::NICK);
+    nick.add_param("nick").unwrap();
+    writer.send(nick).await.unwrap();
+
+    let mut join = MessageBuilder::new(Commands::JOIN);
+    join.add_param("#channel").unwrap();
+    writer.send(&join).await.unwrap();
+    drop(writer);
+
+    let mut output = String::new();
+    client.read_to_string(&mut output).await.unwrap();
+    assert_eq!("NICK nick\r\nJOIN #channel\r\n", output);
+}
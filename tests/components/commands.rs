@@ -43,6 +43,29 @@ prop_compose! {
             Just("LINKS".to_string()),
             Just("USERHOST".to_string()),
             Just("WALLOPS".to_string()),
+            Just("TAGMSG".to_string()),
+            Just("BATCH".to_string()),
+            Just("ACCOUNT".to_string()),
+            Just("CHGHOST".to_string()),
+            Just("SETNAME".to_string()),
+            Just("CHATHISTORY".to_string()),
+            Just("MONITOR".to_string()),
+            Just("FAIL".to_string()),
+            Just("WARN".to_string()),
+            Just("NOTE".to_string()),
+            Just("MARKREAD".to_string()),
+            Just("REDACT".to_string()),
+            Just("WEBIRC".to_string()),
+            Just("RENAME".to_string()),
+            Just("CLEARCHAT".to_string()),
+            Just("CLEARMSG".to_string()),
+            Just("GLOBALUSERSTATE".to_string()),
+            Just("USERSTATE".to_string()),
+            Just("ROOMSTATE".to_string()),
+            Just("USERNOTICE".to_string()),
+            Just("WHISPER".to_string()),
+            Just("HOSTTARGET".to_string()),
+            Just("RECONNECT".to_string()),
             (000u16..=999u16).prop_map(|n| format!("{:03}", n)),
             "[a-zA-Z]{1,10}",
     ]) -> String { cmd }
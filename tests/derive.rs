@@ -38,3 +38,64 @@ fn roundtip() {
     assert_eq!(msg.command, de.command);
     assert_eq!(msg.message, de.message);
 }
+
+#[cfg(all(feature = "derive", feature = "twitch"))]
+#[test]
+fn twitch_typed_tags() {
+    use ircv3_parse::twitch::{Badges, EmoteRanges};
+    use ircv3_parse::FromMessage;
+
+    #[derive(FromMessage)]
+    #[irc(command = "PRIVMSG")]
+    struct Chat<'a> {
+        #[irc(tag = "emotes")]
+        emotes: EmoteRanges<'a>,
+        #[irc(tag = "badges")]
+        badges: Option<Badges<'a>>,
+        #[irc(tag = "badge-info")]
+        badge_info: Option<Badges<'a>>,
+        #[irc(trailing)]
+        message: &'a str,
+    }
+
+    let input = "@badges=subscriber/12,premium/1;emotes=25:0-4/1902:9-13 \
+                 :ronni!ronni@ronni.tmi.twitch.tv PRIVMSG #ronni :Kappa ¡! Keepo";
+    let msg: Chat = ircv3_parse::from_str(input).unwrap();
+
+    let emotes: Vec<_> = msg
+        .emotes
+        .map(|emote| emote.text(msg.message).unwrap())
+        .collect();
+    assert_eq!(vec!["Kappa", "Keepo"], emotes);
+    assert_eq!(Some("12"), msg.badges.unwrap().version("subscriber"));
+    assert!(msg.badge_info.is_none());
+}
+
+#[cfg(all(feature = "derive", feature = "time"))]
+#[test]
+fn server_time_tag() {
+    use ircv3_parse::message::ser::ToMessage;
+    use ircv3_parse::time::ServerTime;
+    use ircv3_parse::{FromMessage, ToMessage};
+
+    #[derive(FromMessage, ToMessage)]
+    #[irc(command = "PRIVMSG", crlf)]
+    struct PrivMsg<'a> {
+        #[irc(tag = "time")]
+        at: ServerTime,
+        #[irc(param)]
+        channel: &'a str,
+        #[irc(trailing)]
+        message: &'a str,
+    }
+
+    let input = "@time=2024-01-01T12:34:56.7Z PRIVMSG #c :hi";
+    let msg: PrivMsg = ircv3_parse::from_str(input).unwrap();
+    assert_eq!((12, 34, 56, 700), msg.at.time());
+
+    assert_eq!(
+        "@time=2024-01-01T12:34:56.700Z PRIVMSG #c :hi\r\n",
+        msg.to_bytes().unwrap()
+    );
+    assert!(ircv3_parse::from_str::<PrivMsg>("@time=never PRIVMSG #c :hi").is_err());
+}
@@ -19,7 +19,7 @@ proptest! {
         let msg = test_message.to_string();
         let TestMessage {tags, source, command, params, line_ending} = test_message;
         let result = ircv3_parse::parse(&msg).unwrap();
-
+        prop_assert!(result.validate().is_ok());
 
         if let Some(tags) = tags {
             let actual_tags = result.tags();